@@ -0,0 +1,179 @@
+use super::{quad::QuadMesh, Idx};
+use crate::prelude::{
+    Color, DefaultVertex, Frame, GrowableVertexBuffer, IndexBuffer, Target, TexturePosition,
+    Vertex, VertexBuffer,
+};
+use bytemuck::{Pod, Zeroable};
+use glam::{Vec2, Vec4};
+use std::collections::BinaryHeap;
+
+//
+
+/// Per instance data of [`InstancedQuadRenderer`]
+///
+/// Uses shader locations 3 to 8
+/// next to the unit quad's [`DefaultVertex`].
+#[derive(Debug, Clone, Copy, PartialEq, Default, Zeroable, Pod, Vertex)]
+#[repr(C)]
+#[vertex(crate = "crate", instance, location = 3)]
+#[cfg_attr(
+    test,
+    vertex(
+        wgsl = "../srs2dge-res/res/shader/instanced_2d.wgsl",
+        input = "InstanceInput"
+    )
+)]
+pub struct QuadInstance {
+    pos: Vec2,
+    size: Vec2,
+    col: Color,
+    tex: Vec4,
+    pivot: Vec2,
+    rotation: f32,
+    #[vertex(skip)]
+    _pad: f32,
+}
+
+/// Draws quads by instancing a single
+/// unit quad with per instance data
+///
+/// Shaders for it are built with
+/// `(DefaultVertex, QuadInstance)` vertices.
+#[derive(Debug)]
+pub struct InstancedQuadRenderer {
+    quad: VertexBuffer<DefaultVertex>,
+    ibo: IndexBuffer<u32>,
+    instances: GrowableVertexBuffer<QuadInstance>,
+
+    modified: bool,
+    free: BinaryHeap<usize>,
+    used: Vec<Option<QuadMesh>>,
+}
+
+//
+
+impl QuadInstance {
+    pub fn new(pos: Vec2, size: Vec2, col: Color, tex: TexturePosition) -> Self {
+        Self {
+            pos,
+            size,
+            col,
+            tex: tex.to_vec4(),
+            pivot: Vec2::ZERO,
+            rotation: 0.0,
+            _pad: 0.0,
+        }
+    }
+
+    /// Same as [`QuadMesh::with_rotation`]
+    pub fn with_rotation(mut self, rotation: f32, pivot: Vec2) -> Self {
+        self.rotation = rotation;
+        self.pivot = pivot;
+        self
+    }
+}
+
+impl From<QuadMesh> for QuadInstance {
+    fn from(quad: QuadMesh) -> Self {
+        Self::new(quad.pos, quad.size, quad.col, quad.tex).with_rotation(quad.rotation, quad.pivot)
+    }
+}
+
+impl InstancedQuadRenderer {
+    pub fn new(target: &Target) -> Self {
+        // uv.y is flipped like in `QuadMesh`
+        let col = Color::WHITE;
+        let quad = [
+            DefaultVertex::new(Vec2::new(0.0, 0.0), col, Vec2::new(0.0, 1.0)),
+            DefaultVertex::new(Vec2::new(0.0, 1.0), col, Vec2::new(0.0, 0.0)),
+            DefaultVertex::new(Vec2::new(1.0, 0.0), col, Vec2::new(1.0, 1.0)),
+            DefaultVertex::new(Vec2::new(1.0, 1.0), col, Vec2::new(1.0, 0.0)),
+        ];
+
+        Self {
+            quad: VertexBuffer::new_with(target, &quad),
+            ibo: IndexBuffer::new_with(target, &[0, 1, 2, 3]),
+            instances: GrowableVertexBuffer::new(target),
+
+            modified: false,
+            free: Default::default(),
+            used: Default::default(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.modified = true;
+        self.free.clear();
+        self.used.clear();
+    }
+
+    pub fn push_with(&mut self, quad: QuadMesh) -> Idx {
+        self.modified = true;
+        let spot = if let Some(spot) = self.free.pop() {
+            self.used[spot] = Some(quad);
+            spot
+        } else {
+            let spot = self.used.len();
+            self.used.push(Some(quad));
+            spot
+        };
+        Idx(spot)
+    }
+
+    pub fn push(&mut self) -> Idx {
+        self.push_with(Default::default())
+    }
+
+    pub fn drop(&mut self, idx: Idx) {
+        if let Some(m) = self.used.get_mut(idx.0) {
+            *m = None;
+        }
+        self.modified = true;
+        self.free.push(idx.0);
+    }
+
+    pub fn get(&self, idx: Idx) -> Option<&QuadMesh> {
+        self.used.get(idx.0)?.as_ref()
+    }
+
+    pub fn get_mut(&mut self, idx: Idx) -> Option<&mut QuadMesh> {
+        let quad = self.used.get_mut(idx.0)?.as_mut()?;
+        self.modified = true;
+        Some(quad)
+    }
+
+    /// Returns the unit quad, the instances,
+    /// the indices and the instance count
+    ///
+    /// Draw with `draw_indexed(0..4, 0, 0..instance_count)`.
+    pub fn generate(
+        &mut self,
+        target: &mut Target,
+        frame: &mut Frame,
+    ) -> (
+        &'_ VertexBuffer<DefaultVertex>,
+        &'_ VertexBuffer<QuadInstance>,
+        &'_ IndexBuffer<u32>,
+        u32,
+    ) {
+        if self.modified {
+            self.modified = false;
+
+            // dropped quads stay in place as empty instances
+            let new_data: Vec<QuadInstance> = self
+                .used
+                .iter()
+                .map(|quad| quad.map(QuadInstance::from).unwrap_or_default())
+                .collect();
+
+            self.instances.upload(target, frame, &new_data);
+        }
+
+        (
+            &self.quad,
+            &self.instances,
+            &self.ibo,
+            self.used.len() as u32,
+        )
+    }
+}
@@ -4,8 +4,18 @@ use wgpu::PrimitiveTopology;
 //
 
 pub trait Mesh<V = DefaultVertex> {
+    /// Topology of the shaders drawing this mesh
+    ///
+    /// Batched meshes are drawn in one call, strip
+    /// topologies would connect them to each other.
     const PRIM: PrimitiveTopology;
 
+    /// Vertex count shared by every mesh of this type
+    ///
+    /// Lets `BatchRenderer` give each mesh a stable
+    /// vertex offset and upload only the modified ones.
+    const FIXED_VERTICES: Option<u32> = None;
+
     type VertexIter: Iterator<Item = V>;
     type IndexIter: Iterator<Item = u32>;
 
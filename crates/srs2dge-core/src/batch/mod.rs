@@ -1,7 +1,16 @@
 use self::{mesh::Mesh, quad::QuadMesh};
-use crate::prelude::{DefaultVertex, Frame, IndexBuffer, Target, Vertex, VertexBuffer};
+use crate::prelude::{
+    DefaultVertex, Frame, GrowableIndexBuffer, GrowableVertexBuffer, IndexBuffer, Target, Vertex,
+    VertexBuffer,
+};
 use serde::{Deserialize, Serialize};
-use std::{collections::BinaryHeap, marker::PhantomData};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeSet, BinaryHeap},
+    marker::PhantomData,
+    mem,
+    ops::Range,
+};
 
 //
 
@@ -9,33 +18,85 @@ pub use wgpu::PrimitiveTopology;
 
 //
 
+pub mod instanced;
 pub mod mesh;
 pub mod prelude;
 pub mod quad;
+pub mod textured;
 
 //
 
+/// Batches meshes into a single vertex and index buffer
+///
+/// Meshes with [`Mesh::FIXED_VERTICES`] get stable
+/// vertex offsets. Only the modified meshes are uploaded
+/// and the index buffer is only regenerated when
+/// meshes are pushed, dropped or reordered.
+///
+/// Meshes are drawn in the order given by
+/// their [`DrawKey`]s and the [`SortMode`].
 #[derive(Debug)]
 pub struct BatchRenderer<M = QuadMesh, V = DefaultVertex>
 where
     M: Mesh<V>,
     V: Vertex + Copy,
 {
-    vbo: VertexBuffer<V>,
-    ibo: IndexBuffer<u32>,
+    vbo: GrowableVertexBuffer<V>,
+    ibo: GrowableIndexBuffer<u32>,
     ibo_regen: bool,
     ibo_len: u32,
+    texture_ranges: Vec<(u32, Range<u32>)>,
 
-    modified: bool, /* HashSet<usize> */
+    // first modified slot of variable sized meshes,
+    // the vertices after it are uploaded again
+    modified: Option<usize>,
+    dirty: BTreeSet<usize>,
     free: BinaryHeap<usize>,
     used: Vec<Option<M>>,
 
+    // draw key and insertion order of every slot
+    keys: Vec<(DrawKey, u64)>,
+    next_order: u64,
+    sort: SortMode,
+
     _p: PhantomData<M>,
 }
 
 #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
 pub struct Idx(usize);
 
+/// Draw order of a mesh in a [`BatchRenderer`]
+///
+/// Meshes on higher layers are drawn on top of lower
+/// layers. `z` and `texture` order meshes
+/// within a layer depending on the [`SortMode`].
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct DrawKey {
+    pub layer: i32,
+    pub z: f32,
+    pub texture: u32,
+}
+
+/// How a [`BatchRenderer`] orders its meshes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SortMode {
+    /// Draw in the order the meshes were pushed
+    #[default]
+    Insertion,
+
+    /// Draw by layer and then by z, highest first,
+    /// for correct overlap of alpha blended meshes
+    ///
+    /// Higher z is further away, like with a
+    /// `CompareFunction::Less` depth test
+    /// and a depth cleared to 1.0.
+    BackToFront,
+
+    /// Draw by layer and then group by texture
+    /// to minimize bind group changes
+    Texture,
+}
+
 //
 
 impl<M, V> BatchRenderer<M, V>
@@ -45,15 +106,21 @@ where
 {
     pub fn new(target: &Target) -> Self {
         Self {
-            vbo: VertexBuffer::new(target, 0),
-            ibo: IndexBuffer::new(target, 0),
+            vbo: GrowableVertexBuffer::new(target),
+            ibo: GrowableIndexBuffer::new(target),
             ibo_regen: false,
             ibo_len: 0,
+            texture_ranges: vec![],
 
-            modified: Default::default(),
+            modified: None,
+            dirty: Default::default(),
             free: Default::default(),
             used: Default::default(),
 
+            keys: Default::default(),
+            next_order: 0,
+            sort: SortMode::default(),
+
             _p: Default::default(),
         }
     }
@@ -61,22 +128,34 @@ where
     pub fn clear(&mut self) {
         self.ibo_regen = true;
         self.ibo_len = 0;
-        self.modified = false; //.clear();
+        self.texture_ranges.clear();
+        self.modified = None;
+        self.dirty.clear();
         self.free.clear();
         self.used.clear();
+        self.keys.clear();
+        self.vbo.clear();
     }
 
     pub fn push_with(&mut self, mesh: M) -> Idx {
+        self.push_with_key(mesh, DrawKey::default())
+    }
+
+    pub fn push_with_key(&mut self, mesh: M, key: DrawKey) -> Idx {
         self.ibo_regen = true;
+        let order = self.next_order;
+        self.next_order += 1;
         let spot = if let Some(spot) = self.free.pop() {
             self.used[spot] = Some(mesh);
+            self.keys[spot] = (key, order);
             spot
         } else {
             let spot = self.used.len();
             self.used.push(Some(mesh));
+            self.keys.push((key, order));
             spot
         };
-        self.modified = true; //.insert(spot);
+        self.mark_dirty(spot);
         Idx(spot)
     }
 
@@ -88,12 +167,15 @@ where
     }
 
     pub fn drop(&mut self, idx: Idx) {
-        self.ibo_regen = true;
-        if let Some(m) = self.used.get_mut(idx.0) {
+        if let Some(m @ Some(_)) = self.used.get_mut(idx.0) {
             *m = None;
+            self.ibo_regen = true;
+            // the vertices of fixed size meshes can stay
+            if M::FIXED_VERTICES.is_none() {
+                self.mark_modified(idx.0);
+            }
+            self.free.push(idx.0);
         }
-        self.modified = true; //.insert(idx.0);
-        self.free.push(idx.0);
     }
 
     pub fn get(&self, idx: Idx) -> Option<&M> {
@@ -105,72 +187,375 @@ where
     }
 
     pub fn get_mut(&mut self, idx: Idx) -> Option<&mut M> {
+        if let Some(Some(_)) = self.used.get(idx.0) {
+            self.mark_dirty(idx.0);
+        }
         if let Some(Some(mesh)) = self.used.get_mut(idx.0) {
-            self.modified = true; //.insert(idx.0);
             Some(mesh)
         } else {
             None
         }
     }
 
+    pub fn get_key(&self, idx: Idx) -> Option<DrawKey> {
+        self.get(idx)?;
+        Some(self.keys[idx.0].0)
+    }
+
+    /// Move the mesh at `idx` in the draw order
+    pub fn set_key(&mut self, idx: Idx, key: DrawKey) {
+        if self.get_key(idx).is_some_and(|old| old != key) {
+            self.keys[idx.0].0 = key;
+            self.ibo_regen = true;
+        }
+    }
+
+    pub fn set_sort_mode(&mut self, sort: SortMode) {
+        if self.sort != sort {
+            self.sort = sort;
+            self.ibo_regen = true;
+        }
+    }
+
+    pub fn get_sort_mode(&self) -> SortMode {
+        self.sort
+    }
+
     pub fn generate(
         &mut self,
         target: &mut Target,
         frame: &mut Frame,
     ) -> (&'_ VertexBuffer<V>, &'_ IndexBuffer<u32>, u32) {
+        match M::FIXED_VERTICES {
+            Some(step) => self.generate_fixed(target, frame, step),
+            None => self.generate_all(target, frame),
+        }
+
+        self.buffers()
+    }
+
+    /// Buffers and index count from the last [`Self::generate`]
+    pub fn buffers(&self) -> (&'_ VertexBuffer<V>, &'_ IndexBuffer<u32>, u32) {
+        (&self.vbo, &self.ibo, self.ibo_len)
+    }
+
+    /// Index ranges of consecutive meshes
+    /// sharing the same [`DrawKey::texture`]
+    ///
+    /// Up to date after [`Self::generate`].
+    pub fn texture_ranges(&self) -> &[(u32, Range<u32>)] {
+        &self.texture_ranges
+    }
+
+    fn upload_indices<F>(&mut self, target: &mut Target, frame: &mut Frame, offset: F)
+    where
+        F: Fn(usize) -> u32,
+    {
+        let mut new_data = vec![];
+        self.texture_ranges.clear();
+        for slot in self.draw_order() {
+            let start = new_data.len() as u32;
+            new_data.extend(self.used[slot].as_ref().unwrap().indices(offset(slot)));
+            let end = new_data.len() as u32;
+
+            let texture = self.keys[slot].0.texture;
+            match self.texture_ranges.last_mut() {
+                Some((last, range)) if *last == texture => range.end = end,
+                _ => self.texture_ranges.push((texture, start..end)),
+            }
+        }
+        self.ibo_len = new_data.len() as _;
+
+        self.ibo.upload(target, frame, &new_data);
+    }
+
+    /// Used slots in draw order
+    fn draw_order(&self) -> Vec<usize> {
+        let slots = self
+            .keys
+            .iter()
+            .enumerate()
+            .filter(|(slot, _)| self.used[*slot].is_some())
+            .map(|(slot, (key, order))| (slot, *key, *order));
+        draw_order(slots, self.sort)
+    }
+
+    fn mark_dirty(&mut self, slot: usize) {
+        if M::FIXED_VERTICES.is_some() {
+            self.dirty.insert(slot);
+        } else {
+            // every mesh after this one might move
+            self.ibo_regen = true;
+            self.mark_modified(slot);
+        }
+    }
+
+    fn mark_modified(&mut self, slot: usize) {
+        self.modified = Some(self.modified.map_or(slot, |first| first.min(slot)));
+    }
+
+    /// Mesh `i` always starts at vertex `i * step`
+    fn generate_fixed(&mut self, target: &mut Target, frame: &mut Frame, step: u32) {
         if self.ibo_regen {
-            let mut i = 0;
-            let new_data: Vec<u32> = self
-                .used
+            self.ibo_regen = false;
+            self.upload_indices(target, frame, |slot| slot as u32 * step);
+        }
+
+        let used = &self.used;
+        let dirty = mem::take(&mut self.dirty);
+        let dirty = dirty.into_iter().filter(|&slot| used[slot].is_some());
+        for range in dirty_ranges(dirty) {
+            let offset = range.start * step as usize;
+            let new_data: Vec<V> = used[range]
                 .iter()
-                .filter_map(|m| m.as_ref())
-                .flat_map(|m| {
-                    let offset = i;
-                    i += m.index_step();
-                    m.indices(offset)
-                })
+                .flat_map(|m| m.as_ref().unwrap().vertices())
                 .collect();
-            self.ibo_len = new_data.len() as _;
 
-            // TODO: Copy old data instead of regenerating it
-            if self.ibo.capacity() >= new_data.len() {
-                self.ibo.upload(target, frame, &new_data);
-            } else {
-                self.ibo = IndexBuffer::new_with(target, &new_data);
+            self.vbo.upload_at(target, frame, offset, &new_data);
+        }
+    }
+
+    fn generate_all(&mut self, target: &mut Target, frame: &mut Frame) {
+        if self.ibo_regen {
+            self.ibo_regen = false;
+            // vertices are packed in slot order
+            let mut offsets = vec![0; self.used.len()];
+            let mut i = 0;
+            for (slot, m) in self.used.iter().enumerate() {
+                if let Some(m) = m {
+                    offsets[slot] = i;
+                    i += m.index_step();
+                }
             }
+
+            self.upload_indices(target, frame, |slot| offsets[slot]);
         }
 
-        if self.modified {
-            let new_data: Vec<V> = self
-                .used
+        if let Some(first) = self.modified.take() {
+            // the meshes before `first` keep their vertices
+            let offset = self.used[..first]
+                .iter()
+                .filter_map(|m| m.as_ref())
+                .map(|m| m.index_step() as usize)
+                .sum();
+            let new_data: Vec<V> = self.used[first..]
                 .iter()
                 .filter_map(|m| m.as_ref())
                 .flat_map(|s| s.vertices())
                 .collect();
 
-            if self.vbo.capacity() >= new_data.len() {
-                self.vbo.upload(target, frame, &new_data);
-            } else {
-                self.vbo = VertexBuffer::new_with(target, &new_data);
-            }
+            self.vbo.truncate(offset);
+            self.vbo.extend(target, frame, &new_data);
+        }
+    }
+}
 
-            // TODO: Copy old data instead of regenerating it
-            /* if self.max * M::VERTICES >= self.vbo.len() {
-                self.vbo = VertexBuffer::new_with(target, &new_data);
+//
 
-                /* let new = VertexBuffer::empty_dynamic(facade, self.max * M::VERTICES * 2).unwrap();
-                self.vbo.copy_to(&new).unwrap();
-                self.vbo = new; */
+/// Sort `(slot, key, insertion order)` by `sort`
+fn draw_order<I>(slots: I, sort: SortMode) -> Vec<usize>
+where
+    I: IntoIterator<Item = (usize, DrawKey, u64)>,
+{
+    let mut slots: Vec<_> = slots.into_iter().collect();
+    slots.sort_unstable_by(|(_, a, a_order), (_, b, b_order)| {
+        match sort {
+            SortMode::Insertion => Ordering::Equal,
+            SortMode::BackToFront => a.layer.cmp(&b.layer).then(b.z.total_cmp(&a.z)),
+            SortMode::Texture => a.layer.cmp(&b.layer).then(a.texture.cmp(&b.texture)),
+        }
+        .then(a_order.cmp(b_order))
+    });
+    slots.into_iter().map(|(slot, _, _)| slot).collect()
+}
+
+/// Merge sorted slot indices into contiguous ranges
+fn dirty_ranges<I>(slots: I) -> Vec<Range<usize>>
+where
+    I: IntoIterator<Item = usize>,
+{
+    let mut ranges: Vec<Range<usize>> = vec![];
+    for slot in slots {
+        match ranges.last_mut() {
+            Some(last) if last.end == slot => last.end += 1,
+            _ => ranges.push(slot..slot + 1),
+        }
+    }
+    ranges
+}
+
+//
+
+#[cfg(test)]
+mod test {
+    use super::{dirty_ranges, draw_order, DrawKey, SortMode};
+    #[cfg(feature = "testing")]
+    use crate::{
+        batch::{mesh::Mesh, quad::QuadMesh, BatchRenderer},
+        buffer::DefaultVertex,
+        color::Color,
+        shader::{module::ShaderModule, Shader},
+        testing::{
+            fixture::{quad, SHADER},
+            GoldenImage,
+        },
+        texture::pos::TexturePosition,
+    };
+    #[cfg(feature = "testing")]
+    use glam::Vec2;
+    #[cfg(feature = "testing")]
+    use wgpu::{
+        CompareFunction, DepthStencilState, PipelineLayoutDescriptor, PrimitiveTopology,
+        TextureFormat,
+    };
+
+    #[test]
+    fn merge_dirty_ranges() {
+        assert_eq!(dirty_ranges([]), []);
+        assert_eq!(dirty_ranges([0, 1, 2, 5, 7, 8]), [0..3, 5..6, 7..9]);
+    }
+
+    #[test]
+    fn sort_modes() {
+        let key = |layer, z, texture| DrawKey { layer, z, texture };
+        // slots reused out of insertion order
+        let slots = [
+            (0, key(1, 0.0, 0), 3),
+            (1, key(0, 0.5, 1), 0),
+            (2, key(0, 0.8, 0), 2),
+            (3, key(0, 0.5, 0), 1),
+        ];
+
+        assert_eq!(draw_order(slots, SortMode::Insertion), [1, 3, 2, 0]);
+        assert_eq!(draw_order(slots, SortMode::BackToFront), [2, 1, 3, 0]);
+        assert_eq!(draw_order(slots, SortMode::Texture), [3, 2, 1, 0]);
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn batch_renderer() {
+        GoldenImage::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/golden/batch_renderer.png"
+        ))
+        .check_blocking(|target, frame| {
+            let module = ShaderModule::new_wgsl_source(target, SHADER.into()).unwrap();
+            let shader: Shader<DefaultVertex, u32> = Shader::builder()
+                .with_vertex(&module, "vs_main")
+                .with_fragment(&module, "fs_main")
+                .with_format(target.get_format())
+                .with_baked_layout(PipelineLayoutDescriptor::default())
+                .with_topology(QuadMesh::PRIM)
+                .build(target);
+
+            let quad = |x: f32, col: Color| {
+                QuadMesh::new_centered(
+                    Vec2::new(x, 0.0),
+                    Vec2::new(0.4, 0.4),
+                    col,
+                    TexturePosition::default(),
+                )
+            };
+            let mut batcher = BatchRenderer::new(target);
+            let a = batcher.push_with(quad(-0.6, Color::RED));
+            let b = batcher.push_with(quad(0.0, Color::GREEN));
+            batcher.push_with(quad(0.6, Color::BLUE));
+            batcher.generate(target, frame);
+
+            // only `a` gets uploaded again, `b` stays in the
+            // vertex buffer but is no longer indexed
+            let a = batcher.get_mut(a).unwrap();
+            a.pos.y = 0.4;
+            a.col = Color::ORANGE;
+            batcher.drop(b);
+            let (vbo, ibo, count) = batcher.generate(target, frame);
+
+            frame
+                .primary_render_pass()
+                .bind_vbo(vbo)
+                .bind_ibo(ibo)
+                .bind_shader(&shader)
+                .draw_indexed(0..count, 0, 0..1);
+        })
+        .unwrap();
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn batch_back_to_front() {
+        struct DepthQuad([DefaultVertex; 4]);
+
+        impl Mesh for DepthQuad {
+            const PRIM: PrimitiveTopology = PrimitiveTopology::TriangleList;
+
+            type VertexIter = std::array::IntoIter<DefaultVertex, 4>;
+            type IndexIter = std::array::IntoIter<u32, 6>;
+
+            fn vertices(&self) -> Self::VertexIter {
+                self.0.into_iter()
             }
 
-            let mut map = self.vbo.map_write();
-            for modified in self.modified.drain() {
-                for (i, vert) in self.used[modified].vertices().enumerate() {
-                    map.set(modified * M::VERTICES + i, vert);
-                }
-            } */
+            fn indices(&self, offset: u32) -> Self::IndexIter {
+                [
+                    offset,
+                    offset + 1,
+                    offset + 2,
+                    offset + 2,
+                    offset + 1,
+                    offset + 3,
+                ]
+                .into_iter()
+            }
+
+            fn index_step(&self) -> u32 {
+                4
+            }
         }
 
-        (&self.vbo, &self.ibo, self.ibo_len)
+        let format = TextureFormat::Depth32Float;
+        GoldenImage::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/golden/back_to_front.png"
+        ))
+        .with_depth_stencil(Some(format))
+        .check_blocking(|target, frame| {
+            let module = ShaderModule::new_wgsl_source(target, SHADER.into()).unwrap();
+            let shader: Shader<DefaultVertex, u32> = Shader::builder()
+                .with_vertex(&module, "vs_main")
+                .with_fragment(&module, "fs_main")
+                .with_format(target.get_format())
+                .with_baked_layout(PipelineLayoutDescriptor::default())
+                .with_topology(DepthQuad::PRIM)
+                .with_depth_stencil(Some(DepthStencilState {
+                    format,
+                    depth_write_enabled: true,
+                    depth_compare: CompareFunction::Less,
+                    stencil: Default::default(),
+                    bias: Default::default(),
+                }))
+                .build(target);
+
+            // the near quad is pushed first, drawing it first would
+            // hide the far quad behind it instead of blending over it
+            let mut batcher = BatchRenderer::<DepthQuad>::new(target);
+            batcher.set_sort_mode(SortMode::BackToFront);
+            let near = DepthQuad(quad(-0.2, 0.2, Color::new(1.0, 0.0, 0.0, 0.5)));
+            let far = DepthQuad(quad(0.2, 0.8, Color::new(0.0, 0.0, 1.0, 0.5)));
+            let key = |z| DrawKey {
+                z,
+                ..Default::default()
+            };
+            batcher.push_with_key(near, key(0.2));
+            batcher.push_with_key(far, key(0.8));
+            let (vbo, ibo, count) = batcher.generate(target, frame);
+
+            frame
+                .primary_render_pass()
+                .bind_vbo(vbo)
+                .bind_ibo(ibo)
+                .bind_shader(&shader)
+                .draw_indexed(0..count, 0, 0..1);
+        })
+        .unwrap();
     }
 }
@@ -1 +1 @@
-pub use super::{mesh::*, quad::*, *};
+pub use super::{instanced::*, mesh::*, quad::*, textured::*, *};
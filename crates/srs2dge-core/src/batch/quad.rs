@@ -3,18 +3,38 @@ use crate::{
     prelude::{DefaultVertex, Mesh, TexturePosition},
     util::RemapRange,
 };
-use glam::{Vec2, Vec3, Vec3Swizzles, Vec4, Vec4Swizzles};
+use glam::{Mat3, Vec2, Vec3, Vec3Swizzles, Vec4, Vec4Swizzles};
 use std::array::IntoIter;
 use wgpu::PrimitiveTopology;
 
 //
 
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
 pub struct QuadMesh {
     pub pos: Vec2,
     pub size: Vec2,
     pub col: Color,
     pub tex: TexturePosition,
+
+    /// counter clockwise rotation in radians
+    pub rotation: f32,
+    /// point the quad rotates around,
+    /// `(0, 0)` is `pos` and `(1, 1)` is `pos + size`
+    ///
+    /// Depends on the constructor: [`Self::new_top_left`]
+    /// and `Default` use `(0, 0)`, [`Self::new_centered`]
+    /// uses `(0.5, 0.5)`.
+    pub pivot: Vec2,
+}
+
+/// Unit quad mapped through an affine transform
+///
+/// For rotated, skewed and mirrored quads.
+#[derive(Debug, Clone, Copy)]
+pub struct TransformedQuadMesh {
+    pub transform: Mat3,
+    pub col: Color,
+    pub tex: TexturePosition,
 }
 
 #[derive(Debug, Clone, Copy, Default)]
@@ -29,28 +49,61 @@ pub struct IsoQuadMesh {
 //
 
 impl QuadMesh {
+    /// `pos` is the `(0, 0)` corner,
+    /// rotates around that corner
     pub fn new_top_left(pos: Vec2, size: Vec2, col: Color, tex: TexturePosition) -> Self {
         Self {
             pos,
             size,
             col,
             tex,
+            rotation: 0.0,
+            pivot: Vec2::ZERO,
         }
     }
 
+    /// `pos` is the center,
+    /// rotates around the center
     pub fn new_centered(pos: Vec2, size: Vec2, col: Color, tex: TexturePosition) -> Self {
         Self {
             pos: pos - size * 0.5,
             size,
             col,
             tex,
+            rotation: 0.0,
+            pivot: Vec2::splat(0.5),
         }
     }
 
+    /// Overrides the constructor's pivot
+    pub fn with_rotation(mut self, rotation: f32, pivot: Vec2) -> Self {
+        self.rotation = rotation;
+        self.pivot = pivot;
+        self
+    }
+
+    /// `pos` + `size` + `rotation` as a [`TransformedQuadMesh`] transform
+    pub fn transform(&self) -> Mat3 {
+        let pivot = self.pos + self.size * self.pivot;
+        Mat3::from_translation(pivot)
+            * Mat3::from_angle(self.rotation)
+            * Mat3::from_translation(self.pos - pivot)
+            * Mat3::from_scale(self.size)
+    }
+
     /// remove those quads that are outside of the bounds
     ///
     /// and 'cut' those quads that are touching the bounds
+    /// rotated quads are only removed, not cut
     pub fn clip(self, bounds_min: Vec2, bounds_max: Vec2) -> Option<Self> {
+        if self.rotation != 0.0 {
+            let (min, max) = self.vertices().fold(
+                (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+                |(min, max), v| (min.min(v.pos()), max.max(v.pos())),
+            );
+            return (max.cmpge(bounds_min).all() && min.cmplt(bounds_max).all()).then_some(self);
+        }
+
         // discard quads outside of the bounding box
         if (self.pos + self.size).cmplt(bounds_min).any() || self.pos.cmpge(bounds_max).any() {
             return None;
@@ -84,12 +137,17 @@ impl QuadMesh {
 }
 
 impl Mesh<DefaultVertex> for QuadMesh {
-    const PRIM: PrimitiveTopology = PrimitiveTopology::TriangleStrip;
+    const PRIM: PrimitiveTopology = PrimitiveTopology::TriangleList;
+    const FIXED_VERTICES: Option<u32> = Some(4);
 
     type VertexIter = IntoIter<DefaultVertex, 4>;
-    type IndexIter = IntoIter<u32, 5>;
+    type IndexIter = IntoIter<u32, 6>;
 
     fn vertices(&self) -> Self::VertexIter {
+        if self.rotation != 0.0 {
+            return TransformedQuadMesh::from(*self).vertices();
+        }
+
         let top_left = self.pos;
         let bottom_right = self.pos + self.size;
         let p = Vec4::new(top_left.x, top_left.y, bottom_right.x, bottom_right.y);
@@ -108,7 +166,56 @@ impl Mesh<DefaultVertex> for QuadMesh {
     }
 
     fn indices(&self, offset: u32) -> Self::IndexIter {
-        IntoIterator::into_iter([offset, offset + 1, offset + 2, offset + 3, !0])
+        quad_indices(offset)
+    }
+
+    fn index_step(&self) -> u32 {
+        4
+    }
+}
+
+impl TransformedQuadMesh {
+    pub fn new(transform: Mat3, col: Color, tex: TexturePosition) -> Self {
+        Self {
+            transform,
+            col,
+            tex,
+        }
+    }
+}
+
+impl From<QuadMesh> for TransformedQuadMesh {
+    fn from(quad: QuadMesh) -> Self {
+        Self::new(quad.transform(), quad.col, quad.tex)
+    }
+}
+
+impl Mesh<DefaultVertex> for TransformedQuadMesh {
+    const PRIM: PrimitiveTopology = PrimitiveTopology::TriangleList;
+    const FIXED_VERTICES: Option<u32> = Some(4);
+
+    type VertexIter = IntoIter<DefaultVertex, 4>;
+    type IndexIter = IntoIter<u32, 6>;
+
+    fn vertices(&self) -> Self::VertexIter {
+        // same corner and uv order as `QuadMesh`
+        let p = |x: f32, y: f32| self.transform.transform_point2(Vec2::new(x, y));
+        let c = Vec4::new(
+            self.tex.top_left.x,
+            self.tex.bottom_right.y,
+            self.tex.bottom_right.x,
+            self.tex.top_left.y,
+        );
+        IntoIterator::into_iter([
+            DefaultVertex::new(p(0.0, 0.0), self.col, c.xy()),
+            DefaultVertex::new(p(0.0, 1.0), self.col, c.xw()),
+            DefaultVertex::new(p(1.0, 0.0), self.col, c.zy()),
+            DefaultVertex::new(p(1.0, 1.0), self.col, c.zw()),
+        ])
+    }
+
+    fn indices(&self, offset: u32) -> Self::IndexIter {
+        quad_indices(offset)
     }
 
     fn index_step(&self) -> u32 {
@@ -117,10 +224,11 @@ impl Mesh<DefaultVertex> for QuadMesh {
 }
 
 impl Mesh<DefaultVertex> for IsoQuadMesh {
-    const PRIM: PrimitiveTopology = PrimitiveTopology::TriangleStrip;
+    const PRIM: PrimitiveTopology = PrimitiveTopology::TriangleList;
+    const FIXED_VERTICES: Option<u32> = Some(4);
 
     type VertexIter = IntoIter<DefaultVertex, 4>;
-    type IndexIter = IntoIter<u32, 5>;
+    type IndexIter = IntoIter<u32, 6>;
 
     fn vertices(&self) -> Self::VertexIter {
         let c = Vec3::new(0.0, 0.5, 1.0);
@@ -133,7 +241,7 @@ impl Mesh<DefaultVertex> for IsoQuadMesh {
     }
 
     fn indices(&self, offset: u32) -> Self::IndexIter {
-        IntoIterator::into_iter([offset, offset + 1, offset + 2, offset + 3, !0])
+        quad_indices(offset)
     }
 
     fn index_step(&self) -> u32 {
@@ -141,4 +249,50 @@ impl Mesh<DefaultVertex> for IsoQuadMesh {
     }
 }
 
+// two triangles, so batched quads don't
+// depend on primitive restart
+fn quad_indices(offset: u32) -> IntoIter<u32, 6> {
+    IntoIterator::into_iter([
+        offset,
+        offset + 1,
+        offset + 2,
+        offset + 2,
+        offset + 1,
+        offset + 3,
+    ])
+}
+
 //
+
+#[cfg(test)]
+mod test {
+    use super::{QuadMesh, TransformedQuadMesh};
+    use crate::prelude::{Color, Mesh, TexturePosition};
+    use glam::Vec2;
+    use std::f32::consts::FRAC_PI_2;
+
+    #[test]
+    fn rotated_quad() {
+        let quad = QuadMesh::new_centered(
+            Vec2::new(1.0, 1.0),
+            Vec2::new(2.0, 1.0),
+            Color::WHITE,
+            TexturePosition::default(),
+        );
+        let unrotated: Vec<_> = quad.vertices().collect();
+        let transformed: Vec<_> = TransformedQuadMesh::from(quad).vertices().collect();
+        for (a, b) in unrotated.iter().zip(transformed.iter()) {
+            assert!(a.pos().abs_diff_eq(b.pos(), 1e-5));
+            assert_eq!(a.uv(), b.uv());
+        }
+
+        // a quarter turn around the center makes it 1 wide and 2 tall
+        let rotated: Vec<_> = quad
+            .with_rotation(FRAC_PI_2, Vec2::splat(0.5))
+            .vertices()
+            .map(|v| v.pos())
+            .collect();
+        assert!(rotated[0].abs_diff_eq(Vec2::new(1.5, 0.0), 1e-5));
+        assert!(rotated[3].abs_diff_eq(Vec2::new(0.5, 2.0), 1e-5));
+    }
+}
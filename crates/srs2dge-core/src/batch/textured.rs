@@ -0,0 +1,171 @@
+use super::{mesh::Mesh, quad::QuadMesh, BatchRenderer, DrawKey, Idx, SortMode};
+use crate::prelude::{DefaultVertex, Frame, IndexBuffer, RenderPass, Target, Vertex, VertexBuffer};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use wgpu::BindGroup;
+
+//
+
+/// Texture registered in a [`TextureBatcher`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct TextureHandle(u32);
+
+/// Batches `(mesh, texture)` pairs into
+/// as few draw calls as possible
+///
+/// Every texture is registered with the bind group
+/// that the shader uses to sample it. Meshes are grouped
+/// by texture within each layer and drawn with one
+/// `draw_indexed` per group.
+#[derive(Debug)]
+pub struct TextureBatcher<M = QuadMesh, V = DefaultVertex>
+where
+    M: Mesh<V>,
+    V: Vertex + Copy,
+{
+    batcher: BatchRenderer<M, V>,
+    bind_groups: Vec<BindGroup>,
+}
+
+/// Draw calls generated by a [`TextureBatcher`]
+pub struct TextureBatch<'a, V> {
+    vbo: &'a VertexBuffer<V>,
+    ibo: &'a IndexBuffer<u32>,
+    draws: Vec<(Range<u32>, &'a BindGroup)>,
+}
+
+//
+
+impl<M, V> TextureBatcher<M, V>
+where
+    M: Mesh<V>,
+    V: Vertex + Copy,
+{
+    pub fn new(target: &Target) -> Self {
+        let mut batcher = BatchRenderer::new(target);
+        batcher.set_sort_mode(SortMode::Texture);
+        Self {
+            batcher,
+            bind_groups: vec![],
+        }
+    }
+
+    /// Register a texture with the bind group
+    /// used to draw meshes with it
+    pub fn add_texture(&mut self, bind_group: BindGroup) -> TextureHandle {
+        self.bind_groups.push(bind_group);
+        TextureHandle(self.bind_groups.len() as u32 - 1)
+    }
+
+    /// Replace the bind group of `texture`,
+    /// for example after its uniforms changed
+    pub fn set_bind_group(&mut self, texture: TextureHandle, bind_group: BindGroup) {
+        self.bind_groups[texture.0 as usize] = bind_group;
+    }
+
+    pub fn clear(&mut self) {
+        self.batcher.clear();
+    }
+
+    pub fn push_with(&mut self, mesh: M, texture: TextureHandle) -> Idx {
+        self.push_on_layer(mesh, texture, 0)
+    }
+
+    /// Meshes on higher layers are drawn on top
+    pub fn push_on_layer(&mut self, mesh: M, texture: TextureHandle, layer: i32) -> Idx {
+        self.check(texture);
+        self.batcher.push_with_key(
+            mesh,
+            DrawKey {
+                layer,
+                texture: texture.0,
+                ..Default::default()
+            },
+        )
+    }
+
+    pub fn drop(&mut self, idx: Idx) {
+        self.batcher.drop(idx);
+    }
+
+    pub fn get(&self, idx: Idx) -> Option<&M> {
+        self.batcher.get(idx)
+    }
+
+    pub fn get_mut(&mut self, idx: Idx) -> Option<&mut M> {
+        self.batcher.get_mut(idx)
+    }
+
+    pub fn get_texture(&self, idx: Idx) -> Option<TextureHandle> {
+        Some(TextureHandle(self.batcher.get_key(idx)?.texture))
+    }
+
+    pub fn set_texture(&mut self, idx: Idx, texture: TextureHandle) {
+        self.check(texture);
+        if let Some(key) = self.batcher.get_key(idx) {
+            self.batcher.set_key(
+                idx,
+                DrawKey {
+                    texture: texture.0,
+                    ..key
+                },
+            );
+        }
+    }
+
+    pub fn generate(&mut self, target: &mut Target, frame: &mut Frame) -> TextureBatch<'_, V> {
+        self.batcher.generate(target, frame);
+        let (vbo, ibo, _) = self.batcher.buffers();
+        let draws = self
+            .batcher
+            .texture_ranges()
+            .iter()
+            .map(|(texture, range)| (range.clone(), &self.bind_groups[*texture as usize]))
+            .collect();
+
+        TextureBatch { vbo, ibo, draws }
+    }
+
+    fn check(&self, texture: TextureHandle) {
+        if texture.0 as usize >= self.bind_groups.len() {
+            panic!("Texture does not belong to this batcher");
+        }
+    }
+}
+
+impl<'a, V> TextureBatch<'a, V>
+where
+    V: Vertex + 'static,
+{
+    pub fn vbo(&self) -> &'a VertexBuffer<V> {
+        self.vbo
+    }
+
+    pub fn ibo(&self) -> &'a IndexBuffer<u32> {
+        self.ibo
+    }
+
+    /// Index ranges and the bind group to draw each one with
+    pub fn draws(&self) -> &[(Range<u32>, &'a BindGroup)] {
+        &self.draws
+    }
+
+    /// Bind the buffers and replay every draw
+    /// with its bind group at `@group(group)`
+    pub fn draw<'e, Bv, Bi>(
+        &self,
+        pass: RenderPass<'e, V, Bv, u32, Bi, true>,
+        group: u32,
+    ) -> RenderPass<'e, V, V, u32, u32, true>
+    where
+        'a: 'e,
+    {
+        self.draws.iter().fold(
+            pass.bind_vbo(self.vbo).bind_ibo(self.ibo),
+            |pass, (range, bind_group)| {
+                pass.bind_group_at(group, bind_group, &[])
+                    .draw_indexed(range.clone(), 0, 0..1)
+            },
+        )
+    }
+}
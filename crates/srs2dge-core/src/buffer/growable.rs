@@ -0,0 +1,228 @@
+use super::Buffer;
+use crate::prelude::{label, Frame, Target};
+use bytemuck::Pod;
+use std::ops::Deref;
+use wgpu::{util::align_to, BufferDescriptor, BufferUsages, COPY_BUFFER_ALIGNMENT};
+
+//
+
+/// Buffer that grows geometrically when
+/// uploads go past its capacity
+///
+/// The old contents are copied into the new
+/// buffer on the GPU, so only the new data
+/// has to be uploaded. The underlying buffer
+/// always has `COPY_SRC` and `COPY_DST` usages.
+///
+/// Derefs to a [`Buffer`] for drawing.
+#[derive(Debug)]
+pub struct GrowableBuffer<T, const USAGE: u32> {
+    buffer: Buffer<T, USAGE>,
+    len: usize,
+}
+
+//
+
+impl<T, const USAGE: u32> GrowableBuffer<T, USAGE>
+where
+    T: Pod,
+{
+    pub fn new(target: &Target) -> Self {
+        Self::with_capacity(target, 0)
+    }
+
+    pub fn with_capacity(target: &Target, elements: usize) -> Self {
+        Self {
+            buffer: Self::create(target, elements),
+            len: 0,
+        }
+    }
+
+    pub fn new_with(target: &mut Target, frame: &mut Frame, data: &[T]) -> Self {
+        let mut buffer = Self::with_capacity(target, data.len());
+        buffer.upload(target, frame, data);
+        buffer
+    }
+
+    /// Replace the contents with `new_data`
+    ///
+    /// Nothing is copied if the buffer has to grow.
+    pub fn upload(&mut self, target: &mut Target, frame: &mut Frame, new_data: &[T]) {
+        self.len = 0;
+        self.upload_at(target, frame, 0, new_data);
+    }
+
+    /// Write `new_data` starting at the element `offset`
+    ///
+    /// Grows the buffer if needed, elements
+    /// before `offset` are kept.
+    pub fn upload_at(
+        &mut self,
+        target: &mut Target,
+        frame: &mut Frame,
+        offset: usize,
+        new_data: &[T],
+    ) {
+        let end = offset + new_data.len();
+        self.reserve(target, frame, end);
+        self.buffer.upload_at(target, frame, offset as _, new_data);
+        self.len = self.len.max(end);
+    }
+
+    /// Append `new_data` after the current contents
+    pub fn extend(&mut self, target: &mut Target, frame: &mut Frame, new_data: &[T]) {
+        self.upload_at(target, frame, self.len, new_data);
+    }
+
+    /// Make room for at least `elements` elements
+    pub fn reserve(&mut self, target: &Target, frame: &mut Frame, elements: usize) {
+        if self.buffer.capacity() >= elements {
+            return;
+        }
+
+        let capacity = elements.max(self.buffer.capacity() * 2);
+        let new = Self::create(target, capacity);
+        let size = Self::byte_size(self.len);
+        if size != 0 {
+            frame
+                .encoder()
+                .copy_buffer_to_buffer(self.buffer.inner(), 0, new.inner(), 0, size);
+        }
+        self.buffer = new;
+    }
+
+    /// Forget everything after the first `len` elements
+    pub fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Number of elements uploaded
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn create(target: &Target, elements: usize) -> Buffer<T, USAGE> {
+        let buffer = target.device.create_buffer(&BufferDescriptor {
+            label: label!(),
+            size: Self::byte_size(elements),
+            usage: BufferUsages::from_bits_truncate(USAGE)
+                | BufferUsages::COPY_SRC
+                | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Buffer::with_buffer(buffer, elements)
+    }
+
+    /// Size rounded up to what buffer copies accept
+    fn byte_size(elements: usize) -> u64 {
+        let size = Buffer::<T, USAGE>::size_of(elements) as u64;
+        align_to(size, COPY_BUFFER_ALIGNMENT)
+    }
+}
+
+impl<T, const USAGE: u32> Deref for GrowableBuffer<T, USAGE> {
+    type Target = Buffer<T, USAGE>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.buffer
+    }
+}
+
+//
+
+#[cfg(all(test, feature = "testing"))]
+mod test {
+    use crate::{
+        buffer::{DefaultVertex, GrowableVertexBuffer, IndexBuffer},
+        color::Color,
+        label,
+        packer::rect::Rect,
+        shader::{module::ShaderModule, Shader},
+        testing::{
+            fixture::{quad, SHADER},
+            GoldenImage,
+        },
+        Engine,
+    };
+    use wgpu::{
+        BufferDescriptor, BufferUsages, CommandEncoderDescriptor, Maintain, MapMode,
+        PipelineLayoutDescriptor, PrimitiveTopology,
+    };
+
+    #[test]
+    fn grow_keeps_contents() {
+        let engine = Engine::new();
+        let mut target = pollster::block_on(engine.new_target_offscreen(Rect::new(4, 4)));
+
+        let mut frame = target.get_frame();
+        let mut buffer = GrowableVertexBuffer::<u32>::new_with(&mut target, &mut frame, &[1, 2, 3]);
+        assert_eq!(buffer.capacity(), 3);
+        buffer.extend(&mut target, &mut frame, &[4, 5, 6, 7, 8]);
+        target.finish_frame(frame);
+        assert_eq!(buffer.capacity(), 8);
+        assert_eq!(buffer.len(), 8);
+
+        let device = target.get_device();
+        let read = device.create_buffer(&BufferDescriptor {
+            label: label!(),
+            size: 32,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder =
+            device.create_command_encoder(&CommandEncoderDescriptor { label: label!() });
+        encoder.copy_buffer_to_buffer(buffer.inner(), 0, &read, 0, 32);
+        target.queue.submit([encoder.finish()]);
+
+        let range = read.slice(..);
+        range.map_async(MapMode::Read, |res| res.unwrap());
+        device.poll(Maintain::Wait);
+        let contents: Vec<u32> = bytemuck::cast_slice(&range.get_mapped_range()).to_vec();
+        assert_eq!(contents, [1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn growable_buffer() {
+        GoldenImage::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/golden/growable_buffer.png"
+        ))
+        .check_blocking(|target, frame| {
+            let module = ShaderModule::new_wgsl_source(target, SHADER.into()).unwrap();
+            let shader: Shader<DefaultVertex, u32> = Shader::builder()
+                .with_vertex(&module, "vs_main")
+                .with_fragment(&module, "fs_main")
+                .with_topology(PrimitiveTopology::TriangleStrip)
+                .with_format(target.get_format())
+                .with_baked_layout(PipelineLayoutDescriptor::default())
+                .build(target);
+
+            // the second quad grows the buffer, the first one
+            // is only on the GPU and has to be copied over
+            let mut vbo = GrowableVertexBuffer::with_capacity(target, 4);
+            vbo.extend(target, frame, &quad(-0.5, 0.5, Color::RED));
+            vbo.extend(target, frame, &quad(0.5, 0.5, Color::BLUE));
+            assert_eq!(vbo.len(), 8);
+            assert!(vbo.capacity() >= 8);
+            let ibo = IndexBuffer::new_with(target, &[0, 1, 2, 3]);
+
+            frame
+                .primary_render_pass()
+                .bind_vbo(&vbo)
+                .bind_ibo(&ibo)
+                .bind_shader(&shader)
+                .draw_indexed(0..4, 0, 0..1)
+                .draw_indexed(0..4, 4, 0..1);
+        })
+        .unwrap();
+    }
+}
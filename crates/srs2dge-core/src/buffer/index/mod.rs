@@ -1,4 +1,4 @@
-use super::{Buffer, BufferSlice};
+use super::{Buffer, BufferSlice, GrowableBuffer};
 use wgpu::BufferUsages;
 
 //
@@ -17,3 +17,4 @@ const USAGE: u32 = BufferUsages::INDEX.bits() | BufferUsages::COPY_DST.bits();
 
 pub type IndexBuffer<T = DefaultIndex> = Buffer<T, USAGE>;
 pub type IndexBufferSlice<'b, T = DefaultIndex> = BufferSlice<'b, T, USAGE>;
+pub type GrowableIndexBuffer<T = DefaultIndex> = GrowableBuffer<T, USAGE>;
@@ -1 +1,80 @@
+use super::{Buffer, BufferSlice};
+use bytemuck::{Pod, Zeroable};
+use std::ops::Range;
+use wgpu::BufferUsages;
 
+//
+
+const USAGE: u32 = BufferUsages::INDIRECT.bits() | BufferUsages::COPY_DST.bits();
+const STORAGE_USAGE: u32 =
+    BufferUsages::INDIRECT.bits() | BufferUsages::STORAGE.bits() | BufferUsages::COPY_DST.bits();
+
+//
+
+/// Arguments for `ComputePass::dispatch_indirect`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Zeroable, Pod)]
+#[repr(C)]
+pub struct DispatchIndirect {
+    pub x: u32,
+    pub y: u32,
+    pub z: u32,
+}
+
+/// Arguments for `RenderPass::draw_indirect`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Zeroable, Pod)]
+#[repr(C)]
+pub struct DrawIndirect {
+    pub vertex_count: u32,
+    pub instance_count: u32,
+    pub base_vertex: u32,
+    /// non zero values require `Features::INDIRECT_FIRST_INSTANCE`
+    pub base_instance: u32,
+}
+
+/// Arguments for `RenderPass::draw_indexed_indirect`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Zeroable, Pod)]
+#[repr(C)]
+pub struct DrawIndexedIndirect {
+    pub index_count: u32,
+    pub instance_count: u32,
+    pub base_index: u32,
+    pub vertex_offset: i32,
+    /// non zero values require `Features::INDIRECT_FIRST_INSTANCE`
+    pub base_instance: u32,
+}
+
+//
+
+impl DrawIndirect {
+    /// Same arguments as `RenderPass::draw`
+    pub fn new(vertices: Range<u32>, instances: Range<u32>) -> Self {
+        Self {
+            vertex_count: vertices.end - vertices.start,
+            instance_count: instances.end - instances.start,
+            base_vertex: vertices.start,
+            base_instance: instances.start,
+        }
+    }
+}
+
+impl DrawIndexedIndirect {
+    /// Same arguments as `RenderPass::draw_indexed`
+    pub fn new(indices: Range<u32>, base_vertex: i32, instances: Range<u32>) -> Self {
+        Self {
+            index_count: indices.end - indices.start,
+            instance_count: instances.end - instances.start,
+            base_index: indices.start,
+            vertex_offset: base_vertex,
+            base_instance: instances.start,
+        }
+    }
+}
+
+//
+
+pub type IndirectBuffer<T> = Buffer<T, USAGE>;
+pub type IndirectBufferSlice<'b, T> = BufferSlice<'b, T, USAGE>;
+
+/// Indirect buffer that compute shaders can write the arguments to
+pub type IndirectStorageBuffer<T> = Buffer<T, STORAGE_USAGE>;
+pub type IndirectStorageBufferSlice<'b, T> = BufferSlice<'b, T, STORAGE_USAGE>;
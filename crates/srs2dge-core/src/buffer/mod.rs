@@ -12,21 +12,31 @@ use wgpu::{
 
 //
 
+pub use growable::*;
 pub use index::*;
 pub use indirect::*;
+pub use storage::*;
 pub use uniform::*;
 pub use vertex::*;
 
 //
 
+pub mod growable;
 pub mod index;
 pub mod indirect;
 pub mod prelude;
+pub mod storage;
 pub mod uniform;
 pub mod vertex;
 
 //
 
+pub const fn has_usage(usage: u32, flag: BufferUsages) -> bool {
+    BufferUsages::from_bits_truncate(usage).contains(flag)
+}
+
+//
+
 #[derive(Debug)]
 pub struct Buffer<T, const USAGE: u32> {
     buffer: wgpu::Buffer,
@@ -0,0 +1,12 @@
+use super::{Buffer, BufferSlice};
+use wgpu::BufferUsages;
+
+//
+
+const USAGE: u32 =
+    BufferUsages::STORAGE.bits() | BufferUsages::COPY_DST.bits() | BufferUsages::COPY_SRC.bits();
+
+//
+
+pub type StorageBuffer<T> = Buffer<T, USAGE>;
+pub type StorageBufferSlice<'b, T> = BufferSlice<'b, T, USAGE>;
@@ -0,0 +1,63 @@
+use crate::color::Color;
+use glam::{IVec2, IVec3, IVec4, UVec2, UVec3, UVec4, Vec2, Vec3, Vec4};
+use wgpu::VertexFormat;
+
+//
+
+/// Vertex attribute format of a field in
+/// a `#[derive(Vertex)]` struct
+pub trait VertexField {
+    const FORMAT: VertexFormat;
+}
+
+//
+
+macro_rules! impl_vertex_field {
+    ($($format:ident: $($ty:ty),+;)+) => {
+        $($(
+            impl VertexField for $ty {
+                const FORMAT: VertexFormat = VertexFormat::$format;
+            }
+        )+)+
+    };
+}
+
+impl_vertex_field! {
+    Float32: f32;
+    Float32x2: [f32; 2], Vec2;
+    Float32x3: [f32; 3], Vec3;
+    Float32x4: [f32; 4], Vec4, Color;
+    Uint32: u32;
+    Uint32x2: [u32; 2], UVec2;
+    Uint32x3: [u32; 3], UVec3;
+    Uint32x4: [u32; 4], UVec4;
+    Sint32: i32;
+    Sint32x2: [i32; 2], IVec2;
+    Sint32x3: [i32; 3], IVec3;
+    Sint32x4: [i32; 4], IVec4;
+}
+
+/// Used by `#[derive(Vertex)]` to compare a format
+/// to a WGSL type: `kind` is `'f'`, `'i'` or `'u'`
+/// for the scalar type and `components` is 1 to 4
+#[doc(hidden)]
+pub const fn vertex_format_matches(format: VertexFormat, kind: char, components: u32) -> bool {
+    use VertexFormat::*;
+    let (format_kind, format_components) = match format {
+        Float32 => ('f', 1),
+        Float32x2 | Float16x2 | Unorm8x2 | Snorm8x2 | Unorm16x2 | Snorm16x2 => ('f', 2),
+        Float32x3 => ('f', 3),
+        Float32x4 | Float16x4 | Unorm8x4 | Snorm8x4 | Unorm16x4 | Snorm16x4 => ('f', 4),
+        Uint32 => ('u', 1),
+        Uint32x2 | Uint8x2 | Uint16x2 => ('u', 2),
+        Uint32x3 => ('u', 3),
+        Uint32x4 | Uint8x4 | Uint16x4 => ('u', 4),
+        Sint32 => ('i', 1),
+        Sint32x2 | Sint8x2 | Sint16x2 => ('i', 2),
+        Sint32x3 => ('i', 3),
+        Sint32x4 | Sint8x4 | Sint16x4 => ('i', 4),
+        // no f64 vertex inputs in WGSL
+        Float64 | Float64x2 | Float64x3 | Float64x4 => return false,
+    };
+    format_kind == kind && format_components == components
+}
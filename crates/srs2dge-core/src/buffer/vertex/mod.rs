@@ -1,19 +1,28 @@
-use super::{Buffer, BufferSlice};
+use super::{Buffer, BufferSlice, GrowableBuffer};
 use wgpu::BufferUsages;
 
 //
 
+pub use field::*;
 pub use ty::*;
 
 //
 
+pub mod field;
 pub mod ty;
 
 //
 
 const USAGE: u32 = BufferUsages::VERTEX.bits() | BufferUsages::COPY_DST.bits();
+const STORAGE_USAGE: u32 =
+    BufferUsages::VERTEX.bits() | BufferUsages::STORAGE.bits() | BufferUsages::COPY_DST.bits();
 
 //
 
 pub type VertexBuffer<T = DefaultVertex> = Buffer<T, USAGE>;
 pub type VertexBufferSlice<'b, T> = BufferSlice<'b, T, USAGE>;
+pub type GrowableVertexBuffer<T = DefaultVertex> = GrowableBuffer<T, USAGE>;
+
+/// Vertex buffer that compute shaders can write to
+pub type VertexStorageBuffer<T = DefaultVertex> = Buffer<T, STORAGE_USAGE>;
+pub type VertexStorageBufferSlice<'b, T> = BufferSlice<'b, T, STORAGE_USAGE>;
@@ -1,13 +1,19 @@
 use bytemuck::{Pod, Zeroable};
 use glam::Vec2;
-use wgpu::{VertexAttribute, VertexBufferLayout, VertexFormat, VertexStepMode};
+use wgpu::VertexBufferLayout;
 
 use crate::color::Color;
 
 //
 
-#[derive(Debug, Clone, Copy, PartialEq, Default, Zeroable, Pod)]
+pub use srs2dge_derive::Vertex;
+
+//
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Zeroable, Pod, Vertex)]
 #[repr(C)]
+#[vertex(crate = "crate")]
+#[cfg_attr(test, vertex(wgsl = "../srs2dge-res/res/shader/texture_2d.wgsl"))]
 pub struct DefaultVertex {
     pos: Vec2,
     uv: Vec2,
@@ -16,10 +22,107 @@ pub struct DefaultVertex {
 
 //
 
+/// Vertex buffer layout of a type
+///
+/// `#[vertex(wgsl = ..)]` of the derive checks
+/// the layout against a shader input struct:
+///
+/// ```
+/// # use srs2dge_core::{buffer::Vertex, bytemuck::{Pod, Zeroable}, color::Color, glam::Vec2};
+/// #[derive(Clone, Copy, Zeroable, Pod, Vertex)]
+/// #[repr(C)]
+/// #[vertex(crate = "srs2dge_core", wgsl = "../srs2dge-res/res/shader/texture_2d.wgsl")]
+/// struct Checked {
+///     pos: Vec2,
+///     uv: Vec2,
+///     col: Color,
+/// }
+/// ```
+///
+/// A location the shader doesn't have:
+///
+/// ```compile_fail
+/// # use srs2dge_core::{buffer::Vertex, bytemuck::{Pod, Zeroable}, color::Color, glam::Vec2};
+/// #[derive(Clone, Copy, Zeroable, Pod, Vertex)]
+/// #[repr(C)]
+/// #[vertex(crate = "srs2dge_core", wgsl = "../srs2dge-res/res/shader/texture_2d.wgsl")]
+/// struct Checked {
+///     pos: Vec2,
+///     uv: Vec2,
+///     #[vertex(location = 5)]
+///     col: Color,
+/// }
+/// ```
+///
+/// A type that doesn't match:
+///
+/// ```compile_fail
+/// # use srs2dge_core::{buffer::Vertex, bytemuck::{Pod, Zeroable}, glam::Vec2};
+/// #[derive(Clone, Copy, Zeroable, Pod, Vertex)]
+/// #[repr(C)]
+/// #[vertex(crate = "srs2dge_core", wgsl = "../srs2dge-res/res/shader/texture_2d.wgsl")]
+/// struct Checked {
+///     pos: Vec2,
+///     uv: Vec2,
+///     col: Vec2,
+/// }
+/// ```
+///
+/// A shader input without a field:
+///
+/// ```compile_fail
+/// # use srs2dge_core::{buffer::Vertex, bytemuck::{Pod, Zeroable}, glam::Vec2};
+/// #[derive(Clone, Copy, Zeroable, Pod, Vertex)]
+/// #[repr(C)]
+/// #[vertex(crate = "srs2dge_core", wgsl = "../srs2dge-res/res/shader/texture_2d.wgsl")]
+/// struct Checked {
+///     pos: Vec2,
+///     uv: Vec2,
+/// }
+/// ```
 pub trait Vertex: Pod {
     const LAYOUT: &'static [VertexBufferLayout<'static>];
 }
 
+/// Vertex types of every vertex buffer slot a shader reads
+///
+/// A single [`Vertex`] uses slot 0 and a tuple
+/// `(A, B, ..)` uses slots 0, 1, .. in order.
+/// Each [`Vertex`] in a tuple should have exactly one layout.
+pub trait VertexSlots {
+    fn layouts() -> Vec<VertexBufferLayout<'static>>;
+}
+
+/// Type level state of a render pass after
+/// binding a `T` vertex buffer to slot `N`
+///
+/// Slots are bound in order, skipping one
+/// doesn't compile:
+///
+/// ```
+/// # use srs2dge_core::prelude::*;
+/// fn bind<'e>(pass: RenderPass<'e>, vbo: &'e VertexBuffer, instances: &'e VertexBuffer<QuadInstance>) {
+///     pass.bind_vbo(vbo).bind_vbo_slot::<1, _, _>(instances);
+/// }
+/// ```
+///
+/// ```compile_fail
+/// # use srs2dge_core::prelude::*;
+/// fn bind<'e>(pass: RenderPass<'e>, vbo: &'e VertexBuffer, instances: &'e VertexBuffer<QuadInstance>) {
+///     pass.bind_vbo(vbo).bind_vbo_slot::<2, _, _>(instances);
+/// }
+/// ```
+///
+/// ```compile_fail
+/// # use srs2dge_core::prelude::*;
+/// fn bind<'e>(pass: RenderPass<'e>, instances: &'e VertexBuffer<QuadInstance>) {
+///     pass.bind_vbo_slot::<1, _, _>(instances);
+/// }
+/// ```
+pub trait BindSlot<const N: u32, T> {
+    type Output;
+}
+
 //
 
 impl DefaultVertex {
@@ -48,34 +151,126 @@ impl DefaultVertex {
     }
 }
 
-impl Vertex for DefaultVertex {
-    const LAYOUT: &'static [VertexBufferLayout<'static>] = &[VertexBufferLayout {
-        array_stride: 32,
-        step_mode: VertexStepMode::Vertex,
-        attributes: &[
-            VertexAttribute {
-                format: VertexFormat::Float32x2,
-                offset: 0,
-                shader_location: 0,
-            },
-            VertexAttribute {
-                format: VertexFormat::Float32x2,
-                offset: 8,
-                shader_location: 1,
-            },
-            VertexAttribute {
-                format: VertexFormat::Float32x4,
-                offset: 16,
-                shader_location: 2,
-            },
-        ],
-    }];
+//
+
+// impl<T> Vertex for T where T: Pod {}
+
+/// Shaders without vertex buffers
+///
+/// Replaces the old `impl Vertex for ()`. `()` is the
+/// state of a render pass without vertex buffers, a
+/// [`Vertex`] impl would let it bind slot 1 first.
+impl VertexSlots for () {
+    fn layouts() -> Vec<VertexBufferLayout<'static>> {
+        vec![]
+    }
 }
 
-//
+impl<V> VertexSlots for V
+where
+    V: Vertex,
+{
+    fn layouts() -> Vec<VertexBufferLayout<'static>> {
+        V::LAYOUT.to_vec()
+    }
+}
 
-impl Vertex for () {
-    const LAYOUT: &'static [VertexBufferLayout<'static>] = &[];
+impl<A, B> VertexSlots for (A, B)
+where
+    A: Vertex,
+    B: Vertex,
+{
+    fn layouts() -> Vec<VertexBufferLayout<'static>> {
+        [A::LAYOUT, B::LAYOUT].concat()
+    }
 }
 
-// impl<T> Vertex for T where T: Pod {}
+impl<A, B, C> VertexSlots for (A, B, C)
+where
+    A: Vertex,
+    B: Vertex,
+    C: Vertex,
+{
+    fn layouts() -> Vec<VertexBufferLayout<'static>> {
+        [A::LAYOUT, B::LAYOUT, C::LAYOUT].concat()
+    }
+}
+
+// `()` is the unbound state, slots after
+// the next free one have no impl
+
+impl<T> BindSlot<0, T> for () {
+    type Output = T;
+}
+
+impl<A: Vertex, T> BindSlot<0, T> for A {
+    type Output = T;
+}
+
+impl<A: Vertex, T> BindSlot<1, T> for A {
+    type Output = (A, T);
+}
+
+impl<A, B, T> BindSlot<0, T> for (A, B) {
+    type Output = (T, B);
+}
+
+impl<A, B, T> BindSlot<1, T> for (A, B) {
+    type Output = (A, T);
+}
+
+impl<A, B, T> BindSlot<2, T> for (A, B) {
+    type Output = (A, B, T);
+}
+
+impl<A, B, C, T> BindSlot<0, T> for (A, B, C) {
+    type Output = (T, B, C);
+}
+
+impl<A, B, C, T> BindSlot<1, T> for (A, B, C) {
+    type Output = (A, T, C);
+}
+
+impl<A, B, C, T> BindSlot<2, T> for (A, B, C) {
+    type Output = (A, B, T);
+}
+
+//
+
+#[cfg(test)]
+mod test {
+    use super::{DefaultVertex, Vertex};
+    use crate::batch::prelude::QuadInstance;
+    use wgpu::{VertexFormat, VertexStepMode};
+
+    #[test]
+    fn derived_layout() {
+        let layout = &DefaultVertex::LAYOUT[0];
+        assert_eq!(layout.array_stride, 32);
+        assert_eq!(layout.step_mode, VertexStepMode::Vertex);
+        assert_eq!(
+            layout
+                .attributes
+                .iter()
+                .map(|a| (a.format, a.offset, a.shader_location))
+                .collect::<Vec<_>>(),
+            [
+                (VertexFormat::Float32x2, 0, 0),
+                (VertexFormat::Float32x2, 8, 1),
+                (VertexFormat::Float32x4, 16, 2)
+            ]
+        );
+
+        let layout = &QuadInstance::LAYOUT[0];
+        assert_eq!(layout.array_stride, 64);
+        assert_eq!(layout.step_mode, VertexStepMode::Instance);
+        assert_eq!(
+            layout
+                .attributes
+                .iter()
+                .map(|a| (a.offset, a.shader_location))
+                .collect::<Vec<_>>(),
+            [(0, 3), (8, 4), (16, 5), (32, 6), (48, 7), (56, 8)]
+        );
+    }
+}
@@ -1,13 +1,193 @@
+use crate::{
+    buffer::{has_usage, Buffer, DispatchIndirect},
+    shader::compute::ComputeShader,
+};
+use std::mem;
+use wgpu::{BindGroup, BufferUsages};
+
 //
 
-pub struct ComputePass<'e> {
-    _inner: wgpu::ComputePass<'e>,
+pub struct ComputePass<'e, const PIPELINE_BOUND: bool = false> {
+    pub(crate) inner: wgpu::ComputePass<'e>,
 }
 
 //
 
-impl<'e> ComputePass<'e> {
-    pub(crate) fn new(_inner: wgpu::ComputePass<'e>) -> Self {
-        Self { _inner }
+impl<'e, const PIPELINE_BOUND: bool> ComputePass<'e, PIPELINE_BOUND> {
+    pub fn bind_shader<'s>(mut self, shader: &'s ComputeShader) -> ComputePass<'e, true>
+    where
+        's: 'e,
+    {
+        self.inner.set_pipeline(&shader.pipeline);
+        self.pass()
+    }
+
+    pub fn bind_group<'g>(self, bind_group: &'g BindGroup) -> Self
+    where
+        'g: 'e,
+    {
+        self.bind_group_at(0, bind_group, &[])
+    }
+
+    /// Bind `bind_group` to `@group(index)`
+    ///
+    /// `offsets` are the dynamic offsets in bytes
+    /// for the bindings that have them, in order.
+    pub fn bind_group_at<'g>(
+        mut self,
+        index: u32,
+        bind_group: &'g BindGroup,
+        offsets: &[u32],
+    ) -> Self
+    where
+        'g: 'e,
+    {
+        self.inner.set_bind_group(index, bind_group, offsets);
+        self
+    }
+
+    pub fn done(self) -> ComputePass<'e> {
+        self.pass()
+    }
+
+    pub(crate) fn new(inner: wgpu::ComputePass<'e>) -> Self {
+        Self { inner }
+    }
+
+    fn pass<const N: bool>(self) -> ComputePass<'e, N> {
+        ComputePass { inner: self.inner }
+    }
+}
+
+impl<'e> ComputePass<'e, true> {
+    /// Dispatch `x * y * z` workgroups
+    pub fn dispatch(mut self, x: u32, y: u32, z: u32) -> Self {
+        self.inner.dispatch_workgroups(x, y, z);
+        self
+    }
+
+    /// Dispatch with the workgroup counts
+    /// read from `buffer[index]` on the GPU
+    pub fn dispatch_indirect<'b, const USAGE: u32>(
+        mut self,
+        buffer: &'b Buffer<DispatchIndirect, USAGE>,
+        index: u64,
+    ) -> Self
+    where
+        'b: 'e,
+    {
+        if !has_usage(USAGE, BufferUsages::INDIRECT) {
+            panic!("Buffer is not an indirect buffer");
+        }
+        self.inner.dispatch_workgroups_indirect(
+            buffer.inner(),
+            index * mem::size_of::<DispatchIndirect>() as u64,
+        );
+        self
+    }
+}
+
+//
+
+#[cfg(all(test, feature = "testing"))]
+mod test {
+    use crate::{
+        buffer::{
+            DefaultVertex, DispatchIndirect, IndexBuffer, IndirectBuffer, VertexStorageBuffer,
+        },
+        label,
+        shader::{compute::ComputeShader, error::ShaderError, module::ShaderModule, Shader},
+        testing::{fixture::SHADER, GoldenImage},
+    };
+    use wgpu::{BindGroupDescriptor, BindGroupEntry, PipelineLayoutDescriptor, ShaderStages};
+
+    const COMPUTE_SHADER: &str = r#"
+struct Vertex {
+	pos: vec2<f32>,
+	uv: vec2<f32>,
+	col: vec4<f32>,
+};
+
+struct Vertices {
+	data: array<Vertex>,
+};
+
+@group(0)
+@binding(0)
+var<storage, read_write> vertices: Vertices;
+
+@compute
+@workgroup_size(1)
+fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+	let a = f32(id.x) * 2.0943951;
+	vertices.data[id.x] = Vertex(
+		vec2<f32>(sin(a), cos(a)) * 0.8,
+		vec2<f32>(0.0, 0.0),
+		vec4<f32>(0.2, 1.0, 0.4, 1.0),
+	);
+}
+"#;
+
+    #[test]
+    fn compute() {
+        GoldenImage::new(concat!(env!("CARGO_MANIFEST_DIR"), "/golden/compute.png"))
+            .with_compute()
+            .check_blocking(|target, frame| {
+                let module = ShaderModule::new_wgsl_source(target, COMPUTE_SHADER.into()).unwrap();
+                let compute = ComputeShader::new(target, &module, "cs_main");
+                assert_eq!(
+                    ComputeShader::try_new(target, &module, "cs_other").unwrap_err(),
+                    ShaderError::MissingEntryPoint {
+                        stage: ShaderStages::COMPUTE,
+                        entry: "cs_other".to_string()
+                    }
+                );
+                // the layout is missing the storage buffer
+                assert!(matches!(
+                    ComputeShader::try_new_with_layout(
+                        target,
+                        &module,
+                        "cs_main",
+                        PipelineLayoutDescriptor::default()
+                    ),
+                    Err(ShaderError::Validation(_))
+                ));
+
+                let module = ShaderModule::new_wgsl_source(target, SHADER.into()).unwrap();
+                let shader: Shader<DefaultVertex, u32> = Shader::builder()
+                    .with_vertex(&module, "vs_main")
+                    .with_fragment(&module, "fs_main")
+                    .with_format(target.get_format())
+                    .with_baked_layout(PipelineLayoutDescriptor::default())
+                    .build(target);
+
+                // the compute shader generates the triangle
+                let vbo = VertexStorageBuffer::<DefaultVertex>::new(target, 3);
+                let ibo = IndexBuffer::new_with(target, &[0, 1, 2]);
+                let indirect =
+                    IndirectBuffer::new_with(target, &[DispatchIndirect { x: 3, y: 1, z: 1 }]);
+                let bind_group = target.get_device().create_bind_group(&BindGroupDescriptor {
+                    label: label!(),
+                    layout: &compute.bind_group_layout(0),
+                    entries: &[BindGroupEntry {
+                        binding: 0,
+                        resource: vbo.inner().as_entire_binding(),
+                    }],
+                });
+
+                frame
+                    .compute_pass()
+                    .bind_shader(&compute)
+                    .bind_group(&bind_group)
+                    .dispatch_indirect(&indirect, 0);
+
+                frame
+                    .primary_render_pass()
+                    .bind_vbo(&vbo)
+                    .bind_ibo(&ibo)
+                    .bind_shader(&shader)
+                    .draw_indexed(0..3, 0, 0..1);
+            })
+            .unwrap();
     }
 }
@@ -2,15 +2,16 @@ use self::{compute_pass::ComputePass, render_pass::RenderPass};
 use crate::{
     color::Color,
     label,
+    shader::builder::MAX_COLOR_TARGETS,
     target::surface::Surface,
-    texture::{has_render_attachment, Texture},
+    texture::{has_depth, has_render_attachment, has_stencil, RenderTargetTexture, Texture},
 };
 use std::sync::Arc;
 use wgpu::{
     util::StagingBelt, Buffer, BufferAddress, BufferSize, BufferViewMut, CommandEncoder,
-    CommandEncoderDescriptor, ComputePassDescriptor, Device, LoadOp, Operations, Queue,
-    RenderPassColorAttachment, RenderPassDescriptor, SurfaceTexture, TextureFormat, TextureView,
-    TextureViewDescriptor,
+    CommandEncoderDescriptor, ComputePassDescriptor, Device, Features, LoadOp, Operations, Queue,
+    RenderPassColorAttachment, RenderPassDepthStencilAttachment, RenderPassDescriptor,
+    SurfaceTexture, TextureFormat, TextureView, TextureViewDescriptor,
 };
 
 //
@@ -26,8 +27,12 @@ pub struct Frame {
     main_view: TextureView,
     main_format: TextureFormat,
     main_dim: (u32, u32),
+    main_depth: Option<(TextureView, TextureFormat)>,
+    main_msaa: Option<(TextureView, u32)>,
 
     encoder: Option<CommandEncoder>,
+    multi_draw_indirect: bool,
+    indirect_execution: bool,
 
     queue: Arc<Queue>,
 
@@ -53,18 +58,59 @@ impl Frame {
         let main_format = surface.format();
         let main_dim = surface.get_dim();
 
-        let encoder = device.create_command_encoder(&CommandEncoderDescriptor { label: label!() });
+        Self::new_inner(
+            device,
+            queue,
+            Some(main_texture),
+            main_view,
+            main_format,
+            main_dim,
+            belt,
+        )
+    }
+
+    /// Frame that renders into an offscreen texture
+    /// instead of a window surface
+    pub fn new_headless(
+        device: &Device,
+        queue: Arc<Queue>,
+        texture: &RenderTargetTexture,
+        belt: StagingBelt,
+    ) -> Self {
+        let main_view = texture.inner().create_view(&TextureViewDescriptor {
+            label: label!(),
+            ..Default::default()
+        });
+        let main_format = texture.get_format();
+        let main_dim = texture.get_dim().into();
 
-        let main_texture = Some(main_texture);
+        Self::new_inner(device, queue, None, main_view, main_format, main_dim, belt)
+    }
+
+    fn new_inner(
+        device: &Device,
+        queue: Arc<Queue>,
+        main_texture: Option<SurfaceTexture>,
+        main_view: TextureView,
+        main_format: TextureFormat,
+        main_dim: (u32, u32),
+        belt: StagingBelt,
+    ) -> Self {
+        let encoder = device.create_command_encoder(&CommandEncoderDescriptor { label: label!() });
         let encoder = Some(encoder);
+        let multi_draw_indirect = device.features().contains(Features::MULTI_DRAW_INDIRECT);
 
         Self {
             main_texture,
             main_view,
             main_format,
             main_dim,
+            main_depth: None,
+            main_msaa: None,
 
             encoder,
+            multi_draw_indirect,
+            indirect_execution: true,
 
             queue,
 
@@ -85,6 +131,16 @@ impl Frame {
     }
 
     pub fn primary_render_pass(&mut self) -> RenderPass<(), (), (), (), false> {
+        let depth = self
+            .main_depth
+            .as_ref()
+            .map(|(view, format)| (view, *format));
+        // draw into the multisampled color buffer
+        // and resolve it into the main texture
+        let (view, resolve_target, sample_count) = match self.main_msaa.as_ref() {
+            Some((view, sample_count)) => (view, Some(&self.main_view), *sample_count),
+            None => (&self.main_view, None, 1),
+        };
         let pass = self
             .encoder
             .as_mut()
@@ -92,17 +148,25 @@ impl Frame {
             .begin_render_pass(&RenderPassDescriptor {
                 label: label!(),
                 color_attachments: &[Some(RenderPassColorAttachment {
-                    view: &self.main_view,
-                    resolve_target: None,
+                    view,
+                    resolve_target,
                     ops: Operations {
                         load: LoadOp::Clear(self.clear_color.into()),
-                        store: true,
+                        // only the resolved result is needed
+                        store: resolve_target.is_none(),
                     },
                 })],
-                depth_stencil_attachment: None,
+                depth_stencil_attachment: depth.map(depth_stencil_attachment),
             });
 
-        RenderPass::new(pass, self.main_format)
+        RenderPass::new(
+            pass,
+            vec![self.main_format],
+            depth.map(|(_, format)| format),
+            sample_count,
+            self.multi_draw_indirect,
+            self.indirect_execution,
+        )
     }
 
     pub fn secondary_render_pass<'a, const USAGE: u32>(
@@ -112,28 +176,199 @@ impl Frame {
 // where // Rust can't do this yet
 //     If<{ has_render_attachment(USAGE) }>: True,
     {
-        if !has_render_attachment(USAGE) {
+        self.secondary_render_pass_inner::<USAGE, 0, 0>(&[target], None, None)
+    }
+
+    /// Render pass with `depth` as its depth/stencil attachment
+    ///
+    /// `None` if either texture can't be used as a render attachment,
+    /// if `depth` is not a depth format or if the sample counts differ
+    pub fn secondary_render_pass_with_depth<'a, const USAGE: u32, const DEPTH_USAGE: u32>(
+        &'a mut self,
+        target: &'a Texture<USAGE>,
+        depth: &'a Texture<DEPTH_USAGE>,
+    ) -> Option<RenderPass<'a, (), (), (), (), false>> {
+        self.secondary_render_pass_inner::<USAGE, 0, DEPTH_USAGE>(&[target], None, Some(depth))
+    }
+
+    /// Render pass that draws into the multisampled `target`
+    /// and resolves the result into `resolve`
+    ///
+    /// `None` if `target` is not multisampled, `resolve` is
+    /// or if their formats or dimensions don't match
+    pub fn secondary_render_pass_multisampled<'a, const USAGE: u32, const RESOLVE_USAGE: u32>(
+        &'a mut self,
+        target: &'a Texture<USAGE>,
+        resolve: &'a Texture<RESOLVE_USAGE>,
+    ) -> Option<RenderPass<'a, (), (), (), (), false>> {
+        self.secondary_render_pass_inner::<USAGE, RESOLVE_USAGE, 0>(&[target], Some(resolve), None)
+    }
+
+    /// [`Self::secondary_render_pass_multisampled`] with
+    /// `depth` as its depth/stencil attachment
+    ///
+    /// `depth` has to have the same sample count as `target`
+    pub fn secondary_render_pass_multisampled_with_depth<
+        'a,
+        const USAGE: u32,
+        const RESOLVE_USAGE: u32,
+        const DEPTH_USAGE: u32,
+    >(
+        &'a mut self,
+        target: &'a Texture<USAGE>,
+        resolve: &'a Texture<RESOLVE_USAGE>,
+        depth: &'a Texture<DEPTH_USAGE>,
+    ) -> Option<RenderPass<'a, (), (), (), (), false>> {
+        self.secondary_render_pass_inner(&[target], Some(resolve), Some(depth))
+    }
+
+    /// Render pass that draws into every texture in `targets`,
+    /// `@location(n)` of the fragment shader goes to `targets[n]`
+    ///
+    /// `None` if there are no targets, too many of them, if one
+    /// can't be used as a render attachment or if their
+    /// dimensions or sample counts differ
+    pub fn secondary_render_pass_multi<'a, const USAGE: u32>(
+        &'a mut self,
+        targets: &[&'a Texture<USAGE>],
+    ) -> Option<RenderPass<'a, (), (), (), (), false>> {
+        self.secondary_render_pass_inner::<USAGE, 0, 0>(targets, None, None)
+    }
+
+    /// [`Self::secondary_render_pass_multi`] with
+    /// `depth` as its depth/stencil attachment
+    pub fn secondary_render_pass_multi_with_depth<'a, const USAGE: u32, const DEPTH_USAGE: u32>(
+        &'a mut self,
+        targets: &[&'a Texture<USAGE>],
+        depth: &'a Texture<DEPTH_USAGE>,
+    ) -> Option<RenderPass<'a, (), (), (), (), false>> {
+        self.secondary_render_pass_inner::<USAGE, 0, DEPTH_USAGE>(targets, None, Some(depth))
+    }
+
+    /// Set the depth/stencil texture used by [`Self::primary_render_pass`]
+    ///
+    /// [`Target`] does this automatically
+    /// if it has a depth/stencil format set.
+    ///
+    /// [`Target`]: crate::target::Target
+    pub fn set_depth<const USAGE: u32>(&mut self, depth: Option<&Texture<USAGE>>) {
+        self.main_depth = depth.map(|depth| {
+            (
+                depth.inner().create_view(&TextureViewDescriptor {
+                    label: label!(),
+                    ..Default::default()
+                }),
+                depth.get_format(),
+            )
+        });
+    }
+
+    /// Set the multisampled color buffer used by [`Self::primary_render_pass`]
+    ///
+    /// [`Target`] does this automatically
+    /// if it has MSAA enabled.
+    ///
+    /// [`Target`]: crate::target::Target
+    pub fn set_msaa<const USAGE: u32>(&mut self, msaa: Option<&Texture<USAGE>>) {
+        self.main_msaa = msaa.map(|msaa| {
+            (
+                msaa.inner().create_view(&TextureViewDescriptor {
+                    label: label!(),
+                    ..Default::default()
+                }),
+                msaa.get_sample_count(),
+            )
+        });
+    }
+
+    /// Whether the device can read draw arguments
+    /// from indirect buffers, on by default
+    ///
+    /// [`Target`] turns this off if the adapter is
+    /// missing the `INDIRECT_EXECUTION` downlevel flag.
+    ///
+    /// [`Target`]: crate::target::Target
+    pub fn set_indirect_execution(&mut self, supported: bool) {
+        self.indirect_execution = supported;
+    }
+
+    fn secondary_render_pass_inner<
+        'a,
+        const USAGE: u32,
+        const RESOLVE_USAGE: u32,
+        const DEPTH_USAGE: u32,
+    >(
+        &'a mut self,
+        targets: &[&'a Texture<USAGE>],
+        resolve: Option<&'a Texture<RESOLVE_USAGE>>,
+        depth: Option<&'a Texture<DEPTH_USAGE>>,
+    ) -> Option<RenderPass<'a, (), (), (), (), false>> {
+        let target = *targets.first()?;
+        if !has_render_attachment(USAGE)
+            || targets.len() > MAX_COLOR_TARGETS
+            || (resolve.is_some() && targets.len() != 1)
+            || targets.iter().any(|other| {
+                other.get_dim() != target.get_dim()
+                    || other.get_sample_count() != target.get_sample_count()
+            })
+        {
             return None;
         }
 
+        if let Some(resolve) = resolve {
+            if !has_render_attachment(RESOLVE_USAGE)
+                || target.get_sample_count() == 1
+                || resolve.get_sample_count() != 1
+                || target.get_format() != resolve.get_format()
+                || target.get_dim() != resolve.get_dim()
+            {
+                return None;
+            }
+        }
+
+        if let Some(depth) = depth {
+            if !has_render_attachment(DEPTH_USAGE)
+                || !has_depth(depth.get_format())
+                || depth.get_sample_count() != target.get_sample_count()
+            {
+                return None;
+            }
+        }
+        let depth = depth.map(|depth| (&**depth, depth.get_format()));
+
+        let clear_color = self.clear_color;
+        let color_attachments: Vec<_> = targets
+            .iter()
+            .map(|target| {
+                Some(RenderPassColorAttachment {
+                    view: target,
+                    resolve_target: resolve.map(|resolve| &**resolve),
+                    ops: Operations {
+                        load: LoadOp::Clear(clear_color.into()),
+                        store: true,
+                    },
+                })
+            })
+            .collect();
+
         let pass = self
             .encoder
             .as_mut()
             .expect("Frame was dropped")
             .begin_render_pass(&RenderPassDescriptor {
                 label: label!(),
-                color_attachments: &[Some(RenderPassColorAttachment {
-                    view: target,
-                    resolve_target: None,
-                    ops: Operations {
-                        load: LoadOp::Clear(self.clear_color.into()),
-                        store: true,
-                    },
-                })],
-                depth_stencil_attachment: None,
+                color_attachments: &color_attachments,
+                depth_stencil_attachment: depth.map(depth_stencil_attachment),
             });
 
-        Some(RenderPass::new(pass, target.get_format()))
+        Some(RenderPass::new(
+            pass,
+            targets.iter().map(|target| target.get_format()).collect(),
+            depth.map(|(_, format)| format),
+            target.get_sample_count(),
+            self.multi_draw_indirect,
+            self.indirect_execution,
+        ))
     }
 
     pub fn compute_pass(&mut self) -> ComputePass {
@@ -173,10 +408,28 @@ impl Frame {
             .take()
             .expect("Frame was dropped twice")
             .finish()]);
-        self.main_texture
-            .take()
-            .expect("Frame was dropped twice")
-            .present();
+        // headless frames have nothing to present
+        if let Some(main_texture) = self.main_texture.take() {
+            main_texture.present();
+        }
         self.belt
     }
 }
+
+//
+
+fn depth_stencil_attachment(
+    (view, format): (&TextureView, TextureFormat),
+) -> RenderPassDepthStencilAttachment<'_> {
+    RenderPassDepthStencilAttachment {
+        view,
+        depth_ops: has_depth(format).then_some(Operations {
+            load: LoadOp::Clear(1.0),
+            store: true,
+        }),
+        stencil_ops: has_stencil(format).then_some(Operations {
+            load: LoadOp::Clear(0),
+            store: true,
+        }),
+    }
+}
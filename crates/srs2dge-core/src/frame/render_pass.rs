@@ -1,15 +1,22 @@
 use crate::{
-    buffer::{index::Index, IndexBuffer, Vertex, VertexBuffer},
-    shader::Shader,
+    buffer::{
+        has_usage, index::Index, BindSlot, Buffer, DrawIndexedIndirect, DrawIndirect, IndexBuffer,
+        Vertex, VertexSlots,
+    },
+    shader::{error::ShaderError, Shader},
 };
-use std::{marker::PhantomData, ops::Range};
-use wgpu::{BindGroup, TextureFormat};
+use std::{marker::PhantomData, mem, ops::Range};
+use wgpu::{BindGroup, BufferUsages, TextureFormat};
 
 //
 
 pub struct RenderPass<'e, Sv = (), Bv = (), Si = (), Bi = (), const PIPELINE_BOUND: bool = false> {
     pub(crate) inner: wgpu::RenderPass<'e>,
-    pub(crate) format: TextureFormat,
+    pub(crate) formats: Vec<TextureFormat>,
+    pub(crate) depth_format: Option<TextureFormat>,
+    pub(crate) sample_count: u32,
+    pub(crate) multi_draw_indirect: bool,
+    pub(crate) indirect_execution: bool,
 
     _p: PhantomData<(Sv, Bv, Si, Bi)>,
 }
@@ -19,16 +26,36 @@ pub struct RenderPass<'e, Sv = (), Bv = (), Si = (), Bi = (), const PIPELINE_BOU
 impl<'e, Sv, Bv, Si, Bi, const PIPELINE_BOUND: bool>
     RenderPass<'e, Sv, Bv, Si, Bi, PIPELINE_BOUND>
 {
-    pub fn bind_vbo<'b, T>(
+    /// Bind `buffer` to the vertex buffer slot 0
+    ///
+    /// `buffer` is usually a `VertexBuffer` or a `VertexStorageBuffer`.
+    /// This forgets the other slots, bind them after this.
+    pub fn bind_vbo<'b, T, const USAGE: u32>(
         mut self,
-        buffer: &'b VertexBuffer<T>,
-        // slot: u32,
+        buffer: &'b Buffer<T, USAGE>,
     ) -> RenderPass<'e, Sv, T, Si, Bi, PIPELINE_BOUND>
     where
         'b: 'e,
         T: Vertex + 'static,
     {
-        self.inner.set_vertex_buffer(0, buffer.inner().slice(..));
+        self.set_vbo(0, buffer);
+        self.pass()
+    }
+
+    /// Bind `buffer` to the vertex buffer slot `N`
+    ///
+    /// Shaders reading multiple slots are built
+    /// with a tuple of vertex types: `(A, B)`.
+    pub fn bind_vbo_slot<'b, const N: u32, T, const USAGE: u32>(
+        mut self,
+        buffer: &'b Buffer<T, USAGE>,
+    ) -> RenderPass<'e, Sv, <Bv as BindSlot<N, T>>::Output, Si, Bi, PIPELINE_BOUND>
+    where
+        'b: 'e,
+        T: Vertex + 'static,
+        Bv: BindSlot<N, T>,
+    {
+        self.set_vbo(N, buffer);
         self.pass()
     }
 
@@ -51,41 +78,109 @@ impl<'e, Sv, Bv, Si, Bi, const PIPELINE_BOUND: bool>
     ) -> RenderPass<'e, V, Bv, I, Bi, true>
     where
         's: 'e,
-        V: Vertex + 'static,
+        V: VertexSlots + 'static,
         I: Index + 'static,
     {
-        if self.format != shader.format {
-            panic!("Shader output incompatible with this render target");
-        } else {
-            self.inner.set_pipeline(&shader.pipeline);
+        if let Err(err) = self.check_shader(shader) {
+            panic!("{err}");
         }
+        self.inner.set_pipeline(&shader.pipeline);
         self.pass()
     }
 
-    pub fn bind_group<'g>(mut self, bind_group: &'g BindGroup) -> Self
+    /// Whether `shader` renders to the formats
+    /// and sample count of this render pass
+    ///
+    /// [`Self::bind_shader`] panics if it doesn't.
+    pub fn check_shader<V, I>(&self, shader: &Shader<V, I>) -> Result<(), ShaderError>
+    where
+        V: VertexSlots,
+        I: Index,
+    {
+        let mismatch = |err: &str| Err(ShaderError::FormatMismatch(err.to_string()));
+        if self.formats != shader.formats {
+            mismatch("Shader output incompatible with this render target")
+        } else if self.depth_format != shader.depth_format {
+            mismatch("Shader depth/stencil format incompatible with this render target")
+        } else if self.sample_count != shader.sample_count {
+            mismatch("Shader sample count incompatible with this render target")
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn bind_group<'g>(self, bind_group: &'g BindGroup) -> Self
     where
         'g: 'e,
     {
-        self.inner.set_bind_group(0, bind_group, &[]);
-        self.pass()
+        self.bind_group_at(0, bind_group, &[])
+    }
+
+    /// Bind `bind_group` to `@group(index)`
+    ///
+    /// `offsets` are the dynamic offsets in bytes
+    /// for the bindings that have them, in order.
+    pub fn bind_group_at<'g>(
+        mut self,
+        index: u32,
+        bind_group: &'g BindGroup,
+        offsets: &[u32],
+    ) -> Self
+    where
+        'g: 'e,
+    {
+        self.inner.set_bind_group(index, bind_group, offsets);
+        self
+    }
+
+    /// Reference value used by the stencil test
+    pub fn set_stencil_reference(mut self, reference: u32) -> Self {
+        self.inner.set_stencil_reference(reference);
+        self
     }
 
     pub fn done(self) -> RenderPass<'e> {
         self.pass()
     }
 
-    pub(crate) fn new(inner: wgpu::RenderPass<'e>, format: TextureFormat) -> Self {
+    pub(crate) fn new(
+        inner: wgpu::RenderPass<'e>,
+        formats: Vec<TextureFormat>,
+        depth_format: Option<TextureFormat>,
+        sample_count: u32,
+        multi_draw_indirect: bool,
+        indirect_execution: bool,
+    ) -> Self {
         Self {
             inner,
-            format,
+            formats,
+            depth_format,
+            sample_count,
+            multi_draw_indirect,
+            indirect_execution,
             _p: PhantomData::default(),
         }
     }
 
+    fn set_vbo<'b, T, const USAGE: u32>(&mut self, slot: u32, buffer: &'b Buffer<T, USAGE>)
+    where
+        'b: 'e,
+        T: Vertex,
+    {
+        if !has_usage(USAGE, BufferUsages::VERTEX) {
+            panic!("Buffer is not a vertex buffer");
+        }
+        self.inner.set_vertex_buffer(slot, buffer.inner().slice(..));
+    }
+
     fn pass<Svn, Bvn, Sin, Bin, const N: bool>(self) -> RenderPass<'e, Svn, Bvn, Sin, Bin, N> {
         RenderPass {
             inner: self.inner,
-            format: self.format,
+            formats: self.formats,
+            depth_format: self.depth_format,
+            sample_count: self.sample_count,
+            multi_draw_indirect: self.multi_draw_indirect,
+            indirect_execution: self.indirect_execution,
             _p: PhantomData::default(),
         }
     }
@@ -108,12 +203,336 @@ impl<'e, V, I> RenderPass<'e, V, V, I, I, true> {
         self
     }
 
-    // TODO:
-    /* pub fn draw_indirect(&mut self, vertices: Range<u32>, instances: Range<u32>) {
-        self.inner.draw_indirect(vertices, instances)
+    /// [`Self::draw`] with the arguments
+    /// read from `buffer[index]` on the GPU
+    ///
+    /// Panics if the device is missing the
+    /// `INDIRECT_EXECUTION` downlevel flag.
+    pub fn draw_indirect<'b, const USAGE: u32>(
+        mut self,
+        buffer: &'b Buffer<DrawIndirect, USAGE>,
+        index: u64,
+    ) -> Self
+    where
+        'b: 'e,
+    {
+        self.check_indirect(USAGE);
+        self.inner
+            .draw_indirect(buffer.inner(), indirect_offset::<DrawIndirect>(index));
+        self
+    }
+
+    /// [`Self::draw_indexed`] with the arguments
+    /// read from `buffer[index]` on the GPU
+    ///
+    /// Panics if the device is missing the
+    /// `INDIRECT_EXECUTION` downlevel flag.
+    pub fn draw_indexed_indirect<'b, const USAGE: u32>(
+        mut self,
+        buffer: &'b Buffer<DrawIndexedIndirect, USAGE>,
+        index: u64,
+    ) -> Self
+    where
+        'b: 'e,
+    {
+        self.check_indirect(USAGE);
+        self.inner.draw_indexed_indirect(
+            buffer.inner(),
+            indirect_offset::<DrawIndexedIndirect>(index),
+        );
+        self
+    }
+
+    /// [`Self::draw_indirect`] for every index in `indices`
+    ///
+    /// Uses a single multi draw call
+    /// if the device supports it.
+    pub fn multi_draw_indirect<'b, const USAGE: u32>(
+        mut self,
+        buffer: &'b Buffer<DrawIndirect, USAGE>,
+        indices: Range<u32>,
+    ) -> Self
+    where
+        'b: 'e,
+    {
+        self.check_indirect(USAGE);
+        if self.multi_draw_indirect {
+            self.inner.multi_draw_indirect(
+                buffer.inner(),
+                indirect_offset::<DrawIndirect>(indices.start as _),
+                indices.end - indices.start,
+            );
+            self
+        } else {
+            indices.fold(self, |pass, index| pass.draw_indirect(buffer, index as _))
+        }
+    }
+
+    /// [`Self::draw_indexed_indirect`] for every index in `indices`
+    ///
+    /// Uses a single multi draw call
+    /// if the device supports it.
+    pub fn multi_draw_indexed_indirect<'b, const USAGE: u32>(
+        mut self,
+        buffer: &'b Buffer<DrawIndexedIndirect, USAGE>,
+        indices: Range<u32>,
+    ) -> Self
+    where
+        'b: 'e,
+    {
+        self.check_indirect(USAGE);
+        if self.multi_draw_indirect {
+            self.inner.multi_draw_indexed_indirect(
+                buffer.inner(),
+                indirect_offset::<DrawIndexedIndirect>(indices.start as _),
+                indices.end - indices.start,
+            );
+            self
+        } else {
+            indices.fold(self, |pass, index| {
+                pass.draw_indexed_indirect(buffer, index as _)
+            })
+        }
+    }
+
+    fn check_indirect(&self, usage: u32) {
+        if !has_usage(usage, BufferUsages::INDIRECT) {
+            panic!("Buffer is not an indirect buffer");
+        }
+        // the arguments are on the GPU, there
+        // is no direct draw to fall back to
+        if !self.indirect_execution {
+            panic!("Device does not support indirect draws (INDIRECT_EXECUTION)");
+        }
+    }
+}
+
+//
+
+fn indirect_offset<T>(index: u64) -> u64 {
+    index * mem::size_of::<T>() as u64
+}
+
+//
+
+#[cfg(all(test, feature = "testing"))]
+mod test {
+    use crate::{
+        buffer::{
+            DefaultVertex, DrawIndexedIndirect, IndexBuffer, IndirectBuffer, UniformBuffer,
+            VertexBuffer,
+        },
+        color::Color,
+        label,
+        shader::{module::ShaderModule, Shader},
+        testing::{
+            fixture::{quad, SHADER},
+            GoldenImage,
+        },
+    };
+    use glam::Vec4;
+    use std::num::NonZeroU64;
+    use wgpu::{
+        BindGroupDescriptor, BindGroupEntry, BindingResource, BufferBinding,
+        PipelineLayoutDescriptor, PrimitiveTopology,
+    };
+
+    const GROUPS_SHADER: &str = r#"
+struct VertexInput {
+	@location(0) pos: vec2<f32>,
+	@location(1) uv: vec2<f32>,
+	@location(2) col: vec4<f32>,
+};
+
+@group(0)
+@binding(0)
+var<uniform> tint: vec4<f32>;
+
+@group(1)
+@binding(0)
+var<uniform> offset: vec4<f32>;
+
+@vertex
+fn vs_main(vin: VertexInput) -> @builtin(position) vec4<f32> {
+	return vec4<f32>(vin.pos * 0.5 + offset.xy, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+	return tint;
+}
+"#;
+
+    #[test]
+    fn multi_draw_indirect() {
+        GoldenImage::new(concat!(env!("CARGO_MANIFEST_DIR"), "/golden/indirect.png"))
+            .check_blocking(|target, frame| {
+                let module = ShaderModule::new_wgsl_source(target, SHADER.into()).unwrap();
+                let shader: Shader<DefaultVertex, u32> = Shader::builder()
+                    .with_vertex(&module, "vs_main")
+                    .with_fragment(&module, "fs_main")
+                    .with_topology(PrimitiveTopology::TriangleStrip)
+                    .with_format(target.get_format())
+                    .with_baked_layout(PipelineLayoutDescriptor::default())
+                    .build(target);
+
+                let vertices: Vec<DefaultVertex> =
+                    [quad(-0.3, 0.0, Color::RED), quad(0.3, 0.0, Color::BLUE)].concat();
+                let vbo = VertexBuffer::new_with(target, &vertices);
+                let ibo = IndexBuffer::new_with(target, &[0, 1, 2, 3]);
+                let indirect = IndirectBuffer::new_with(
+                    target,
+                    &[
+                        DrawIndexedIndirect::new(0..4, 0, 0..1),
+                        DrawIndexedIndirect::new(0..4, 4, 0..1),
+                    ],
+                );
+
+                frame
+                    .primary_render_pass()
+                    .bind_vbo(&vbo)
+                    .bind_ibo(&ibo)
+                    .bind_shader(&shader)
+                    .multi_draw_indexed_indirect(&indirect, 0..2);
+            })
+            .unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "does not support indirect draws")]
+    fn indirect_without_indirect_execution() {
+        GoldenImage::new(concat!(env!("CARGO_MANIFEST_DIR"), "/golden/indirect.png"))
+            .check_blocking(|target, frame| {
+                let module = ShaderModule::new_wgsl_source(target, SHADER.into()).unwrap();
+                let shader: Shader<DefaultVertex, u32> = Shader::builder()
+                    .with_vertex(&module, "vs_main")
+                    .with_fragment(&module, "fs_main")
+                    .with_format(target.get_format())
+                    .with_baked_layout(PipelineLayoutDescriptor::default())
+                    .build(target);
+
+                let vbo = VertexBuffer::new_with(target, &quad(0.0, 0.0, Color::RED));
+                let ibo = IndexBuffer::new_with(target, &[0, 1, 2]);
+                let indirect =
+                    IndirectBuffer::new_with(target, &[DrawIndexedIndirect::new(0..3, 0, 0..1)]);
+
+                // like on WebGL2
+                frame.set_indirect_execution(false);
+                frame
+                    .primary_render_pass()
+                    .bind_vbo(&vbo)
+                    .bind_ibo(&ibo)
+                    .bind_shader(&shader)
+                    .draw_indexed_indirect(&indirect, 0);
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn bind_groups() {
+        GoldenImage::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/golden/bind_groups.png"
+        ))
+        .check_blocking(|target, frame| {
+            let module = ShaderModule::new_wgsl_source(target, GROUPS_SHADER.into()).unwrap();
+            let shader: Shader<DefaultVertex, u32> = Shader::builder()
+                .with_vertex(&module, "vs_main")
+                .with_fragment(&module, "fs_main")
+                .with_topology(PrimitiveTopology::TriangleStrip)
+                .with_format(target.get_format())
+                .build(target);
+
+            let vbo = VertexBuffer::new_with(target, &quad(0.0, 0.0, Color::WHITE));
+            let ibo = IndexBuffer::new_with(target, &[0, 1, 2, 3]);
+            let bind_group = |index: u32, value: Vec4| {
+                let ubo = UniformBuffer::new_single(target, value);
+                target.get_device().create_bind_group(&BindGroupDescriptor {
+                    label: label!(),
+                    layout: &shader.bind_group_layout(index),
+                    entries: &[BindGroupEntry {
+                        binding: 0,
+                        resource: ubo.inner().as_entire_binding(),
+                    }],
+                })
+            };
+            let tint = bind_group(0, Color::ORANGE.to_vec4());
+            let left = bind_group(1, Vec4::new(-0.5, 0.0, 0.0, 0.0));
+            let right = bind_group(1, Vec4::new(0.5, 0.25, 0.0, 0.0));
+
+            // group 0 stays bound, only group 1 is swapped
+            frame
+                .primary_render_pass()
+                .bind_vbo(&vbo)
+                .bind_ibo(&ibo)
+                .bind_shader(&shader)
+                .bind_group_at(0, &tint, &[])
+                .bind_group_at(1, &left, &[])
+                .draw_indexed(0..4, 0, 0..1)
+                .bind_group_at(1, &right, &[])
+                .draw_indexed(0..4, 0, 0..1);
+        })
+        .unwrap();
     }
 
-    pub fn draw_indirect_indexed(&mut self, vertices: Range<u32>, instances: Range<u32>) {
-        self.inner.draw(vertices, instances)
-    } */
+    #[test]
+    fn dynamic_offsets() {
+        // same quads as `bind_groups` from a single buffer
+        GoldenImage::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/golden/bind_groups.png"
+        ))
+        .check_blocking(|target, frame| {
+            let module = ShaderModule::new_wgsl_source(target, GROUPS_SHADER.into()).unwrap();
+            let shader: Shader<DefaultVertex, u32> = Shader::builder()
+                .with_vertex(&module, "vs_main")
+                .with_fragment(&module, "fs_main")
+                .with_topology(PrimitiveTopology::TriangleStrip)
+                .with_format(target.get_format())
+                .with_dynamic_offset(1, 0)
+                .build(target);
+
+            let vbo = VertexBuffer::new_with(target, &quad(0.0, 0.0, Color::WHITE));
+            let ibo = IndexBuffer::new_with(target, &[0, 1, 2, 3]);
+            let tint = UniformBuffer::new_single(target, Color::ORANGE.to_vec4());
+            let tint = target.get_device().create_bind_group(&BindGroupDescriptor {
+                label: label!(),
+                layout: &shader.bind_group_layout(0),
+                entries: &[BindGroupEntry {
+                    binding: 0,
+                    resource: tint.inner().as_entire_binding(),
+                }],
+            });
+
+            // offsets have to be aligned to 256 bytes
+            let mut offsets = vec![Vec4::ZERO; 32];
+            offsets[0] = Vec4::new(-0.5, 0.0, 0.0, 0.0);
+            offsets[16] = Vec4::new(0.5, 0.25, 0.0, 0.0);
+            let offsets = UniformBuffer::new_with(target, &offsets);
+            let offset = target.get_device().create_bind_group(&BindGroupDescriptor {
+                label: label!(),
+                layout: &shader.bind_group_layout(1),
+                entries: &[BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::Buffer(BufferBinding {
+                        buffer: offsets.inner(),
+                        offset: 0,
+                        size: NonZeroU64::new(16),
+                    }),
+                }],
+            });
+
+            frame
+                .primary_render_pass()
+                .bind_vbo(&vbo)
+                .bind_ibo(&ibo)
+                .bind_shader(&shader)
+                .bind_group_at(0, &tint, &[])
+                .bind_group_at(1, &offset, &[0])
+                .draw_indexed(0..4, 0, 0..1)
+                .bind_group_at(1, &offset, &[256])
+                .draw_indexed(0..4, 0, 0..1);
+        })
+        .unwrap();
+    }
 }
@@ -0,0 +1,287 @@
+use crate::{frame::Frame, packer::rect::Rect, target::Target, texture::RenderTargetTexture};
+use std::{cmp::Reverse, collections::BinaryHeap};
+use wgpu::TextureFormat;
+
+//
+
+pub mod prelude;
+
+//
+
+/// Handle to a texture in a [`RenderGraph`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GraphTexture(usize);
+
+/// Something that records passes into a [`Frame`]
+///
+/// Implemented for closures taking the same arguments.
+pub trait RenderNode {
+    fn record(&mut self, target: &mut Target, frame: &mut Frame, textures: &GraphTextures);
+}
+
+/// Chains render passes through transient textures
+///
+/// Nodes declare the textures they read and write
+/// and get recorded in dependency order. The
+/// transient textures are allocated at the frame size
+/// and recreated when the frame size changes.
+///
+/// Useful for stacking post-processing effects.
+#[derive(Default)]
+pub struct RenderGraph {
+    nodes: Vec<Node>,
+    formats: Vec<TextureFormat>,
+    textures: GraphTextures,
+
+    order: Option<Vec<usize>>,
+}
+
+/// Transient textures of a [`RenderGraph`]
+#[derive(Debug, Default)]
+pub struct GraphTextures {
+    dim: Option<Rect>,
+    textures: Vec<RenderTargetTexture>,
+}
+
+//
+
+struct Node {
+    reads: Vec<GraphTexture>,
+    writes: Vec<GraphTexture>,
+    node: Box<dyn RenderNode>,
+}
+
+//
+
+impl GraphTexture {
+    /// The primary render pass
+    ///
+    /// Can only be written to.
+    pub const PRIMARY: Self = Self(usize::MAX);
+}
+
+impl<F> RenderNode for F
+where
+    F: FnMut(&mut Target, &mut Frame, &GraphTextures),
+{
+    fn record(&mut self, target: &mut Target, frame: &mut Frame, textures: &GraphTextures) {
+        self(target, frame, textures)
+    }
+}
+
+impl RenderGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// New transient texture
+    ///
+    /// It is (re)created at the frame size
+    /// when the graph is executed.
+    pub fn texture(&mut self, format: TextureFormat) -> GraphTexture {
+        self.formats.push(format);
+        // allocate it on the next execute
+        self.textures.dim = None;
+        GraphTexture(self.formats.len() - 1)
+    }
+
+    /// Add a node that reads from `reads`
+    /// and writes to `writes`
+    ///
+    /// Nodes writing to the same texture are recorded
+    /// in insertion order. A node reads what the nodes
+    /// added before it wrote and is recorded before the
+    /// nodes added after it overwrite the texture. If
+    /// no earlier node writes it, every writer goes first.
+    pub fn add_node<F>(&mut self, reads: &[GraphTexture], writes: &[GraphTexture], node: F)
+    where
+        F: FnMut(&mut Target, &mut Frame, &GraphTextures) + 'static,
+    {
+        self.add_render_node(reads, writes, node)
+    }
+
+    /// [`Self::add_node`] for custom [`RenderNode`]s
+    pub fn add_render_node<N>(&mut self, reads: &[GraphTexture], writes: &[GraphTexture], node: N)
+    where
+        N: RenderNode + 'static,
+    {
+        if reads.contains(&GraphTexture::PRIMARY) {
+            panic!("Render graph nodes cannot read from the primary render pass");
+        }
+        if reads
+            .iter()
+            .chain(writes)
+            .any(|texture| *texture != GraphTexture::PRIMARY && texture.0 >= self.formats.len())
+        {
+            panic!("Texture does not belong to this render graph");
+        }
+
+        self.nodes.push(Node {
+            reads: reads.to_vec(),
+            writes: writes.to_vec(),
+            node: Box::new(node),
+        });
+        self.order = None;
+    }
+
+    pub fn get_textures(&self) -> &GraphTextures {
+        &self.textures
+    }
+
+    /// Record every node into `frame`
+    pub fn execute(&mut self, target: &mut Target, frame: &mut Frame) {
+        let dim = Rect::from(frame.get_dim());
+        let dim = Rect::new(dim.width.max(1), dim.height.max(1));
+        if self.textures.dim != Some(dim) {
+            self.textures.dim = Some(dim);
+            self.textures.textures = self
+                .formats
+                .iter()
+                .map(|&format| RenderTargetTexture::new_format(target, dim, format))
+                .collect();
+        }
+
+        let order = self.order.get_or_insert_with(|| order(&self.nodes));
+        for &i in order.iter() {
+            self.nodes[i].node.record(target, frame, &self.textures);
+        }
+    }
+}
+
+impl GraphTextures {
+    pub fn get(&self, texture: GraphTexture) -> &RenderTargetTexture {
+        if texture == GraphTexture::PRIMARY {
+            panic!("The primary render pass is not a texture, use `Frame::primary_render_pass`");
+        }
+        self.textures
+            .get(texture.0)
+            .expect("Render graph was not executed yet")
+    }
+
+    pub fn get_dim(&self) -> Option<Rect> {
+        self.dim
+    }
+}
+
+//
+
+/// Topological order of `nodes`,
+/// ties are broken by insertion order
+fn order(nodes: &[Node]) -> Vec<usize> {
+    let mut edges = vec![vec![]; nodes.len()];
+    let mut incoming = vec![0; nodes.len()];
+    let mut edge = |from: usize, to: usize| {
+        if from != to && !edges[from].contains(&to) {
+            edges[from].push(to);
+            incoming[to] += 1;
+        }
+    };
+
+    for (i, a) in nodes.iter().enumerate() {
+        for (j, b) in nodes.iter().enumerate().skip(i + 1) {
+            // write after write
+            if a.writes.iter().any(|texture| b.writes.contains(texture)) {
+                edge(i, j);
+            }
+        }
+
+        for texture in a.reads.iter() {
+            let writes = |node: &Node| node.writes.contains(texture);
+            let written_before = nodes[..i].iter().any(writes);
+            for (j, _) in nodes.iter().enumerate().filter(|(_, b)| writes(b)) {
+                if j < i || !written_before {
+                    // read after write
+                    edge(j, i);
+                } else {
+                    // write after read
+                    edge(i, j);
+                }
+            }
+        }
+    }
+
+    let mut ready: BinaryHeap<Reverse<usize>> = incoming
+        .iter()
+        .enumerate()
+        .filter(|(_, count)| **count == 0)
+        .map(|(i, _)| Reverse(i))
+        .collect();
+    let mut order = Vec::with_capacity(nodes.len());
+    while let Some(Reverse(i)) = ready.pop() {
+        order.push(i);
+        for &j in edges[i].iter() {
+            incoming[j] -= 1;
+            if incoming[j] == 0 {
+                ready.push(Reverse(j));
+            }
+        }
+    }
+
+    if order.len() != nodes.len() {
+        panic!("Render graph has a cycle");
+    }
+    order
+}
+
+//
+
+#[cfg(test)]
+mod test {
+    use super::{order, GraphTexture, Node};
+
+    fn node(reads: &[usize], writes: &[usize]) -> Node {
+        let texture = |&i: &usize| GraphTexture(i);
+        Node {
+            reads: reads.iter().map(texture).collect(),
+            writes: writes.iter().map(texture).collect(),
+            node: Box::new(|_: &mut _, _: &mut _, _: &_| {}),
+        }
+    }
+
+    #[test]
+    fn dependency_order() {
+        // composite <- bloom <- scene, inserted backwards
+        let nodes = [
+            node(&[0, 1], &[usize::MAX]),
+            node(&[0], &[1]),
+            node(&[], &[0]),
+            node(&[], &[2]),
+        ];
+        assert_eq!(order(&nodes), [2, 1, 0, 3]);
+    }
+
+    #[test]
+    fn same_target_insertion_order() {
+        let nodes = [node(&[], &[0]), node(&[], &[0]), node(&[0], &[1])];
+        assert_eq!(order(&nodes), [0, 1, 2]);
+    }
+
+    #[test]
+    fn write_after_read() {
+        // scene, horizontal and vertical blur ping-ponging
+        // between 0 and 1, then a composite of the result
+        let nodes = [
+            node(&[], &[0]),
+            node(&[0], &[1]),
+            node(&[1], &[0]),
+            node(&[0], &[usize::MAX]),
+        ];
+        assert_eq!(order(&nodes), [0, 1, 2, 3]);
+
+        // 2 overwrites 0 only after 1 read it,
+        // even though 1 waits for 3
+        let nodes = [
+            node(&[], &[0]),
+            node(&[0, 1], &[usize::MAX]),
+            node(&[], &[0]),
+            node(&[], &[1]),
+        ];
+        assert_eq!(order(&nodes), [0, 3, 1, 2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "cycle")]
+    fn cycle() {
+        order(&[node(&[0], &[1]), node(&[1], &[0])]);
+    }
+}
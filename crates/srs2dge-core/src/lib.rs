@@ -7,6 +7,7 @@
 //
 
 use main_game_loop::event::EventLoopTarget;
+use packer::rect::Rect;
 use std::sync::{Arc, RwLock};
 use target::Target;
 use wgpu::{util::backend_bits_from_env, Adapter, Backends, Device, Instance, Queue};
@@ -41,10 +42,13 @@ pub mod batch;
 pub mod buffer;
 pub mod color;
 pub mod frame;
+pub mod graph;
 pub mod packer;
 pub mod prelude;
 pub mod shader;
 pub mod target;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod texture;
 pub mod util;
 
@@ -58,6 +62,7 @@ pub struct Engine {
     instance: Arc<Instance>,
 
     device_storage: DeviceStorage,
+    compute: bool,
 }
 
 //
@@ -68,6 +73,7 @@ impl Default for Engine {
             instance: Self::make_instance(),
 
             device_storage: Default::default(),
+            compute: false,
         }
     }
 }
@@ -77,6 +83,16 @@ impl Engine {
         Self::default()
     }
 
+    /// Request the storage buffer limits compute
+    /// shaders need on adapters that support them
+    ///
+    /// Off by default, devices are created
+    /// with the WebGL2 limits otherwise.
+    pub fn with_compute(mut self) -> Self {
+        self.compute = true;
+        self
+    }
+
     pub async fn new_target(&self, window: Arc<Window>) -> Target {
         #[cfg(target_arch = "wasm32")]
         {
@@ -91,7 +107,13 @@ impl Engine {
                 .unwrap();
         }
 
-        Target::new(self.instance.clone(), window, self.device_storage.clone()).await
+        Target::new(
+            self.instance.clone(),
+            window,
+            self.device_storage.clone(),
+            self.compute,
+        )
+        .await
     }
 
     pub async fn new_target_element_id(&self, window: Arc<Window>, canvas_div_id: &str) -> Target {
@@ -109,7 +131,13 @@ impl Engine {
                 .unwrap();
         }
 
-        Target::new(self.instance.clone(), window, self.device_storage.clone()).await
+        Target::new(
+            self.instance.clone(),
+            window,
+            self.device_storage.clone(),
+            self.compute,
+        )
+        .await
     }
 
     pub async fn new_target_default(&self, target: &EventLoopTarget) -> Result<Target, OsError> {
@@ -123,8 +151,23 @@ impl Engine {
             .await)
     }
 
+    /// Target without a window that renders
+    /// frames into an offscreen texture of size `dim`
+    pub async fn new_target_offscreen(&self, dim: Rect) -> Target {
+        Target::new_headless(
+            self.instance.clone(),
+            self.device_storage.clone(),
+            dim,
+            self.compute,
+        )
+        .await
+    }
+
+    /// Same as `new_target_offscreen(Rect::new(1, 1))`,
+    /// frames are a single pixel
+    #[deprecated(note = "renders 1x1 frames, use `new_target_offscreen` to pick the frame size")]
     pub async fn new_target_headless(&self) -> Target {
-        Target::new_headless(self.instance.clone(), self.device_storage.clone()).await
+        self.new_target_offscreen(Rect::new(1, 1)).await
     }
 
     fn make_instance() -> Arc<Instance> {
@@ -90,15 +90,16 @@ impl Packer {
 
         // add width to all rows
         if rect.width != 0 {
+            let x = self.rect.width - rect.width;
             for row in self.rows.iter_mut() {
-                if let Some(last) = row.free_spaces.last_mut() {
-                    // last free space
-                    last.width += rect.width;
-                } else {
-                    // add one free space, because there weren't any
-                    let x = self.rect.width - rect.width;
-                    let width = rect.width;
-                    row.free_spaces.push(Space { x, width });
+                match row.free_spaces.last_mut() {
+                    // last free space reaches the old edge
+                    Some(last) if last.x + last.width == x => last.width += rect.width,
+                    // add one free space, because the edge was used
+                    _ => row.free_spaces.push(Space {
+                        x,
+                        width: rect.width,
+                    }),
                 }
             }
         }
@@ -240,7 +241,7 @@ impl Packer {
             (false, _) => {
                 let a = Space {
                     x: x + rect.width + pad,
-                    width: w - rect.width + pad,
+                    width: w - rect.width - pad,
                 };
                 self.rows[row].free_spaces[col] = a;
             }
@@ -303,60 +304,68 @@ impl Packer {
 
     #[inline]
     fn aabb_1d(x1: u32, x2: u32, w1: u32, w2: u32) -> bool {
-        // 2 * (x1 + w1 / 2).abs_diff(x2 + w1 / 2) < w1 + w2
-        x2 <= x1 + w1 && x2 + w2 > x1
+        x2 < x1 + w1 && x2 + w2 > x1
     }
 
-    #[inline]
-    fn remove_at_line(row: &mut Row, rect: &PositionedRect) -> bool {
-        if !Self::aabb_1d(row.y, rect.y, row.height, rect.height) {
-            return false;
-        }
-
-        // row.free_spaces
-        //     .drain_filter(|col| Self::aabb_1d(col.x, rect.x, col.width, rect.width));
-        let mut tmp = Default::default();
-        std::mem::swap(&mut tmp, &mut row.free_spaces);
-        row.free_spaces = tmp
-            .into_iter()
-            .partition(|col| Self::aabb_1d(col.x, rect.x, col.width, rect.width))
-            .1;
+    /// Mark `width` pixels from `x` in `row` free,
+    /// merging them with the free spaces they touch
+    fn release(row: &mut Row, x: u32, width: u32) {
+        let (mut start, mut end) = (x, x + width);
+        row.free_spaces.retain(|space| {
+            let touches = space.x <= end && start <= space.x + space.width;
+            if touches {
+                start = start.min(space.x);
+                end = end.max(space.x + space.width);
+            }
+            !touches
+        });
 
-        if row.free_spaces.is_empty() {
-            true
-        } else {
-            row.free_spaces.push(Space {
-                x: rect.x,
-                width: rect.width,
-            });
-            false
-        }
+        let index = row
+            .free_spaces
+            .iter()
+            .position(|space| space.x > start)
+            .unwrap_or(row.free_spaces.len());
+        row.free_spaces.insert(
+            index,
+            Space {
+                x: start,
+                width: end - start,
+            },
+        );
     }
 
-    /// Remove all quads that collide with `rect`.
+    /// Release the space of `rect` so that it can be pushed into again.
+    ///
+    /// `rect` is usually one returned by [`Self::push`],
+    /// larger rects release every quad they cover.
     pub fn remove(&mut self, rect: PositionedRect) {
-        let last_merged = self
+        if rect.width == 0 || rect.height == 0 {
+            return;
+        }
+
+        // pushed quads use their padding too
+        let pad = self.padding as u32;
+        let end = (rect.x + rect.width + pad).min(self.rect.width);
+        if rect.x >= end {
+            return;
+        }
+        for row in self
             .rows
             .iter_mut()
-            .enumerate()
-            .filter_map(|(index, row)| {
-                if Self::remove_at_line(row, &rect) {
-                    None
-                } else {
-                    Some(index)
-                }
-            })
-            .last()
-            .unwrap_or(0);
-
-        self.rows.drain(last_merged..);
+            .filter(|row| Self::aabb_1d(row.y, rect.y, row.height, rect.height))
+        {
+            Self::release(row, rect.x, end - rect.x);
+        }
 
-        if let Some(last) = self.rows.last() {
-            self.bottom.y = last.y;
-            self.bottom.height = self.rect.height - last.y - last.height;
-        } else {
-            self.bottom.y = 0;
-            self.bottom.height = self.rect.height;
+        // empty rows at the bottom go back to the unused area
+        let empty = [Space {
+            x: 0,
+            width: self.rect.width,
+        }];
+        while let Some(row) = self.rows.last().filter(|row| row.free_spaces == empty) {
+            self.bottom.y = row.y;
+            self.bottom.height += row.height;
+            self.rows.pop();
         }
     }
 }
@@ -473,6 +482,30 @@ mod test {
         gen_test! { packer, 10, 10 };
     }
 
+    #[test]
+    pub fn test_remove_reuse() {
+        let mut packer = Packer::new(Rect::new(20, 20));
+        gen_test! { packer, 10, 10 => 0, 0 };
+        gen_test! { packer, 10, 10 => 10, 0 };
+        gen_test! { packer, 10, 10 => 0, 10 };
+
+        gen_test! { packer, 10, 10 ; 10, 0 };
+        gen_test! { packer, 10, 10 => 10, 0 };
+        gen_test! { packer, 10, 10 ; 0, 0 };
+        gen_test! { packer, 10, 10 ; 10, 0 };
+        gen_test! { packer, 20, 10 => 0, 0 };
+
+        // the free space left of a used one does not grow
+        let mut packer = Packer::new(Rect::new(20, 10));
+        gen_test! { packer, 10, 10 => 0, 0 };
+        gen_test! { packer, 10, 10 => 10, 0 };
+        gen_test! { packer, 10, 10 ; 0, 0 };
+        packer.alloc_more(Rect::new(10, 0));
+        gen_test! { packer, 20, 10 };
+        gen_test! { packer, 10, 10 => 0, 0 };
+        gen_test! { packer, 10, 10 => 20, 0 };
+    }
+
     #[test]
     pub fn test_multi_remove() {
         for _ in 0..100 {
@@ -629,6 +662,14 @@ mod test {
         gen_test! { packer, 10, 10 };
     }
 
+    #[test]
+    pub fn test_push_padding() {
+        let mut packer = Packer::new(Rect::new(20, 10)).with_padding(2);
+        gen_test! { packer, 6, 6 => 0, 0 };
+        gen_test! { packer, 6, 6 => 8, 0 };
+        gen_test! { packer, 6, 6 };
+    }
+
     /* #[bench]
     pub fn bench_packing(bencher: &mut Bencher) {
         let mut packer = Packer::new(Rect {
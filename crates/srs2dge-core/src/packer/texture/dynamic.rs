@@ -0,0 +1,359 @@
+use super::USAGE;
+use crate::{
+    label,
+    prelude::{Packer, PositionedRect, Rect, Target, TexturePosition},
+    texture::Texture,
+};
+use image::{DynamicImage, RgbaImage};
+use std::{collections::HashMap, hash::Hash, ops::Deref};
+use wgpu::{CommandEncoderDescriptor, ImageCopyTexture, Origin3d, TextureAspect, TextureFormat};
+
+//
+
+/// Handle to an image in a [`DynamicTextureAtlas`]
+///
+/// Stays valid when the atlas grows, but
+/// not after the image is removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AtlasHandle {
+    index: u32,
+    generation: u32,
+}
+
+/// Texture atlas that images can be added to and
+/// removed from at runtime
+///
+/// The texture is replaced when the atlas grows,
+/// so [`TexturePosition`]s and bind groups have to
+/// be refreshed whenever [`Self::revision`] changes.
+/// [`AtlasHandle`]s always point to the current position.
+#[derive(Debug)]
+pub struct DynamicTextureAtlas<K>
+where
+    K: Eq + Hash + Clone,
+{
+    texture: Texture<USAGE>,
+    packer: Packer,
+
+    // side length limit and the
+    // device limit it is clamped to
+    limit: u32,
+    max_limit: u32,
+
+    slots: Vec<Slot>,
+    free_slots: Vec<u32>,
+    map: HashMap<K, AtlasHandle>,
+
+    revision: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    generation: u32,
+    pos: Option<PositionedRect>,
+}
+
+//
+
+impl<K> DynamicTextureAtlas<K>
+where
+    K: Eq + Hash + Clone,
+{
+    pub fn new(target: &Target, dim: Rect) -> Self {
+        if dim.width == 0 || dim.height == 0 {
+            panic!("Dynamic texture atlas cannot be empty");
+        }
+
+        let max_limit = target.device.limits().max_texture_dimension_2d;
+        if dim.width > max_limit || dim.height > max_limit {
+            panic!("Dynamic texture atlas is larger than the device allows");
+        }
+
+        Self {
+            texture: Texture::new(target, TextureFormat::Rgba8Unorm, dim),
+            packer: Packer::new(dim).with_padding(2),
+            limit: max_limit,
+            max_limit,
+            slots: vec![],
+            free_slots: vec![],
+            map: HashMap::new(),
+            revision: 0,
+        }
+    }
+
+    /// side length limit
+    ///
+    /// Defaults to and is clamped to the
+    /// `max_texture_dimension_2d` device limit.
+    pub fn with_limit(mut self, limit: u16) -> Self {
+        self.limit = (limit as u32).min(self.max_limit);
+        self
+    }
+
+    /// texture padding
+    pub fn with_padding(mut self, padding: u8) -> Self {
+        if !self.map.is_empty() {
+            panic!("Padding has to be set before inserting images");
+        }
+        self.packer.padding = padding;
+        self
+    }
+
+    /// Insert `image` into free space, replacing
+    /// the previous image of `key`
+    ///
+    /// Grows the texture if needed. Returns `None`
+    /// if the side length limit was reached, the
+    /// previous image is kept in that case.
+    pub fn insert(&mut self, target: &Target, key: K, image: RgbaImage) -> Option<AtlasHandle> {
+        let pos = self.place(target, Rect::from(image.dimensions()))?;
+        self.remove(&key);
+        if pos.width != 0 && pos.height != 0 {
+            self.texture
+                .write(target, pos, DynamicImage::ImageRgba8(image))
+                .expect("Atlas spot does not match the image");
+        }
+
+        let handle = match self.free_slots.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index as usize];
+                slot.pos = Some(pos);
+                AtlasHandle {
+                    index,
+                    generation: slot.generation,
+                }
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    pos: Some(pos),
+                });
+                AtlasHandle {
+                    index: self.slots.len() as u32 - 1,
+                    generation: 0,
+                }
+            }
+        };
+        self.map.insert(key, handle);
+
+        Some(handle)
+    }
+
+    /// Release the space of `key`
+    ///
+    /// Returns `false` if there was no such image.
+    pub fn remove(&mut self, key: &K) -> bool {
+        let handle = match self.map.remove(key) {
+            Some(handle) => handle,
+            None => return false,
+        };
+
+        let slot = &mut self.slots[handle.index as usize];
+        let pos = slot.pos.take().unwrap();
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free_slots.push(handle.index);
+
+        self.packer.remove(pos);
+        true
+    }
+
+    pub fn contains(&self, key: &K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    pub fn handle(&self, key: &K) -> Option<AtlasHandle> {
+        self.map.get(key).copied()
+    }
+
+    pub fn get(&self, key: &K) -> Option<TexturePosition> {
+        self.position(self.handle(key)?)
+    }
+
+    /// Current position of `handle`
+    ///
+    /// `None` if the image was removed.
+    pub fn position(&self, handle: AtlasHandle) -> Option<TexturePosition> {
+        let slot = self.slots.get(handle.index as usize)?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+        Some(TexturePosition::new(self.texture.get_dim(), slot.pos?))
+    }
+
+    /// Changes every time the texture is replaced
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    fn place(&mut self, target: &Target, rect: Rect) -> Option<PositionedRect> {
+        loop {
+            if let Some(pos) = self.packer.push(rect) {
+                return Some(pos);
+            }
+            if !self.grow(target) {
+                return None;
+            }
+        }
+    }
+
+    fn within_limit(&self, area: Rect) -> bool {
+        area.width <= self.limit && area.height <= self.limit
+    }
+
+    /// Expand the texture with [`Packer::next_pow2_square`]
+    ///
+    /// Images keep their pixel positions. Returns
+    /// `false` if the side length limit was reached.
+    fn grow(&mut self, target: &Target) -> bool {
+        let old = self.packer.area();
+        let mut packer = self.packer.clone();
+        packer.next_pow2_square();
+        if !self.within_limit(packer.area()) {
+            return false;
+        }
+        self.packer = packer;
+        let texture = Texture::new(target, TextureFormat::Rgba8Unorm, self.packer.area());
+
+        self.copy(
+            target,
+            &texture,
+            [(old.positioned(0, 0), Origin3d::ZERO)].into_iter(),
+        );
+        self.texture = texture;
+        self.revision += 1;
+        true
+    }
+
+    /// GPU side copy from the current texture to `texture`
+    fn copy<I>(&self, target: &Target, texture: &Texture<USAGE>, regions: I)
+    where
+        I: Iterator<Item = (PositionedRect, Origin3d)>,
+    {
+        let mut encoder = target
+            .device
+            .create_command_encoder(&CommandEncoderDescriptor { label: label!() });
+        for (from, to) in regions {
+            encoder.copy_texture_to_texture(
+                ImageCopyTexture {
+                    texture: self.texture.inner(),
+                    mip_level: 0,
+                    origin: Origin3d {
+                        x: from.x,
+                        y: from.y,
+                        z: 0,
+                    },
+                    aspect: TextureAspect::All,
+                },
+                ImageCopyTexture {
+                    texture: texture.inner(),
+                    mip_level: 0,
+                    origin: to,
+                    aspect: TextureAspect::All,
+                },
+                Rect::new(from.width, from.height).into(),
+            );
+        }
+        target.queue.submit([encoder.finish()]);
+    }
+}
+
+impl<K> Deref for DynamicTextureAtlas<K>
+where
+    K: Eq + Hash + Clone,
+{
+    type Target = Texture<USAGE>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.texture
+    }
+}
+
+//
+
+#[cfg(all(test, feature = "testing"))]
+mod test {
+    use super::DynamicTextureAtlas;
+    use crate::{packer::rect::Rect, Engine};
+    use image::{Rgba, RgbaImage};
+
+    #[test]
+    fn insert_remove_grow() {
+        let engine = Engine::new();
+        let target = pollster::block_on(engine.new_target_offscreen(Rect::new(4, 4)));
+        let square = |v: u8| RgbaImage::from_pixel(6, 6, Rgba([v, 255 - v, v / 2, 255]));
+        let check = |atlas: &DynamicTextureAtlas<u8>, keys: &[u8]| {
+            let image = pollster::block_on(atlas.read(&target))
+                .unwrap()
+                .into_rgba8();
+            let (w, h) = image.dimensions();
+            for &key in keys {
+                let pos = atlas.get(&key).unwrap();
+                let x = (pos.top_left.x * w as f32) as u32;
+                let y = (pos.top_left.y * h as f32) as u32;
+                assert_eq!(
+                    (pos.bottom_right - pos.top_left) * glam::Vec2::new(w as f32, h as f32),
+                    glam::Vec2::new(6.0, 6.0)
+                );
+                for (xo, yo) in [(0, 0), (5, 0), (0, 5), (5, 5)] {
+                    assert_eq!(
+                        image.get_pixel(x + xo, y + yo),
+                        square(key * 20).get_pixel(0, 0)
+                    );
+                }
+            }
+        };
+
+        let mut atlas = DynamicTextureAtlas::new(&target, Rect::new(16, 16));
+        let a = atlas.insert(&target, 0, square(0)).unwrap();
+        atlas.insert(&target, 1, square(20)).unwrap();
+        assert_eq!(atlas.revision(), 0);
+        check(&atlas, &[0]);
+
+        // does not fit anymore
+        let revision = atlas.revision();
+        for key in 2..6 {
+            atlas.insert(&target, key, square(key * 20)).unwrap();
+        }
+        assert!(atlas.revision() > revision);
+        assert!(atlas.get_dim().width > 16);
+        assert!(atlas.position(a).is_some());
+        check(&atlas, &[0, 1, 2, 3, 4, 5]);
+
+        assert!(atlas.remove(&0));
+        assert!(!atlas.remove(&0));
+        assert_eq!(atlas.position(a), None);
+        assert_eq!(atlas.get(&0), None);
+
+        for key in 6..12 {
+            atlas.insert(&target, key, square(key * 20)).unwrap();
+        }
+        let revision = atlas.revision();
+        atlas.remove(&3);
+        atlas.insert(&target, 3, square(3 * 20)).unwrap();
+        // fits into the released space
+        assert_eq!(atlas.revision(), revision);
+        atlas.insert(&target, 12, RgbaImage::new(0, 0)).unwrap();
+        assert_eq!(atlas.len(), 12);
+        check(&atlas, &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]);
+
+        let mut limited = DynamicTextureAtlas::new(&target, Rect::new(8, 8)).with_limit(8);
+        assert!(limited.insert(&target, 0, square(0)).is_some());
+        assert!(limited.insert(&target, 1, square(0)).is_none());
+        // failed replacements keep the old image
+        assert!(limited.insert(&target, 0, RgbaImage::new(12, 12)).is_none());
+        check(&limited, &[0]);
+
+        let max = target.device.limits().max_texture_dimension_2d;
+        let atlas = DynamicTextureAtlas::<u8>::new(&target, Rect::new(8, 8));
+        assert_eq!(atlas.limit, max);
+        assert_eq!(atlas.with_limit(u16::MAX).limit, max.min(u16::MAX as u32));
+    }
+}
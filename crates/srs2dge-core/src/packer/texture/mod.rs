@@ -11,10 +11,12 @@ use wgpu::TextureUsages;
 
 //
 
+pub use dynamic::*;
 pub use map::*;
 
 //
 
+mod dynamic;
 mod map;
 
 //
@@ -134,7 +136,12 @@ impl TextureAtlas {
     }
 
     pub async fn convert(&self, target: &Target) -> TextureAtlasFile {
-        let image = self.texture.read(target).await.into_rgba8();
+        let image = self
+            .texture
+            .read(target)
+            .await
+            .expect("Atlas textures are readable Rgba8 textures")
+            .into_rgba8();
         TextureAtlasFile { image }
     }
 }
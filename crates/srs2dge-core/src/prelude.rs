@@ -1,6 +1,6 @@
 pub use crate::{
-    batch::prelude::*, buffer::prelude::*, color::*, frame::prelude::*, packer::prelude::*,
-    shader::prelude::*, target::prelude::*, texture::prelude::*, util::*, *,
+    batch::prelude::*, buffer::prelude::*, color::*, frame::prelude::*, graph::prelude::*,
+    packer::prelude::*, shader::prelude::*, target::prelude::*, texture::prelude::*, util::*, *,
 };
 
 pub use winit::{
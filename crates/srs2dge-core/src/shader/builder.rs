@@ -1,21 +1,28 @@
-use super::{layout::AutoLayout, module::ShaderModule, Shader};
+use super::{error::ShaderError, layout::AutoLayout, module::ShaderModule, Shader};
 use crate::{
     buffer::{
         index::{DefaultIndex, Index},
-        vertex::{DefaultVertex, Vertex},
+        vertex::{DefaultVertex, VertexSlots},
     },
     label,
     target::Target,
+    texture::sampler::Sampler,
 };
-use std::marker::PhantomData;
+use std::{marker::PhantomData, mem};
 use wgpu::{
-    BlendState, ColorTargetState, ColorWrites, FragmentState, FrontFace, MultisampleState,
-    PipelineLayoutDescriptor, PolygonMode, PrimitiveState, PrimitiveTopology,
-    RenderPipelineDescriptor, TextureFormat, VertexState,
+    BlendState, ColorTargetState, ColorWrites, DepthStencilState, Face, Features, FragmentState,
+    FrontFace, MultisampleState, PipelineLayoutDescriptor, PolygonMode, PrimitiveState,
+    PrimitiveTopology, RenderPipelineDescriptor, SamplerBindingType, ShaderStages, TextureFormat,
+    TextureFormatFeatureFlags, TextureSampleType, TextureUsages, VertexState,
 };
 
 //
 
+/// max number of color targets
+pub const MAX_COLOR_TARGETS: usize = 8;
+
+//
+
 pub struct ShaderBuilder<
     's,
     V = DefaultVertex,
@@ -26,9 +33,25 @@ pub struct ShaderBuilder<
 > {
     pub(crate) vert: Option<(&'s ShaderModule<'s>, &'s str)>,
     pub(crate) frag: Option<(&'s ShaderModule<'s>, &'s str)>,
-    format: Option<TextureFormat>,
+    targets: Vec<ColorTargetState>,
+    // `with_target_*` overrides, applied in `try_build`
+    // once the color targets are known
+    target_blends: Vec<(usize, Option<BlendState>)>,
+    target_write_masks: Vec<(usize, ColorWrites)>,
+    blend: Option<BlendState>,
+    write_mask: ColorWrites,
+    depth_stencil: Option<DepthStencilState>,
+    sample_count: Option<u32>,
     layout: Option<PipelineLayoutDescriptor<'s>>,
+    // (group, binding) and size of the Rust uniform types
+    uniforms: Vec<((u32, u32), u64)>,
+    dynamic_offsets: Vec<(u32, u32)>,
+    samplers: Vec<((u32, u32), SamplerBindingType)>,
     topology: PrimitiveTopology,
+    cull_mode: Option<Face>,
+    front_face: FrontFace,
+    polygon_mode: PolygonMode,
+    filtering: bool,
     label: Option<&'s str>,
 
     _p: PhantomData<(V, I)>,
@@ -43,9 +66,22 @@ impl<'s, V, I, const VS: bool, const FS: bool, const FMT: bool> Default
         Self {
             vert: None,
             frag: None,
-            format: None,
+            targets: vec![],
+            target_blends: vec![],
+            target_write_masks: vec![],
+            blend: Some(BlendState::ALPHA_BLENDING),
+            write_mask: ColorWrites::ALL,
+            depth_stencil: None,
+            sample_count: None,
             layout: None,
-            topology: PrimitiveTopology::TriangleStrip,
+            uniforms: vec![],
+            dynamic_offsets: vec![],
+            samplers: vec![],
+            topology: PrimitiveTopology::TriangleList,
+            cull_mode: None,
+            front_face: FrontFace::Ccw,
+            polygon_mode: PolygonMode::Fill,
+            filtering: false,
             label: label!(),
 
             _p: PhantomData::default(),
@@ -66,9 +102,22 @@ impl<'s, V, I, const VS: bool, const FS: bool, const FMT: bool>
         ShaderBuilder {
             vert: self.vert,
             frag: self.frag,
-            format: self.format,
+            targets: self.targets,
+            target_blends: self.target_blends,
+            target_write_masks: self.target_write_masks,
+            blend: self.blend,
+            write_mask: self.write_mask,
+            depth_stencil: self.depth_stencil,
+            sample_count: self.sample_count,
             layout: self.layout,
+            uniforms: self.uniforms,
+            dynamic_offsets: self.dynamic_offsets,
+            samplers: self.samplers,
             topology: self.topology,
+            cull_mode: self.cull_mode,
+            front_face: self.front_face,
+            polygon_mode: self.polygon_mode,
+            filtering: self.filtering,
             label: self.label,
 
             _p: PhantomData::default(),
@@ -98,12 +147,77 @@ impl<'s, V, I, const VS: bool, const FS: bool, const FMT: bool>
     }
 
     pub fn with_format(self, format: TextureFormat) -> ShaderBuilder<'s, V, I, VS, FS, true> {
+        self.with_formats(&[format])
+    }
+
+    /// One color target per format for multiple render targets
+    ///
+    /// The targets use the blend state and write mask
+    /// set with [`Self::with_blend`] and [`Self::with_write_mask`].
+    pub fn with_formats(self, formats: &[TextureFormat]) -> ShaderBuilder<'s, V, I, VS, FS, true> {
+        let targets = formats
+            .iter()
+            .map(|&format| ColorTargetState {
+                format,
+                blend: self.blend,
+                write_mask: self.write_mask,
+            })
+            .collect();
+        self.with_color_targets(targets)
+    }
+
+    /// Full control over every color target
+    pub fn with_color_targets(
+        self,
+        targets: Vec<ColorTargetState>,
+    ) -> ShaderBuilder<'s, V, I, VS, FS, true> {
         ShaderBuilder {
-            format: Some(format),
+            targets,
             ..self.pass()
         }
     }
 
+    /// Blend state of every color target
+    ///
+    /// Defaults to `BlendState::ALPHA_BLENDING`.
+    /// `None` replaces the target pixels.
+    pub fn with_blend(mut self, blend: Option<BlendState>) -> Self {
+        self.blend = blend;
+        self.target_blends.clear();
+        for target in self.targets.iter_mut() {
+            target.blend = blend;
+        }
+        self
+    }
+
+    /// Blend state of the color target at `index`
+    ///
+    /// Can be called before the color targets are set,
+    /// `build` fails if there is no target at `index`.
+    pub fn with_target_blend(mut self, index: usize, blend: Option<BlendState>) -> Self {
+        self.target_blends.push((index, blend));
+        self
+    }
+
+    /// Write mask of every color target
+    pub fn with_write_mask(mut self, write_mask: ColorWrites) -> Self {
+        self.write_mask = write_mask;
+        self.target_write_masks.clear();
+        for target in self.targets.iter_mut() {
+            target.write_mask = write_mask;
+        }
+        self
+    }
+
+    /// Write mask of the color target at `index`
+    ///
+    /// Can be called before the color targets are set,
+    /// `build` fails if there is no target at `index`.
+    pub fn with_target_write_mask(mut self, index: usize, write_mask: ColorWrites) -> Self {
+        self.target_write_masks.push((index, write_mask));
+        self
+    }
+
     pub fn with_vertex_format<Vn>(self) -> ShaderBuilder<'s, Vn, I, VS, FS, true> {
         ShaderBuilder { ..self.pass() }
     }
@@ -112,16 +226,105 @@ impl<'s, V, I, const VS: bool, const FS: bool, const FMT: bool>
         ShaderBuilder { ..self.pass() }
     }
 
+    /// The render pass has to use a
+    /// depth/stencil attachment of the same format
+    pub fn with_depth_stencil(mut self, depth_stencil: Option<DepthStencilState>) -> Self {
+        self.depth_stencil = depth_stencil;
+        self
+    }
+
+    /// MSAA sample count of the render pass
+    ///
+    /// Defaults to `Target::get_msaa` of the target given
+    /// to `build`, which is what the primary render pass
+    /// uses. Only needed for offscreen render passes.
+    pub fn with_sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = Some(sample_count);
+        self
+    }
+
+    /// Defaults to `TriangleList`, the topology of the built-in
+    /// quad meshes, strips don't need a restart index
     pub fn with_topology(mut self, topology: PrimitiveTopology) -> Self {
         self.topology = topology;
         self
     }
 
+    pub fn with_cull_mode(mut self, cull_mode: Option<Face>) -> Self {
+        self.cull_mode = cull_mode;
+        self
+    }
+
+    pub fn with_front_face(mut self, front_face: FrontFace) -> Self {
+        self.front_face = front_face;
+        self
+    }
+
+    /// `PolygonMode::Line` and `PolygonMode::Point`
+    /// require device features
+    pub fn with_polygon_mode(mut self, polygon_mode: PolygonMode) -> Self {
+        self.polygon_mode = polygon_mode;
+        self
+    }
+
+    /// Whether the generated layout uses filtering samplers
+    /// and filterable float textures for the textures
+    /// sampled with them, off by default
+    ///
+    /// Ignored with a baked layout. Use [`Self::with_sampler`]
+    /// to pick the sampler type of a single binding.
+    pub fn with_filtering(mut self, filtering: bool) -> Self {
+        self.filtering = filtering;
+        self
+    }
+
     pub fn with_label<'n: 's>(mut self, label: Option<&'n str>) -> Self {
         self.label = label;
         self
     }
 
+    /// `build` checks that the uniform at `@group(group)
+    /// @binding(binding)` has the size of `U`
+    ///
+    /// Catches Rust structs that are missing
+    /// the padding of the WGSL struct. Shaders
+    /// that don't declare the binding fail too.
+    pub fn with_uniform<U>(mut self, group: u32, binding: u32) -> Self {
+        self.uniforms
+            .push(((group, binding), mem::size_of::<U>() as u64));
+        self
+    }
+
+    /// The generated layout binds the buffer at
+    /// `@group(group) @binding(binding)` with a dynamic offset
+    ///
+    /// Pass the offsets to `RenderPass::bind_group_at`.
+    /// Ignored with a baked layout.
+    pub fn with_dynamic_offset(mut self, group: u32, binding: u32) -> Self {
+        self.dynamic_offsets.push((group, binding));
+        self
+    }
+
+    /// The generated layout binds the sampler at
+    /// `@group(group) @binding(binding)` with the
+    /// binding type of `sampler`
+    ///
+    /// Overrides [`Self::with_filtering`] for that binding.
+    /// Ignored with a baked layout.
+    pub fn with_sampler(self, group: u32, binding: u32, sampler: &Sampler) -> Self {
+        self.with_sampler_type(group, binding, sampler.binding_type())
+    }
+
+    /// See [`Self::with_sampler`]
+    ///
+    /// `build` fails with [`ShaderError::LayoutMismatch`] if
+    /// the shader has no sampler at the binding or if only
+    /// one of them is a comparison sampler.
+    pub fn with_sampler_type(mut self, group: u32, binding: u32, ty: SamplerBindingType) -> Self {
+        self.samplers.push(((group, binding), ty));
+        self
+    }
+
     pub fn with_baked_layout<'l: 's>(
         self,
         layout: PipelineLayoutDescriptor<'l>,
@@ -135,21 +338,49 @@ impl<'s, V, I, const VS: bool, const FS: bool, const FMT: bool>
 
 impl<'s, V, I> ShaderBuilder<'s, V, I, true, true, true>
 where
-    V: Vertex,
+    V: VertexSlots,
     I: Index,
 {
+    /// Panics if [`Self::try_build`] fails
     pub fn build(self, target: &Target) -> Shader<V, I> {
+        self.try_build(target)
+            .unwrap_or_else(|err| panic!("Invalid shader: {err}"))
+    }
+
+    /// Compares the vertex layouts of `V` to the vertex
+    /// shader inputs and the uniform sizes given with
+    /// [`Self::with_uniform`] to the shader's uniforms
+    /// before creating the pipeline
+    ///
+    /// wgpu validation errors are caught and returned
+    /// as [`ShaderError::Validation`].
+    pub fn try_build(self, target: &Target) -> Result<Shader<V, I>, ShaderError> {
         let (vert_mod, vert_entry) = self.vert.unwrap();
         let (frag_mod, frag_entry) = self.frag.unwrap();
-        let format = self.format.unwrap();
-
-        let layout = match self.layout {
-            Some(l) => target.device.create_pipeline_layout(&l),
-            None => {
-                let a = AutoLayout::new(target, (vert_mod, vert_entry), (frag_mod, frag_entry));
-                let a = a.get();
-                target.device.create_pipeline_layout(&a.get())
-            }
+        let color_targets = self.color_targets()?;
+        self.validate(target, &color_targets)?;
+        let formats = color_targets.iter().map(|target| target.format).collect();
+        let targets: Vec<_> = color_targets.into_iter().map(Some).collect();
+        let buffers = V::layouts();
+
+        AutoLayout::check_entry_point((frag_mod, frag_entry), ShaderStages::FRAGMENT)?;
+        AutoLayout::check_vertex_inputs((vert_mod, vert_entry), &buffers)?;
+        for &(binding, size) in self.uniforms.iter() {
+            AutoLayout::check_uniform_size(&[vert_mod, frag_mod], binding, size)?;
+        }
+        let depth_format = self.depth_stencil.as_ref().map(|ds| ds.format);
+        let sample_count = self.sample_count.unwrap_or_else(|| target.get_msaa());
+
+        let auto_layout = match self.layout {
+            Some(_) => None,
+            None => Some(AutoLayout::try_new_with(
+                target,
+                (vert_mod, vert_entry),
+                (frag_mod, frag_entry),
+                self.filtering,
+                &self.dynamic_offsets,
+                &self.samplers,
+            )?),
         };
 
         let strip_index_format = if let PrimitiveTopology::LineStrip
@@ -160,44 +391,416 @@ where
             None
         };
 
-        let pipeline = target
-            .device
-            .create_render_pipeline(&RenderPipelineDescriptor {
-                label: self.label,
-                layout: Some(&layout),
-                vertex: VertexState {
-                    module: &vert_mod.inner,
-                    entry_point: vert_entry,
-                    buffers: V::LAYOUT,
-                },
-                primitive: PrimitiveState {
-                    topology: self.topology,
-                    strip_index_format,
-                    front_face: FrontFace::Ccw,
-                    cull_mode: None,
-                    unclipped_depth: false,
-                    polygon_mode: PolygonMode::Fill,
-                    conservative: false,
-                },
-                depth_stencil: None,
-                multisample: MultisampleState::default(),
-                fragment: Some(FragmentState {
-                    module: &frag_mod.inner,
-                    entry_point: frag_entry,
-                    targets: &[Some(ColorTargetState {
-                        format,
-                        blend: Some(BlendState::ALPHA_BLENDING),
-                        write_mask: ColorWrites::ALL,
-                    })],
-                }),
-                multiview: None,
-            });
-
-        Shader {
+        let pipeline = target.catch_error(|target| {
+            let layout = match (&self.layout, &auto_layout) {
+                (Some(l), _) => target.device.create_pipeline_layout(l),
+                (None, Some(a)) => target.device.create_pipeline_layout(&a.get().get()),
+                (None, None) => unreachable!(),
+            };
+
+            target
+                .device
+                .create_render_pipeline(&RenderPipelineDescriptor {
+                    label: self.label,
+                    layout: Some(&layout),
+                    vertex: VertexState {
+                        module: &vert_mod.inner,
+                        entry_point: vert_entry,
+                        buffers: &buffers,
+                    },
+                    primitive: PrimitiveState {
+                        topology: self.topology,
+                        strip_index_format,
+                        front_face: self.front_face,
+                        cull_mode: self.cull_mode,
+                        unclipped_depth: false,
+                        polygon_mode: self.polygon_mode,
+                        conservative: false,
+                    },
+                    depth_stencil: self.depth_stencil,
+                    multisample: MultisampleState {
+                        count: sample_count,
+                        ..Default::default()
+                    },
+                    fragment: Some(FragmentState {
+                        module: &frag_mod.inner,
+                        entry_point: frag_entry,
+                        targets: &targets,
+                    }),
+                    multiview: None,
+                })
+        });
+        let pipeline = pipeline.map_err(ShaderError::Validation)?;
+
+        Ok(Shader {
             pipeline,
-            format,
+            formats,
+            depth_format,
+            sample_count,
 
             _p: PhantomData::default(),
+        })
+    }
+
+    /// The color targets with the `with_target_*` overrides
+    fn color_targets(&self) -> Result<Vec<ColorTargetState>, ShaderError> {
+        let mut targets = self.targets.clone();
+        let indices = self.target_blends.iter().map(|(index, _)| index);
+        let mut indices = indices.chain(self.target_write_masks.iter().map(|(index, _)| index));
+        if let Some(index) = indices.find(|&&index| index >= targets.len()) {
+            return Err(ShaderError::FormatMismatch(format!(
+                "No color target at index {index}, the shader has {}",
+                targets.len()
+            )));
+        }
+
+        for &(index, blend) in self.target_blends.iter() {
+            targets[index].blend = blend;
+        }
+        for &(index, write_mask) in self.target_write_masks.iter() {
+            targets[index].write_mask = write_mask;
+        }
+        Ok(targets)
+    }
+
+    fn validate(
+        &self,
+        target: &Target,
+        color_targets: &[ColorTargetState],
+    ) -> Result<(), ShaderError> {
+        let mismatch = |err: String| Err(ShaderError::FormatMismatch(err));
+        if color_targets.is_empty() {
+            return mismatch("No color target formats".to_string());
+        }
+        if color_targets.len() > MAX_COLOR_TARGETS {
+            return Err(ShaderError::Unsupported(format!(
+                "Too many color targets, max is {MAX_COLOR_TARGETS}"
+            )));
+        }
+
+        for color_target in color_targets.iter() {
+            let desc = color_target.format.describe();
+            let features = target.get_format_features(color_target.format);
+            if desc.sample_type == TextureSampleType::Depth {
+                return mismatch(format!(
+                    "Color target format {:?} is a depth format",
+                    color_target.format
+                ));
+            }
+            if !features
+                .allowed_usages
+                .contains(TextureUsages::RENDER_ATTACHMENT)
+            {
+                return mismatch(format!(
+                    "Color target format {:?} is not renderable",
+                    color_target.format
+                ));
+            }
+            // wgpu 0.13 has no blendable format flag,
+            // it checks FILTERABLE of the device instead
+            if color_target.blend.is_some()
+                && !features
+                    .flags
+                    .contains(TextureFormatFeatureFlags::FILTERABLE)
+            {
+                return mismatch(format!(
+                    "Color target format {:?} is not blendable",
+                    color_target.format
+                ));
+            }
+        }
+
+        let feature = match self.polygon_mode {
+            PolygonMode::Fill => Features::empty(),
+            PolygonMode::Line => Features::POLYGON_MODE_LINE,
+            PolygonMode::Point => Features::POLYGON_MODE_POINT,
+        };
+        if !target.device.features().contains(feature) {
+            return Err(ShaderError::Unsupported(format!(
+                "{:?} requires device feature {feature:?}",
+                self.polygon_mode
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+//
+
+#[cfg(all(test, feature = "testing"))]
+mod test {
+    use crate::{
+        buffer::{vertex::DefaultVertex, IndexBuffer, VertexBuffer},
+        color::Color,
+        packer::rect::Rect,
+        shader::{error::ShaderError, module::ShaderModule, Shader},
+        testing::{
+            fixture::{quad, SHADER as QUAD_SHADER},
+            GoldenImage,
+        },
+        Engine,
+    };
+    use glam::{Mat4, Vec4};
+    use wgpu::{
+        BlendComponent, BlendFactor, BlendOperation, BlendState, ColorWrites, Face, Features,
+        FrontFace, PipelineLayoutDescriptor, PolygonMode, PrimitiveTopology, SamplerBindingType,
+        ShaderStages, TextureFormat,
+    };
+
+    const SHADER: &str = r#"
+struct VertexInput {
+	@location(0) pos: vec2<f32>,
+	@location(2) col: vec4<f32>,
+};
+
+struct UniformInput {
+	mvp: mat4x4<f32>,
+	weight: f32,
+};
+
+@group(0)
+@binding(0)
+var<uniform> ubo: UniformInput;
+
+@vertex
+fn vs_main(vin: VertexInput) -> @builtin(position) vec4<f32> {
+	return ubo.mvp * vec4<f32>(vin.pos, ubo.weight, 1.0) * vin.col.x;
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+	return vec4<f32>(1.0);
+}
+"#;
+
+    #[test]
+    fn layout_mismatch() {
+        let engine = Engine::new();
+        let target = pollster::block_on(engine.new_target_offscreen(Rect::new(4, 4)));
+        let try_build = |source: &str, uniform_size: bool| {
+            let module = ShaderModule::new_wgsl_source(&target, source.into()).unwrap();
+            let builder = Shader::<DefaultVertex, u32>::builder()
+                .with_vertex(&module, "vs_main")
+                .with_fragment(&module, "fs_main")
+                .with_format(target.get_format());
+            let builder = if uniform_size {
+                builder.with_uniform::<(Mat4, Vec4)>(0, 0)
+            } else {
+                builder.with_uniform::<Mat4>(0, 0)
+            };
+            builder.try_build(&target).map(|_| ())
+        };
+
+        // the struct is padded to 80 bytes
+        assert_eq!(try_build(SHADER, true), Ok(()));
+        assert_eq!(
+            try_build(SHADER, false).unwrap_err(),
+            ShaderError::LayoutMismatch(
+                "Uniform `ubo` at @group(0) @binding(0) is 80 bytes but the uniform type is 64 bytes".to_string()
+            )
+        );
+        assert_eq!(
+            try_build(
+                &SHADER
+                    .replace("ubo.mvp * ", "")
+                    .replace(", ubo.weight", ", 0.0")
+                    .replace(
+                        "@group(0)\n@binding(0)\nvar<uniform> ubo: UniformInput;",
+                        ""
+                    ),
+                false
+            )
+            .unwrap_err(),
+            ShaderError::LayoutMismatch(
+                "No uniform at @group(0) @binding(0) for a uniform type of 64 bytes".to_string()
+            )
+        );
+        assert_eq!(
+            try_build(&SHADER.replace("col: vec4", "col: vec3"), true).unwrap_err(),
+            ShaderError::LayoutMismatch(
+                "Vertex input `col` at @location(2) does not match the vertex attribute format Float32x4".to_string()
+            )
+        );
+        assert_eq!(
+            try_build(&SHADER.replace("location(2)", "location(3)"), true).unwrap_err(),
+            ShaderError::LayoutMismatch(
+                "Vertex input `col` at @location(3) has no vertex attribute".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn try_build_errors() {
+        let engine = Engine::new();
+        let target = pollster::block_on(engine.new_target_offscreen(Rect::new(4, 4)));
+        let module = ShaderModule::new_wgsl_source(&target, SHADER.into()).unwrap();
+        let builder = |vs_main| {
+            Shader::<DefaultVertex, u32>::builder()
+                .with_vertex(&module, vs_main)
+                .with_fragment(&module, "fs_main")
+                .with_format(target.get_format())
+        };
+
+        assert_eq!(
+            builder("vs_other").try_build(&target).unwrap_err(),
+            ShaderError::MissingEntryPoint {
+                stage: ShaderStages::VERTEX,
+                entry: "vs_other".to_string()
+            }
+        );
+        assert!(matches!(
+            builder("vs_main")
+                .with_format(TextureFormat::Depth32Float)
+                .try_build(&target),
+            Err(ShaderError::FormatMismatch(_))
+        ));
+        assert_eq!(
+            builder("vs_main")
+                .with_dynamic_offset(0, 1)
+                .try_build(&target)
+                .unwrap_err(),
+            ShaderError::LayoutMismatch(
+                "No buffer at @group(0) @binding(1) for a dynamic offset".to_string()
+            )
+        );
+        assert_eq!(
+            builder("vs_main")
+                .with_sampler_type(0, 0, SamplerBindingType::Filtering)
+                .try_build(&target)
+                .unwrap_err(),
+            ShaderError::LayoutMismatch(
+                "No sampler at @group(0) @binding(0) for a Filtering sampler".to_string()
+            )
+        );
+        assert_eq!(
+            builder("vs_main")
+                .with_format(TextureFormat::R32Float)
+                .try_build(&target)
+                .unwrap_err(),
+            ShaderError::FormatMismatch(
+                "Color target format R32Float is not blendable".to_string()
+            )
+        );
+        assert_eq!(
+            builder("vs_main")
+                .with_formats(&[target.get_format(); 9])
+                .try_build(&target)
+                .unwrap_err(),
+            ShaderError::Unsupported("Too many color targets, max is 8".to_string())
+        );
+        assert_eq!(
+            builder("vs_main")
+                .with_formats(&[])
+                .try_build(&target)
+                .unwrap_err(),
+            ShaderError::FormatMismatch("No color target formats".to_string())
+        );
+        assert_eq!(
+            builder("vs_main")
+                .with_target_blend(1, None)
+                .try_build(&target)
+                .unwrap_err(),
+            ShaderError::FormatMismatch("No color target at index 1, the shader has 1".to_string())
+        );
+        assert_eq!(
+            builder("vs_main")
+                .with_target_write_mask(2, ColorWrites::ALL)
+                .try_build(&target)
+                .unwrap_err(),
+            ShaderError::FormatMismatch("No color target at index 2, the shader has 1".to_string())
+        );
+        // overrides given before the color targets
+        assert!(Shader::<DefaultVertex, u32>::builder()
+            .with_target_blend(1, None)
+            .with_target_write_mask(2, ColorWrites::ALL)
+            .with_vertex(&module, "vs_main")
+            .with_fragment(&module, "fs_main")
+            .with_formats(&[target.get_format(); 3])
+            .try_build(&target)
+            .is_ok());
+        if !target
+            .device
+            .features()
+            .contains(Features::POLYGON_MODE_POINT)
+        {
+            assert!(matches!(
+                builder("vs_main")
+                    .with_polygon_mode(PolygonMode::Point)
+                    .try_build(&target),
+                Err(ShaderError::Unsupported(_))
+            ));
         }
+        // the baked layout is missing the uniform
+        assert!(matches!(
+            builder("vs_main")
+                .with_baked_layout(PipelineLayoutDescriptor::default())
+                .try_build(&target),
+            Err(ShaderError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn blend_and_cull() {
+        GoldenImage::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/golden/blend_and_cull.png"
+        ))
+        .check_blocking(|target, frame| {
+            let module = ShaderModule::new_wgsl_source(target, QUAD_SHADER.into()).unwrap();
+            let builder = || {
+                Shader::<DefaultVertex, u32>::builder()
+                    .with_vertex(&module, "vs_main")
+                    .with_fragment(&module, "fs_main")
+                    .with_topology(PrimitiveTopology::TriangleStrip)
+                    .with_format(target.get_format())
+                    .with_baked_layout(PipelineLayoutDescriptor::default())
+                    .with_cull_mode(Some(Face::Back))
+            };
+            // `quad` is wound clockwise
+            let additive = builder()
+                .with_blend(Some(BlendState {
+                    color: BlendComponent {
+                        src_factor: BlendFactor::One,
+                        dst_factor: BlendFactor::One,
+                        operation: BlendOperation::Add,
+                    },
+                    alpha: BlendComponent::OVER,
+                }))
+                .with_front_face(FrontFace::Cw)
+                .build(target);
+            let culled = builder().build(target);
+
+            let mut quads = quad(-0.25, 0.5, Color::RED).to_vec();
+            quads.extend(quad(0.25, 0.5, Color::BLUE));
+            quads.extend(quad(0.0, 0.5, Color::GREEN));
+            let vbo = VertexBuffer::new_with(target, &quads);
+            let ibo = IndexBuffer::new_with(target, &[0, 1, 2, 3]);
+
+            frame
+                .primary_render_pass()
+                .bind_vbo(&vbo)
+                .bind_ibo(&ibo)
+                .bind_shader(&additive)
+                .draw_indexed(0..4, 0, 0..1)
+                .draw_indexed(0..4, 4, 0..1)
+                .bind_shader(&culled)
+                .draw_indexed(0..4, 8, 0..1);
+        })
+        .unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "not blendable")]
+    fn blend_unblendable_format() {
+        let engine = Engine::new();
+        let target = pollster::block_on(engine.new_target_offscreen(Rect::new(4, 4)));
+        let module = ShaderModule::new_wgsl_source(&target, QUAD_SHADER.into()).unwrap();
+        Shader::<DefaultVertex, u32>::builder()
+            .with_vertex(&module, "vs_main")
+            .with_fragment(&module, "fs_main")
+            .with_format(TextureFormat::R32Float)
+            .with_baked_layout(PipelineLayoutDescriptor::default())
+            .build(&target);
     }
 }
@@ -0,0 +1,98 @@
+use super::{error::ShaderError, layout::AutoLayout, module::ShaderModule};
+use crate::{label, target::Target};
+use wgpu::{BindGroupLayout, ComputePipeline, ComputePipelineDescriptor, PipelineLayoutDescriptor};
+
+//
+
+/// Storage buffers need a target
+/// from [`crate::Engine::with_compute`]
+#[derive(Debug)]
+pub struct ComputeShader {
+    pub(crate) pipeline: ComputePipeline,
+}
+
+//
+
+impl ComputeShader {
+    /// Compute shader with an automatically
+    /// generated bind group layout
+    pub fn new(target: &Target, module: &ShaderModule, entry: &str) -> Self {
+        Self::new_with_filtering(target, module, entry, false)
+    }
+
+    /// Generated layout with filtering samplers,
+    /// see `AutoLayout::new_with_filtering`
+    pub fn new_with_filtering(
+        target: &Target,
+        module: &ShaderModule,
+        entry: &str,
+        filtering: bool,
+    ) -> Self {
+        Self::try_new_with_filtering(target, module, entry, filtering)
+            .unwrap_or_else(|err| panic!("Invalid compute shader: {err}"))
+    }
+
+    /// Errors with [`ShaderError::MissingEntryPoint`]
+    /// instead of panicking if `module` has no
+    /// compute entry point `entry`
+    pub fn try_new(
+        target: &Target,
+        module: &ShaderModule,
+        entry: &str,
+    ) -> Result<Self, ShaderError> {
+        Self::try_new_with_filtering(target, module, entry, false)
+    }
+
+    /// See [`Self::try_new`] and [`Self::new_with_filtering`]
+    pub fn try_new_with_filtering(
+        target: &Target,
+        module: &ShaderModule,
+        entry: &str,
+        filtering: bool,
+    ) -> Result<Self, ShaderError> {
+        let layout =
+            AutoLayout::try_new_compute_with_filtering(target, (module, entry), filtering)?;
+        let layout = layout.get();
+        Self::try_new_with_layout(target, module, entry, layout.get())
+    }
+
+    pub fn new_with_layout(
+        target: &Target,
+        module: &ShaderModule,
+        entry: &str,
+        layout: PipelineLayoutDescriptor,
+    ) -> Self {
+        Self::try_new_with_layout(target, module, entry, layout)
+            .unwrap_or_else(|err| panic!("Invalid compute shader: {err}"))
+    }
+
+    /// wgpu validation errors are caught and
+    /// returned as [`ShaderError::Validation`]
+    pub fn try_new_with_layout(
+        target: &Target,
+        module: &ShaderModule,
+        entry: &str,
+        layout: PipelineLayoutDescriptor,
+    ) -> Result<Self, ShaderError> {
+        let pipeline = target.catch_error(|target| {
+            let layout = target.device.create_pipeline_layout(&layout);
+            target
+                .device
+                .create_compute_pipeline(&ComputePipelineDescriptor {
+                    label: label!(),
+                    layout: Some(&layout),
+                    module: &module.inner,
+                    entry_point: entry,
+                })
+        });
+        let pipeline = pipeline.map_err(ShaderError::Validation)?;
+
+        Ok(Self { pipeline })
+    }
+
+    /// Layout for creating bind groups
+    /// compatible with this shader
+    pub fn bind_group_layout(&self, index: u32) -> BindGroupLayout {
+        self.pipeline.get_bind_group_layout(index)
+    }
+}
@@ -0,0 +1,43 @@
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+use wgpu::ShaderStages;
+
+//
+
+/// Why a shader could not be built or used
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShaderError {
+    /// The module has no entry point with this name
+    MissingEntryPoint { stage: ShaderStages, entry: String },
+    /// naga or wgpu rejected the module or the pipeline
+    Validation(String),
+    /// Vertex layouts or uniform types don't
+    /// match the shader's inputs
+    LayoutMismatch(String),
+    /// Color, depth/stencil or sample count
+    /// don't work with the target or render pass
+    FormatMismatch(String),
+    /// Needs a device feature that isn't enabled
+    /// or goes over a device or wgpu limit
+    Unsupported(String),
+}
+
+//
+
+impl Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShaderError::MissingEntryPoint { stage, entry } => {
+                write!(f, "Missing {stage:?} entry point `{entry}`")
+            }
+            ShaderError::Validation(err) => write!(f, "Validation error: {err}"),
+            ShaderError::LayoutMismatch(err) => write!(f, "Layout mismatch: {err}"),
+            ShaderError::FormatMismatch(err) => write!(f, "Format mismatch: {err}"),
+            ShaderError::Unsupported(err) => write!(f, "Unsupported: {err}"),
+        }
+    }
+}
+
+impl Error for ShaderError {}
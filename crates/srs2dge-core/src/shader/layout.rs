@@ -1,23 +1,26 @@
-use crate::{label, prelude::ShaderModule, target::Target};
+use super::error::ShaderError;
+use crate::{buffer::vertex::vertex_format_matches, label, prelude::ShaderModule, target::Target};
 use naga::{
     valid::{Capabilities, ValidationFlags, Validator},
-    AddressSpace, ImageClass, ImageDimension, Module, ScalarKind, TypeInner,
+    AddressSpace, Binding, GlobalVariable, Handle, ImageClass, ImageDimension, Module, ScalarKind,
+    StorageAccess, StorageFormat, Type, TypeInner,
 };
 use std::{collections::BTreeMap, num::NonZeroU64};
 use wgpu::{
     BindGroupLayout, BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingType,
     BufferBindingType, PipelineLayoutDescriptor, SamplerBindingType, ShaderSource, ShaderStages,
-    TextureSampleType, TextureViewDimension,
+    StorageTextureAccess, TextureFormat, TextureSampleType, TextureViewDimension,
+    VertexBufferLayout,
 };
 
 //
 
 pub struct AutoLayout {
-    group: BindGroupLayout,
+    groups: Vec<BindGroupLayout>,
 }
 
 pub struct AutoLayoutGetter<'a> {
-    groups: [&'a BindGroupLayout; 1],
+    groups: Vec<&'a BindGroupLayout>,
 }
 
 //
@@ -33,73 +36,360 @@ impl<'a> AutoLayoutGetter<'a> {
 }
 
 impl AutoLayout {
-    pub fn new(
+    /// Layout with `NonFiltering` samplers
+    /// and non filterable float textures
+    pub fn new(target: &Target, vs: (&ShaderModule, &str), fs: (&ShaderModule, &str)) -> Self {
+        Self::new_with_filtering(target, vs, fs, false)
+    }
+
+    /// With `filtering` on, samplers are `Filtering` and the
+    /// float textures sampled with them are filterable
+    ///
+    /// Textures that are only loaded with `textureLoad`
+    /// stay non filterable, so formats like `R32Float`
+    /// still work.
+    pub fn new_with_filtering(
+        target: &Target,
+        vs: (&ShaderModule, &str),
+        fs: (&ShaderModule, &str),
+        filtering: bool,
+    ) -> Self {
+        Self::try_new_with(target, vs, fs, filtering, &[], &[])
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Errors instead of panicking on missing
+    /// entry points and invalid modules
+    ///
+    /// The buffers at the `(group, binding)` pairs in
+    /// `dynamic_offsets` are bound with dynamic offsets.
+    ///
+    /// The samplers in `samplers` use the given binding
+    /// type instead of the one picked by `filtering`.
+    pub fn try_new_with(
         target: &Target,
         (vs, vs_main): (&ShaderModule, &str),
         (fs, fs_main): (&ShaderModule, &str),
+        filtering: bool,
+        dynamic_offsets: &[(u32, u32)],
+        samplers: &[((u32, u32), SamplerBindingType)],
+    ) -> Result<Self, ShaderError> {
+        let mut validator = Validator::new(ValidationFlags::all(), Capabilities::all());
+        let vs = Self::module(
+            vs,
+            vs_main,
+            ShaderStages::VERTEX,
+            filtering,
+            samplers,
+            &mut validator,
+        )?;
+        let fs = Self::module(
+            fs,
+            fs_main,
+            ShaderStages::FRAGMENT,
+            filtering,
+            samplers,
+            &mut validator,
+        )?;
+
+        let mut entries = Self::merge(vs, fs);
+        for &((group, binding), ty) in samplers {
+            match entries.get(&(group, binding)).map(|entry| entry.ty) {
+                Some(BindingType::Sampler(found)) if found == ty => {}
+                Some(BindingType::Sampler(found)) => {
+                    return Err(ShaderError::LayoutMismatch(format!(
+                        "Sampler at @group({group}) @binding({binding}) is {found:?}, not {ty:?}"
+                    )))
+                }
+                _ => {
+                    return Err(ShaderError::LayoutMismatch(format!(
+                        "No sampler at @group({group}) @binding({binding}) for a {ty:?} sampler"
+                    )))
+                }
+            }
+        }
+        for &(group, binding) in dynamic_offsets {
+            match entries
+                .get_mut(&(group, binding))
+                .map(|entry| &mut entry.ty)
+            {
+                Some(BindingType::Buffer {
+                    has_dynamic_offset, ..
+                }) => *has_dynamic_offset = true,
+                _ => {
+                    return Err(ShaderError::LayoutMismatch(format!(
+                        "No buffer at @group({group}) @binding({binding}) for a dynamic offset"
+                    )))
+                }
+            }
+        }
+
+        Ok(Self::from_entries(target, entries))
+    }
+
+    pub fn new_compute(target: &Target, cs: (&ShaderModule, &str)) -> Self {
+        Self::new_compute_with_filtering(target, cs, false)
+    }
+
+    /// See [`Self::new_with_filtering`]
+    pub fn new_compute_with_filtering(
+        target: &Target,
+        cs: (&ShaderModule, &str),
+        filtering: bool,
     ) -> Self {
+        Self::try_new_compute_with_filtering(target, cs, filtering)
+            .unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Errors instead of panicking on a missing
+    /// entry point or an invalid module
+    pub fn try_new_compute_with_filtering(
+        target: &Target,
+        (cs, cs_main): (&ShaderModule, &str),
+        filtering: bool,
+    ) -> Result<Self, ShaderError> {
         let mut validator = Validator::new(ValidationFlags::all(), Capabilities::all());
-        let vs = Self::module(vs, vs_main, ShaderStages::VERTEX, &mut validator);
-        let fs = Self::module(fs, fs_main, ShaderStages::FRAGMENT, &mut validator);
+        let cs = Self::module(
+            cs,
+            cs_main,
+            ShaderStages::COMPUTE,
+            filtering,
+            &[],
+            &mut validator,
+        )?;
 
-        let entries: Vec<BindGroupLayoutEntry> = Self::merge(vs, fs)
-            .into_iter()
-            .map(|(_, entry)| entry)
-            .collect();
+        Ok(Self::from_entries(target, Self::merge(cs, vec![])))
+    }
 
-        let group = target
-            .device
-            .create_bind_group_layout(&BindGroupLayoutDescriptor {
-                label: label!(),
-                entries: &entries,
-            });
+    /// Whether `module` has the entry point `entry`
+    pub fn check_entry_point(
+        (module, entry): (&ShaderModule, &str),
+        stage: ShaderStages,
+    ) -> Result<(), ShaderError> {
+        entry_point(&parse(&module.source)?, entry, stage).map(|_| ())
+    }
 
-        Self { group }
+    /// Layout of `@group(index)`
+    pub fn group(&self, index: u32) -> Option<&BindGroupLayout> {
+        self.groups.get(index as usize)
     }
 
     pub fn get(&self) -> AutoLayoutGetter {
         AutoLayoutGetter {
-            groups: [&self.group],
+            groups: self.groups.iter().collect(),
+        }
+    }
+
+    /// Compare vertex buffer layouts to the
+    /// `@location` inputs of the vertex entry point
+    ///
+    /// Every input needs an attribute with the same
+    /// scalar kind and component count. Attributes
+    /// that the shader doesn't read are allowed.
+    pub fn check_vertex_inputs(
+        (vs, vs_main): (&ShaderModule, &str),
+        layouts: &[VertexBufferLayout],
+    ) -> Result<(), ShaderError> {
+        let module = parse(&vs.source)?;
+        let entry = &module.entry_points[entry_point(&module, vs_main, ShaderStages::VERTEX)?];
+
+        // arguments and members of struct arguments
+        let mut inputs = vec![];
+        for arg in entry.function.arguments.iter() {
+            match (&arg.binding, &module.types[arg.ty].inner) {
+                (Some(binding), _) => inputs.push((binding, arg.name.as_deref(), arg.ty)),
+                (None, TypeInner::Struct { members, .. }) => inputs.extend(
+                    members
+                        .iter()
+                        .filter_map(|m| Some((m.binding.as_ref()?, m.name.as_deref(), m.ty))),
+                ),
+                (None, _) => {}
+            }
+        }
+
+        for (binding, name, ty) in inputs {
+            let location = match binding {
+                Binding::Location { location, .. } => *location,
+                Binding::BuiltIn(_) => continue,
+            };
+            let name = name.unwrap_or("?");
+            let (kind, components) = numeric(&module, ty);
+
+            let attribute = layouts
+                .iter()
+                .flat_map(|layout| layout.attributes.iter())
+                .find(|attribute| attribute.shader_location == location)
+                .ok_or_else(|| {
+                    ShaderError::LayoutMismatch(format!(
+                        "Vertex input `{name}` at @location({location}) has no vertex attribute"
+                    ))
+                })?;
+            let kind = match kind {
+                Some(ScalarKind::Float) => 'f',
+                Some(ScalarKind::Sint) => 'i',
+                Some(ScalarKind::Uint) => 'u',
+                _ => ' ',
+            };
+            if !vertex_format_matches(attribute.format, kind, components) {
+                return Err(ShaderError::LayoutMismatch(format!(
+                    "Vertex input `{name}` at @location({location}) does not match the vertex attribute format {:?}",
+                    attribute.format
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Compare the size of a Rust uniform type to
+    /// the uniform at `@group(group) @binding(binding)`
+    ///
+    /// `size` is checked against the first module that
+    /// declares the binding, a missing binding is a
+    /// [`ShaderError::LayoutMismatch`].
+    pub fn check_uniform_size(
+        modules: &[&ShaderModule],
+        (group, binding): (u32, u32),
+        size: u64,
+    ) -> Result<(), ShaderError> {
+        for module in modules {
+            let module = parse(&module.source)?;
+            let layouter = layouter(&module)?;
+
+            let found = module.global_variables.iter().find(|(_, var)| {
+                var.space == AddressSpace::Uniform
+                    && matches!(&var.binding, Some(b) if b.group == group && b.binding == binding)
+            });
+            if let Some((_, var)) = found {
+                let expected = layouter[var.ty].size as u64;
+                if expected != size {
+                    return Err(ShaderError::LayoutMismatch(format!(
+                        "Uniform `{}` at @group({group}) @binding({binding}) is {expected} bytes but the uniform type is {size} bytes",
+                        var.name.as_deref().unwrap_or("?")
+                    )));
+                }
+                return Ok(());
+            }
         }
+
+        Err(ShaderError::LayoutMismatch(format!(
+            "No uniform at @group({group}) @binding({binding}) for a uniform type of {size} bytes"
+        )))
+    }
+
+    fn from_entries(target: &Target, entries: BTreeMap<(u32, u32), BindGroupLayoutEntry>) -> Self {
+        // groups in between the used ones are left empty
+        let count = entries
+            .keys()
+            .last()
+            .map(|(group, _)| group + 1)
+            .unwrap_or(1);
+        let groups = (0..count)
+            .map(|group| {
+                let entries: Vec<BindGroupLayoutEntry> = entries
+                    .range((group, 0)..=(group, u32::MAX))
+                    .map(|(_, entry)| *entry)
+                    .collect();
+
+                target
+                    .device
+                    .create_bind_group_layout(&BindGroupLayoutDescriptor {
+                        label: label!(),
+                        entries: &entries,
+                    })
+            })
+            .collect();
+
+        Self { groups }
     }
 
     fn module(
         module: &ShaderModule,
         entry: &str,
         visibility: ShaderStages,
+        filtering: bool,
+        samplers: &[((u32, u32), SamplerBindingType)],
         validator: &mut Validator,
-    ) -> Vec<BindGroupLayoutEntry> {
-        let module = parse(&module.source);
+    ) -> Result<Vec<(u32, BindGroupLayoutEntry)>, ShaderError> {
+        let module = parse(&module.source)?;
 
-        let i = module
-            .entry_points
-            .iter()
-            .enumerate()
-            .find(|(_, ep)| ep.name == entry)
-            .unwrap()
-            .0;
+        let i = entry_point(&module, entry, visibility)?;
 
-        let module_info = validator.validate(&module).unwrap();
+        let module_info = validator
+            .validate(&module)
+            .map_err(|err| ShaderError::Validation(err.to_string()))?;
 
         let entry_function = module_info.get_entry_point(i);
 
-        let mut layouter = naga::proc::Layouter::default();
-        layouter.update(&module.types, &module.constants).unwrap();
+        // the given type if it can replace the default,
+        // try_new_with reports the ones that can't
+        let sampler_type = |var: &GlobalVariable, comparison: bool| {
+            let default = match (comparison, filtering) {
+                (true, _) => SamplerBindingType::Comparison,
+                (false, true) => SamplerBindingType::Filtering,
+                (false, false) => SamplerBindingType::NonFiltering,
+            };
+            var.binding
+                .as_ref()
+                .and_then(|bind| {
+                    samplers
+                        .iter()
+                        .find(|(at, _)| *at == (bind.group, bind.binding))
+                })
+                .map(|&(_, ty)| ty)
+                .filter(|ty| (*ty == SamplerBindingType::Comparison) == comparison)
+                .unwrap_or(default)
+        };
+
+        // textures that need a filterable sample type
+        let filtered: Vec<Handle<GlobalVariable>> = entry_function
+            .sampling_set
+            .iter()
+            .filter(|key| {
+                let var = &module.global_variables[key.sampler];
+                let comparison = matches!(
+                    module.types[var.ty].inner,
+                    TypeInner::Sampler { comparison: true }
+                );
+                sampler_type(var, comparison) == SamplerBindingType::Filtering
+            })
+            .map(|key| key.image)
+            .collect();
+
+        let layouter = layouter(&module)?;
 
         module
             .global_variables
             .iter()
             .filter(|(handle, _)| !entry_function[*handle].is_empty())
-            .filter_map(|(_, var)| Some((var.space, var.binding.clone()?, var.ty)))
-            .filter_map(|(space, bind, ty)| {
+            .filter_map(|(handle, var)| Some((handle, var, var.binding.clone()?, var.ty)))
+            .map(|(handle, var, bind, ty)| {
+                let space = var.space;
                 let size = layouter[ty];
                 let ty = module.types.get_handle(ty).unwrap();
 
-                match (&ty.inner, space) {
-                    (TypeInner::Sampler { .. }, _) => Some(BindGroupLayoutEntry {
+                let entry = match (&ty.inner, space) {
+                    (TypeInner::Sampler { comparison }, _) => Some(BindGroupLayoutEntry {
+                        binding: bind.binding,
+                        visibility,
+                        ty: BindingType::Sampler(sampler_type(var, *comparison)),
+                        count: None,
+                    }),
+                    (
+                        TypeInner::Image {
+                            dim,
+                            arrayed,
+                            class: ImageClass::Storage { format, access },
+                        },
+                        _,
+                    ) => Some(BindGroupLayoutEntry {
                         binding: bind.binding,
                         visibility,
-                        ty: BindingType::Sampler(SamplerBindingType::NonFiltering),
+                        ty: BindingType::StorageTexture {
+                            access: storage_texture_access(*access),
+                            format: storage_format(*format),
+                            view_dimension: view_dimension(*dim, *arrayed)?,
+                        },
                         count: None,
                     }),
                     (
@@ -114,10 +404,13 @@ impl AutoLayout {
                         visibility,
                         ty: BindingType::Texture {
                             sample_type: match class {
+                                // multisampled textures cannot be filtered
                                 ImageClass::Sampled {
                                     kind: ScalarKind::Float,
-                                    ..
-                                } => TextureSampleType::Float { filterable: false },
+                                    multi,
+                                } => TextureSampleType::Float {
+                                    filterable: !multi && filtered.contains(&handle),
+                                },
                                 ImageClass::Sampled {
                                     kind: ScalarKind::Sint,
                                     ..
@@ -127,19 +420,18 @@ impl AutoLayout {
                                     ..
                                 } => TextureSampleType::Uint,
                                 ImageClass::Depth { .. } => TextureSampleType::Depth,
-                                ImageClass::Storage { .. } => todo!(),
-                                _ => todo!(),
+                                other => {
+                                    return Err(ShaderError::Unsupported(format!(
+                                        "Texture class {other:?}"
+                                    )))
+                                }
                             },
-                            view_dimension: match (dim, arrayed) {
-                                (ImageDimension::D1, false) => TextureViewDimension::D1,
-                                (ImageDimension::D2, false) => TextureViewDimension::D2,
-                                (ImageDimension::D2, true) => TextureViewDimension::D2Array,
-                                (ImageDimension::D3, false) => TextureViewDimension::D3,
-                                (ImageDimension::Cube, false) => TextureViewDimension::Cube,
-                                (ImageDimension::Cube, true) => TextureViewDimension::CubeArray,
-                                _ => unimplemented!(),
-                            },
-                            multisampled: false,
+                            view_dimension: view_dimension(*dim, *arrayed)?,
+                            multisampled: matches!(
+                                class,
+                                ImageClass::Sampled { multi: true, .. }
+                                    | ImageClass::Depth { multi: true }
+                            ),
                         },
                         count: None,
                     }),
@@ -153,25 +445,60 @@ impl AutoLayout {
                         },
                         count: None,
                     }),
-                    other => unimplemented!("Unimplemented: {other:?}"),
-                }
+                    (_, AddressSpace::Storage { access }) => Some(BindGroupLayoutEntry {
+                        binding: bind.binding,
+                        visibility,
+                        ty: BindingType::Buffer {
+                            ty: BufferBindingType::Storage {
+                                read_only: !access.contains(StorageAccess::STORE),
+                            },
+                            has_dynamic_offset: false,
+                            // runtime sized arrays need at least one element
+                            min_binding_size: NonZeroU64::new(size.size as _),
+                        },
+                        count: None,
+                    }),
+                    (other, space) => {
+                        return Err(ShaderError::Unsupported(format!(
+                            "Binding of {other:?} in {space:?}"
+                        )))
+                    }
+                };
+                Ok(entry.map(|entry| (bind.group, entry)))
             })
+            .filter_map(Result::transpose)
             .collect()
     }
 
     fn merge(
-        vs: Vec<BindGroupLayoutEntry>,
-        fs: Vec<BindGroupLayoutEntry>,
-    ) -> BTreeMap<u32, BindGroupLayoutEntry> {
-        let mut first: BTreeMap<u32, BindGroupLayoutEntry> =
-            vs.into_iter().map(|entry| (entry.binding, entry)).collect();
-
-        for mut entry in fs.into_iter() {
-            if let Some(existing_entry) = first.get(&entry.binding) {
+        vs: Vec<(u32, BindGroupLayoutEntry)>,
+        fs: Vec<(u32, BindGroupLayoutEntry)>,
+    ) -> BTreeMap<(u32, u32), BindGroupLayoutEntry> {
+        let mut first: BTreeMap<(u32, u32), BindGroupLayoutEntry> = vs
+            .into_iter()
+            .map(|(group, entry)| ((group, entry.binding), entry))
+            .collect();
+
+        for (group, mut entry) in fs.into_iter() {
+            if let Some(existing_entry) = first.get(&(group, entry.binding)) {
                 entry.visibility |= existing_entry.visibility;
-                first.insert(entry.binding, entry);
+                // filterable if either stage samples it with filtering
+                if let (
+                    BindingType::Texture {
+                        sample_type: TextureSampleType::Float { filterable },
+                        ..
+                    },
+                    BindingType::Texture {
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        ..
+                    },
+                ) = (&mut entry.ty, existing_entry.ty)
+                {
+                    *filterable = true;
+                }
+                first.insert((group, entry.binding), entry);
             } else {
-                first.insert(entry.binding, entry);
+                first.insert((group, entry.binding), entry);
             }
         }
 
@@ -181,8 +508,100 @@ impl AutoLayout {
 
 //
 
-fn parse(source: &ShaderSource) -> Module {
-    match source {
+fn view_dimension(dim: ImageDimension, arrayed: bool) -> Result<TextureViewDimension, ShaderError> {
+    Ok(match (dim, arrayed) {
+        (ImageDimension::D1, false) => TextureViewDimension::D1,
+        (ImageDimension::D2, false) => TextureViewDimension::D2,
+        (ImageDimension::D2, true) => TextureViewDimension::D2Array,
+        (ImageDimension::D3, false) => TextureViewDimension::D3,
+        (ImageDimension::Cube, false) => TextureViewDimension::Cube,
+        (ImageDimension::Cube, true) => TextureViewDimension::CubeArray,
+        (dim, true) => {
+            return Err(ShaderError::Unsupported(format!(
+                "Arrayed {dim:?} textures"
+            )))
+        }
+    })
+}
+
+fn storage_texture_access(access: StorageAccess) -> StorageTextureAccess {
+    match (
+        access.contains(StorageAccess::LOAD),
+        access.contains(StorageAccess::STORE),
+    ) {
+        (true, true) => StorageTextureAccess::ReadWrite,
+        (true, false) => StorageTextureAccess::ReadOnly,
+        _ => StorageTextureAccess::WriteOnly,
+    }
+}
+
+fn storage_format(format: StorageFormat) -> TextureFormat {
+    match format {
+        StorageFormat::R8Unorm => TextureFormat::R8Unorm,
+        StorageFormat::R8Snorm => TextureFormat::R8Snorm,
+        StorageFormat::R8Uint => TextureFormat::R8Uint,
+        StorageFormat::R8Sint => TextureFormat::R8Sint,
+        StorageFormat::R16Uint => TextureFormat::R16Uint,
+        StorageFormat::R16Sint => TextureFormat::R16Sint,
+        StorageFormat::R16Float => TextureFormat::R16Float,
+        StorageFormat::Rg8Unorm => TextureFormat::Rg8Unorm,
+        StorageFormat::Rg8Snorm => TextureFormat::Rg8Snorm,
+        StorageFormat::Rg8Uint => TextureFormat::Rg8Uint,
+        StorageFormat::Rg8Sint => TextureFormat::Rg8Sint,
+        StorageFormat::R32Uint => TextureFormat::R32Uint,
+        StorageFormat::R32Sint => TextureFormat::R32Sint,
+        StorageFormat::R32Float => TextureFormat::R32Float,
+        StorageFormat::Rg16Uint => TextureFormat::Rg16Uint,
+        StorageFormat::Rg16Sint => TextureFormat::Rg16Sint,
+        StorageFormat::Rg16Float => TextureFormat::Rg16Float,
+        StorageFormat::Rgba8Unorm => TextureFormat::Rgba8Unorm,
+        StorageFormat::Rgba8Snorm => TextureFormat::Rgba8Snorm,
+        StorageFormat::Rgba8Uint => TextureFormat::Rgba8Uint,
+        StorageFormat::Rgba8Sint => TextureFormat::Rgba8Sint,
+        StorageFormat::Rgb10a2Unorm => TextureFormat::Rgb10a2Unorm,
+        StorageFormat::Rg11b10Float => TextureFormat::Rg11b10Float,
+        StorageFormat::Rg32Uint => TextureFormat::Rg32Uint,
+        StorageFormat::Rg32Sint => TextureFormat::Rg32Sint,
+        StorageFormat::Rg32Float => TextureFormat::Rg32Float,
+        StorageFormat::Rgba16Uint => TextureFormat::Rgba16Uint,
+        StorageFormat::Rgba16Sint => TextureFormat::Rgba16Sint,
+        StorageFormat::Rgba16Float => TextureFormat::Rgba16Float,
+        StorageFormat::Rgba32Uint => TextureFormat::Rgba32Uint,
+        StorageFormat::Rgba32Sint => TextureFormat::Rgba32Sint,
+        StorageFormat::Rgba32Float => TextureFormat::Rgba32Float,
+    }
+}
+
+/// Scalar kind and component count of a vertex input
+fn numeric(module: &Module, ty: Handle<Type>) -> (Option<ScalarKind>, u32) {
+    match module.types[ty].inner {
+        TypeInner::Scalar { kind, .. } => (Some(kind), 1),
+        TypeInner::Vector { size, kind, .. } => (Some(kind), size as u32),
+        _ => (None, 0),
+    }
+}
+
+fn entry_point(module: &Module, entry: &str, stage: ShaderStages) -> Result<usize, ShaderError> {
+    module
+        .entry_points
+        .iter()
+        .position(|ep| ep.name == entry)
+        .ok_or_else(|| ShaderError::MissingEntryPoint {
+            stage,
+            entry: entry.to_string(),
+        })
+}
+
+fn layouter(module: &Module) -> Result<naga::proc::Layouter, ShaderError> {
+    let mut layouter = naga::proc::Layouter::default();
+    layouter
+        .update(&module.types, &module.constants)
+        .map_err(|err| ShaderError::Validation(err.to_string()))?;
+    Ok(layouter)
+}
+
+fn parse(source: &ShaderSource) -> Result<Module, ShaderError> {
+    let module = match source {
         #[cfg(feature = "spirv")]
         ShaderSource::SpirV(spv) => {
             // source from wgpu repo to keep it somewhat similar:
@@ -193,7 +612,7 @@ fn parse(source: &ShaderSource) -> Module {
                 block_ctx_dump_prefix: None,
             };
             let parser = naga::front::spv::Parser::new(spv.iter().cloned(), &options);
-            parser.parse().unwrap()
+            parser.parse().map_err(|err| format!("{err:?}"))
         }
 
         #[cfg(feature = "glsl")]
@@ -209,11 +628,158 @@ fn parse(source: &ShaderSource) -> Module {
                 defines: defines.clone(),
             };
             let mut parser = naga::front::glsl::Parser::default();
-            parser.parse(&options, shader).unwrap()
+            parser
+                .parse(&options, shader)
+                .map_err(|err| format!("{err:?}"))
+        }
+
+        ShaderSource::Wgsl(source) => {
+            naga::front::wgsl::parse_str(source).map_err(|err| err.message().to_string())
+        }
+
+        // SPIR-V or GLSL without their features and naga modules
+        _ => {
+            return Err(ShaderError::Unsupported(
+                "Shader source, only WGSL is enabled by default".to_string(),
+            ))
         }
+    };
+    module.map_err(ShaderError::Validation)
+}
 
-        ShaderSource::Wgsl(source) => naga::front::wgsl::parse_str(source).unwrap(),
+//
+
+#[cfg(all(test, feature = "testing"))]
+mod test {
+    use super::AutoLayout;
+    use crate::{
+        packer::rect::Rect,
+        shader::{error::ShaderError, module::ShaderModule},
+        texture::sampler::Sampler,
+        Engine,
+    };
+    use naga::valid::{Capabilities, ValidationFlags, Validator};
+    use wgpu::{BindingType, SamplerBindingType, ShaderStages, TextureSampleType};
+
+    const SHADER: &str = r#"
+@group(0)
+@binding(0)
+var t_sampled: texture_2d<f32>;
+
+@group(0)
+@binding(1)
+var t_loaded: texture_2d<f32>;
+
+@group(0)
+@binding(2)
+var s_sampler: sampler;
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+	return textureSample(t_sampled, s_sampler, vec2<f32>(0.5)) + textureLoad(t_loaded, vec2<i32>(0), 0);
+}
+"#;
+
+    #[test]
+    fn filterable_per_binding() {
+        let engine = Engine::new();
+        let target = pollster::block_on(engine.new_target_offscreen(Rect::new(4, 4)));
+        let module = ShaderModule::new_wgsl_source(&target, SHADER.into()).unwrap();
+        let entries = |filtering, samplers: &[((u32, u32), SamplerBindingType)]| {
+            let mut validator = Validator::new(ValidationFlags::all(), Capabilities::all());
+            let mut entries = AutoLayout::module(
+                &module,
+                "fs_main",
+                ShaderStages::FRAGMENT,
+                filtering,
+                samplers,
+                &mut validator,
+            )
+            .unwrap();
+            entries.sort_by_key(|(_, entry)| entry.binding);
+            entries
+                .into_iter()
+                .map(|(_, entry)| entry.ty)
+                .collect::<Vec<_>>()
+        };
+        let sample_type = |ty: &BindingType| match ty {
+            BindingType::Texture { sample_type, .. } => *sample_type,
+            other => panic!("not a texture: {other:?}"),
+        };
 
-        _ => todo!(),
+        let on = entries(true, &[]);
+        assert_eq!(
+            sample_type(&on[0]),
+            TextureSampleType::Float { filterable: true }
+        );
+        assert_eq!(
+            sample_type(&on[1]),
+            TextureSampleType::Float { filterable: false }
+        );
+        assert_eq!(on[2], BindingType::Sampler(SamplerBindingType::Filtering));
+
+        let off = entries(false, &[]);
+        assert_eq!(
+            sample_type(&off[0]),
+            TextureSampleType::Float { filterable: false }
+        );
+        assert_eq!(
+            sample_type(&off[1]),
+            TextureSampleType::Float { filterable: false }
+        );
+        assert_eq!(
+            off[2],
+            BindingType::Sampler(SamplerBindingType::NonFiltering)
+        );
+
+        // a linear sampler at binding 2 without global filtering
+        let linear = Sampler::linear(&target);
+        let per_binding = entries(false, &[((0, 2), linear.binding_type())]);
+        assert_eq!(
+            sample_type(&per_binding[0]),
+            TextureSampleType::Float { filterable: true }
+        );
+        assert_eq!(
+            sample_type(&per_binding[1]),
+            TextureSampleType::Float { filterable: false }
+        );
+        assert_eq!(
+            per_binding[2],
+            BindingType::Sampler(SamplerBindingType::Filtering)
+        );
+    }
+
+    #[test]
+    fn unsupported_binding() {
+        let engine = Engine::new();
+        let target = pollster::block_on(engine.new_target_offscreen(Rect::new(4, 4)));
+        let module = ShaderModule::new_wgsl_source(
+            &target,
+            r#"
+@group(0)
+@binding(0)
+var t_array: binding_array<texture_2d<f32>, 2>;
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+	return textureLoad(t_array[0], vec2<i32>(0), 0);
+}
+"#
+            .into(),
+        )
+        .unwrap();
+
+        let mut validator = Validator::new(ValidationFlags::all(), Capabilities::all());
+        assert!(matches!(
+            AutoLayout::module(
+                &module,
+                "fs_main",
+                ShaderStages::FRAGMENT,
+                false,
+                &[],
+                &mut validator,
+            ),
+            Err(ShaderError::Unsupported(_))
+        ));
     }
 }
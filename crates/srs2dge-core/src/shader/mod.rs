@@ -1,25 +1,31 @@
 use self::builder::ShaderBuilder;
-use crate::buffer::{index::Index, vertex::Vertex};
+use crate::buffer::{index::Index, vertex::VertexSlots};
 use std::marker::PhantomData;
 use wgpu::{BindGroup, BindGroupLayout, Device, RenderPipeline, TextureFormat};
 
 //
 
 pub mod builder;
+pub mod compute;
+pub mod error;
 pub mod layout;
 pub mod module;
 pub mod prelude;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod watcher;
 
 //
 
 #[derive(Debug)]
 pub struct Shader<V, I>
 where
-    V: Vertex,
+    V: VertexSlots,
     I: Index,
 {
     pub(crate) pipeline: RenderPipeline,
-    pub(crate) format: TextureFormat,
+    pub(crate) formats: Vec<TextureFormat>,
+    pub(crate) depth_format: Option<TextureFormat>,
+    pub(crate) sample_count: u32,
 
     _p: PhantomData<(V, I)>,
 }
@@ -33,14 +39,47 @@ pub trait Layout<'a> {
     fn bind_group(&self, bindings: Self::Bindings) -> BindGroup;
 }
 
+/// [`Layout`] of `@group(GROUP)` for shaders
+/// that split their bindings into multiple groups
+///
+/// Bind the result with `RenderPass::bind_group_at(GROUP, ..)`.
+pub trait GroupLayout<'a, const GROUP: u32> {
+    type Bindings;
+
+    fn group_layout(device: &Device) -> BindGroupLayout;
+    fn group(&self, bindings: Self::Bindings) -> BindGroup;
+}
+
 //
 
+/// Single group layouts are `@group(0)`
+impl<'a, L> GroupLayout<'a, 0> for L
+where
+    L: Layout<'a>,
+{
+    type Bindings = L::Bindings;
+
+    fn group_layout(device: &Device) -> BindGroupLayout {
+        L::bind_group_layout(device)
+    }
+
+    fn group(&self, bindings: Self::Bindings) -> BindGroup {
+        self.bind_group(bindings)
+    }
+}
+
 impl<V, I> Shader<V, I>
 where
-    V: Vertex,
+    V: VertexSlots,
     I: Index,
 {
     pub fn builder<'s>() -> ShaderBuilder<'s, V, I> {
         ShaderBuilder::<'s, V, I>::new()
     }
+
+    /// Layout of `@group(index)` for creating
+    /// bind groups compatible with this shader
+    pub fn bind_group_layout(&self, index: u32) -> BindGroupLayout {
+        self.pipeline.get_bind_group_layout(index)
+    }
 }
@@ -5,6 +5,7 @@ use wgpu::ShaderModuleDescriptor;
 //
 
 pub use naga::{FastHashMap, ShaderStage};
+pub use srs2dge_preprocess::{Preprocessed, Preprocessor};
 pub use wgpu::ShaderSource;
 
 //
@@ -21,6 +22,20 @@ impl<'a> ShaderModule<'a> {
         Self::new(target, ShaderSource::Wgsl(source))
     }
 
+    /// Run `source` through `preprocessor` first
+    ///
+    /// Errors point at the original file and line.
+    pub fn new_wgsl_preprocessed(
+        target: &Target,
+        preprocessor: &Preprocessor,
+        name: &str,
+        source: &str,
+    ) -> Result<Self, String> {
+        let source = preprocessor.process(name, source)?;
+        source.check()?;
+        Self::new_wgsl_source(target, Cow::Owned(source.into_source()))
+    }
+
     #[cfg(feature = "glsl")]
     pub fn new_glsl_source(
         target: &Target,
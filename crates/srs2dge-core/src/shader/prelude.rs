@@ -1 +1,3 @@
-pub use super::{builder::*, layout::*, module::*, *};
+#[cfg(not(target_arch = "wasm32"))]
+pub use super::watcher::*;
+pub use super::{builder::*, compute::*, error::*, layout::*, module::*, *};
@@ -0,0 +1,350 @@
+use super::module::{Preprocessed, Preprocessor, ShaderModule};
+use crate::target::Target;
+use std::{
+    borrow::Cow,
+    fmt::{self, Debug, Display, Formatter},
+    fs,
+    ops::Deref,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+//
+
+type Build<T> = Box<dyn FnMut(&Target, &ShaderModule) -> Result<T, String>>;
+
+/// Rebuilds a shader when its `.wgsl` file changes
+///
+/// `build` turns the compiled module into the
+/// shader, usually with `ShaderBuilder::try_build` or
+/// one of the preset shaders. If compiling or building
+/// fails, the error is logged and the last good shader
+/// is kept.
+///
+/// Sources go through a [`Preprocessor`] that also
+/// finds includes next to the file. Included files
+/// are watched as well.
+///
+/// Changes are picked up in [`Self::poll`], call it
+/// once per frame. Not available on wasm.
+pub struct ShaderWatcher<T> {
+    path: PathBuf,
+    // the file itself and its includes
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+    preprocessor: Preprocessor,
+    source: String,
+
+    shader: T,
+    build: Build<T>,
+    error: Option<String>,
+}
+
+//
+
+impl<T> ShaderWatcher<T> {
+    /// Load and build the shader at `path`
+    pub fn new<F, E>(target: &Target, path: impl AsRef<Path>, build: F) -> Result<Self, String>
+    where
+        F: FnMut(&Target, &ShaderModule) -> Result<T, E> + 'static,
+        E: Display,
+    {
+        Self::new_with_preprocessor(target, path, None, Preprocessor::new(), build)
+    }
+
+    /// Build from `source`, like the `include_str!`
+    /// shaders in `srs2dge-res`, and reload from
+    /// `path` once the file changes
+    pub fn new_with_source<F, E>(
+        target: &Target,
+        path: impl AsRef<Path>,
+        source: &str,
+        build: F,
+    ) -> Result<Self, String>
+    where
+        F: FnMut(&Target, &ShaderModule) -> Result<T, E> + 'static,
+        E: Display,
+    {
+        Self::new_with_preprocessor(target, path, Some(source), Preprocessor::new(), build)
+    }
+
+    /// Snippets and defines of `preprocessor` are used
+    /// for every reload
+    ///
+    /// `source` is read from `path` if it is `None`.
+    pub fn new_with_preprocessor<F, E>(
+        target: &Target,
+        path: impl AsRef<Path>,
+        source: Option<&str>,
+        preprocessor: Preprocessor,
+        mut build: F,
+    ) -> Result<Self, String>
+    where
+        F: FnMut(&Target, &ShaderModule) -> Result<T, E> + 'static,
+        E: Display,
+    {
+        let mut build: Build<T> =
+            Box::new(move |target, module| build(target, module).map_err(|err| err.to_string()));
+        let path = path.as_ref().to_path_buf();
+        let source = match source {
+            Some(source) => source.to_string(),
+            None => fs::read_to_string(&path)
+                .map_err(|err| format!("Could not read {}: {err}", path.display()))?,
+        };
+        let preprocessor = preprocessor.with_include_dir(
+            path.parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| PathBuf::from(".")),
+        );
+
+        let processed = preprocessor.process(&name(&path), &source)?;
+        let shader = compile(target, &processed, &mut build)?;
+
+        let mut watcher = Self {
+            path,
+            files: vec![],
+            preprocessor,
+            source: processed.source().to_string(),
+
+            shader,
+            build,
+            error: None,
+        };
+        watcher.watch(processed.dependencies());
+        Ok(watcher)
+    }
+
+    /// Rebuild if the file or its includes have changed
+    ///
+    /// Returns `true` when a new shader was swapped in.
+    pub fn poll(&mut self, target: &Target) -> bool {
+        if self
+            .files
+            .iter()
+            .all(|(path, modified)| modified_at(path) == *modified)
+        {
+            return false;
+        }
+        for (path, modified) in self.files.iter_mut() {
+            *modified = modified_at(path);
+        }
+
+        // the file can be missing for a moment while editors save
+        let source = match fs::read_to_string(&self.path) {
+            Ok(source) => source,
+            Err(_) => return false,
+        };
+        let result = self
+            .preprocessor
+            .process(&name(&self.path), &source)
+            .and_then(|processed| {
+                self.watch(processed.dependencies());
+                if processed.source() == self.source {
+                    return Ok(None);
+                }
+                self.source = processed.source().to_string();
+                compile(target, &processed, &mut self.build).map(Some)
+            });
+
+        match result {
+            Ok(None) => false,
+            Ok(Some(shader)) => {
+                log::info!("Reloaded shader {}", self.path.display());
+                self.shader = shader;
+                self.error = None;
+                true
+            }
+            Err(err) => {
+                log::error!("Shader {} failed to reload: {err}", self.path.display());
+                self.error = Some(err);
+                false
+            }
+        }
+    }
+
+    /// Why the latest change was rejected
+    pub fn get_error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn inner(&self) -> &T {
+        &self.shader
+    }
+
+    pub fn into_inner(self) -> T {
+        self.shader
+    }
+
+    fn watch(&mut self, dependencies: &[PathBuf]) {
+        self.files = [&self.path]
+            .into_iter()
+            .chain(dependencies)
+            .map(|path| (path.clone(), modified_at(path)))
+            .collect();
+    }
+}
+
+impl<T> Deref for ShaderWatcher<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.shader
+    }
+}
+
+impl<T: Debug> Debug for ShaderWatcher<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShaderWatcher")
+            .field("path", &self.path)
+            .field("shader", &self.shader)
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+//
+
+fn modified_at(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+fn name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn compile<T>(
+    target: &Target,
+    processed: &Preprocessed,
+    build: &mut Build<T>,
+) -> Result<T, String> {
+    processed.check()?;
+    let module = ShaderModule::new_wgsl_source(target, Cow::Borrowed(processed.source()))?;
+    // validation errors of anything else that `build` creates
+    target.catch_error(|target| build(target, &module))?
+}
+
+//
+
+#[cfg(all(test, feature = "testing"))]
+mod test {
+    use super::ShaderWatcher;
+    use crate::{
+        buffer::vertex::DefaultVertex,
+        packer::rect::Rect,
+        shader::{error::ShaderError, module::ShaderModule, Shader},
+        target::Target,
+        Engine,
+    };
+    use std::{
+        fs::{self, File},
+        path::Path,
+        time::{Duration, SystemTime},
+    };
+    use wgpu::PipelineLayoutDescriptor;
+
+    const SHADER: &str = r#"
+struct VertexInput {
+	@location(0) pos: vec2<f32>,
+	@location(1) uv: vec2<f32>,
+	@location(2) col: vec4<f32>,
+};
+
+struct FragmentInput {
+	@builtin(position) pos: vec4<f32>,
+	@location(0) col: vec4<f32>,
+};
+
+@vertex
+fn vs_main(vin: VertexInput) -> FragmentInput {
+	var fin: FragmentInput;
+	fin.pos = vec4<f32>(vin.pos, 0.0, 1.0);
+	fin.col = vin.col;
+	return fin;
+}
+
+@fragment
+fn fs_main(fin: FragmentInput) -> @location(0) vec4<f32> {
+	return fin.col;
+}
+"#;
+
+    #[test]
+    fn reload() {
+        let engine = Engine::new();
+        let target = pollster::block_on(engine.new_target_offscreen(Rect::new(4, 4)));
+        let dir = std::env::temp_dir().join("srs2dge-test");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("watched.wgsl");
+        let mut age = 0;
+        let include = dir.join("watched_input.wgsl");
+        let mut save_as = |path: &Path, source: &str| {
+            fs::write(path, source).unwrap();
+            // mtime resolution might be too coarse to notice
+            age += 1;
+            File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(SystemTime::now() + Duration::from_secs(age))
+                .unwrap();
+        };
+
+        let mut save = |source: &str| save_as(&path, source);
+        save(SHADER);
+        let mut watcher = ShaderWatcher::new(
+            &target,
+            &path,
+            |target: &Target, module| -> Result<Shader<DefaultVertex, u32>, ShaderError> {
+                Shader::builder()
+                    .with_vertex(module, "vs_main")
+                    .with_fragment(module, "fs_main")
+                    .with_format(target.get_format())
+                    .with_baked_layout(PipelineLayoutDescriptor::default())
+                    .try_build(target)
+            },
+        )
+        .unwrap();
+        assert!(!watcher.poll(&target));
+
+        // syntax error
+        save(&SHADER.replace("fin.col;", "fin.col"));
+        assert!(!watcher.poll(&target));
+        assert!(watcher.get_error().is_some());
+
+        // entry point missing from the pipeline
+        save(&SHADER.replace("fs_main", "fs_other"));
+        assert!(!watcher.poll(&target));
+        assert_eq!(
+            watcher.get_error(),
+            Some("Missing FRAGMENT entry point `fs_main`")
+        );
+
+        save(&SHADER.replace("fin.col;", "vec4<f32>(1.0);"));
+        assert!(watcher.poll(&target));
+        assert!(watcher.get_error().is_none());
+
+        // includes are found next to the file and watched
+        fs::write(&include, "struct Unused { a: f32, };").unwrap();
+        save(&format!("#include \"watched_input.wgsl\"\n{SHADER}"));
+        assert!(watcher.poll(&target));
+        save_as(&include, "struct Unused { a: f32 };\n!");
+        assert!(!watcher.poll(&target));
+        assert!(watcher
+            .get_error()
+            .unwrap()
+            .starts_with("watched_input.wgsl:2:1: "));
+
+        assert!(
+            ShaderWatcher::new(&target, dir.join("missing.wgsl"), |_, _: &ShaderModule| {
+                Ok::<_, ShaderError>(())
+            })
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use crate::target::Target;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc::{channel, Receiver},
+    Arc,
+};
+use wgpu::Device;
+
+//
+
+pub struct Catcher {
+    error_receiver: Receiver<String>,
+    error_listening: Arc<AtomicBool>,
+}
+
+//
+
+impl Catcher {
+    pub fn new(device: &Device) -> Self {
+        // error capturing/handling
+        let (error_sender, error_receiver) = channel();
+        let listening = Arc::new(AtomicBool::new(false));
+        let error_listening = listening.clone();
+        device.on_uncaptured_error(move |err| match err {
+            wgpu::Error::OutOfMemory { source } => log::error!("Out of memory: {source}"),
+            wgpu::Error::Validation {
+                source,
+                description,
+            } => {
+                if listening.load(Ordering::SeqCst) {
+                    log::warn!("Handled validation error: {source} {description}");
+                    error_sender.send(description).unwrap();
+                } else {
+                    panic!("Unhandled validation error: {source} {description}")
+                }
+            }
+        });
+
+        Self {
+            error_receiver,
+            error_listening,
+        }
+    }
+
+    /// Every validation error raised in `f`,
+    /// separated by empty lines
+    pub fn catch_error<T, F: FnOnce(&Target) -> T>(target: &Target, f: F) -> Result<T, String> {
+        let s = &target.catcher;
+        // nested calls keep the outer one listening
+        let listening = s.error_listening.swap(true, Ordering::SeqCst);
+        let result = f(target);
+        s.error_listening.store(listening, Ordering::SeqCst);
+
+        // drain so that errors don't leak into the next call
+        let errors: Vec<String> = s.error_receiver.try_iter().collect();
+        if errors.is_empty() {
+            Ok(result)
+        } else {
+            Err(errors.join("\n\n"))
+        }
+    }
+}
+
+//
+
+#[cfg(all(test, feature = "testing"))]
+mod test {
+    use crate::{label, packer::rect::Rect, Engine};
+    use wgpu::{BufferDescriptor, BufferUsages};
+
+    #[test]
+    fn catch_every_error() {
+        let engine = Engine::new();
+        let target = pollster::block_on(engine.new_target_offscreen(Rect::new(4, 4)));
+        // buffers mapped at creation need a size aligned to 4
+        let invalid = |target: &crate::target::Target| {
+            target.device.create_buffer(&BufferDescriptor {
+                label: label!(),
+                size: 3,
+                usage: BufferUsages::COPY_DST,
+                mapped_at_creation: true,
+            });
+        };
+
+        let err = target
+            .catch_error(|target| {
+                invalid(target);
+                invalid(target);
+            })
+            .unwrap_err();
+        assert_eq!(err.matches("Validation Error").count(), 2, "{err}");
+        assert_eq!(target.catch_error(|_| ()), Ok(()));
+    }
+}
@@ -0,0 +1,529 @@
+use self::{
+    belt::Belt,
+    catcher::Catcher,
+    surface::{ISurface, Surface},
+};
+use crate::{
+    label,
+    prelude::{Frame, Rect},
+    texture::{mipmap::MipmapPipelines, AttachmentTexture, RenderTargetTexture},
+    DeviceStorage,
+};
+use colorful::Colorful;
+use image::RgbaImage;
+use std::sync::Arc;
+use wgpu::{
+    util::power_preference_from_env, Adapter, Device, DeviceDescriptor, DownlevelCapabilities,
+    DownlevelFlags, Features, Instance, Limits, PowerPreference, Queue, RequestAdapterOptionsBase,
+    TextureFormat, TextureFormatFeatureFlags, TextureFormatFeatures,
+};
+use winit::window::Window;
+
+//
+
+pub mod prelude;
+pub mod surface;
+
+//
+
+mod belt;
+mod catcher;
+
+//
+
+pub struct Target {
+    adapter: Arc<Adapter>,
+    pub(crate) device: Arc<Device>,
+    pub(crate) queue: Arc<Queue>,
+
+    pub(crate) surface: Option<Surface>,
+    pub(crate) headless: Option<RenderTargetTexture>,
+    pub(crate) depth_format: Option<TextureFormat>,
+    pub(crate) depth: Option<AttachmentTexture>,
+    pub(crate) msaa: u32,
+    pub(crate) msaa_texture: Option<AttachmentTexture>,
+    pub(crate) belt: Belt,
+    pub(crate) mipmap_pipelines: MipmapPipelines,
+    catcher: Catcher,
+
+    active: bool,
+    init: bool,
+}
+
+//
+
+impl Target {
+    pub async fn new(
+        instance: Arc<Instance>,
+        window: Arc<Window>,
+        device_storage: DeviceStorage,
+        compute: bool,
+    ) -> Self {
+        // create a surface that is compatible with both the window and the instance
+        let surface = ISurface::new(window, instance.clone());
+
+        // create a device and a queue for it
+        let (adapter, device, queue) =
+            Self::new_with_opt(instance, Some(&surface), device_storage, compute).await;
+
+        // complete the surface (ready for rendering)
+        let surface = Some(surface.complete(&adapter, device.clone()));
+
+        // create a belt for fast data uploading
+        let belt = Belt::new(device.clone());
+
+        // create a catcher to catch non fatal errors
+        // for example: shader compilation errors
+        let catcher = Catcher::new(&device);
+
+        Self {
+            adapter,
+            device,
+            queue,
+
+            surface,
+            headless: None,
+            depth_format: None,
+            depth: None,
+            msaa: 1,
+            msaa_texture: None,
+            belt,
+            mipmap_pipelines: Default::default(),
+            catcher,
+
+            active: false,
+            init: true,
+        }
+    }
+
+    /// Target without a window
+    ///
+    /// Frames are rendered into an offscreen
+    /// texture of size `dim`
+    pub async fn new_headless(
+        instance: Arc<Instance>,
+        device_storage: DeviceStorage,
+        dim: Rect,
+        compute: bool,
+    ) -> Self {
+        let (adapter, device, queue) =
+            Self::new_with_opt(instance, None, device_storage, compute).await;
+
+        // create a belt for fast data uploading
+        let belt = Belt::new(device.clone());
+
+        // create a catcher to catch non fatal errors
+        // for example: shader compilation errors
+        let catcher = Catcher::new(&device);
+
+        let mut target = Self {
+            adapter,
+            device,
+            queue,
+
+            surface: None,
+            headless: None,
+            depth_format: None,
+            depth: None,
+            msaa: 1,
+            msaa_texture: None,
+            belt,
+            mipmap_pipelines: Default::default(),
+            catcher,
+
+            active: false,
+            init: true,
+        };
+
+        // offscreen texture that replaces the surface
+        target.headless = Some(RenderTargetTexture::new_format(
+            &target,
+            dim,
+            target.get_format(),
+        ));
+
+        target
+    }
+
+    async fn new_with_opt(
+        instance: Arc<Instance>,
+        surface: Option<&wgpu::Surface>,
+        device_storage: DeviceStorage,
+        compute: bool,
+    ) -> (Arc<Adapter>, Arc<Device>, Arc<Queue>) {
+        // 'borrow' a device and a queue if this surface is compatible with any previous ones
+        // or create new if there were none
+        if let Some(pre_existing) =
+            Self::try_borrow_device(surface, device_storage.clone(), compute)
+        {
+            // borrow
+            pre_existing
+        } else {
+            // create
+            // get a GPU
+            let adapter = Self::make_adapter(surface, &instance).await;
+
+            // print out some info about the selected GPU
+            Self::debug_report(&adapter);
+
+            // create a logical device and a queue for it
+            let (device, queue) = Self::make_device(&adapter, compute).await;
+
+            // push to the device storage
+            if let Ok(mut write) = device_storage.write() {
+                write.push((adapter.clone(), device.clone(), queue.clone()));
+            }
+
+            (adapter, device, queue)
+        }
+    }
+
+    /// check if objects created with `self` target
+    /// can be used with the `other` target
+    pub fn compatible_with(&self, other: &Target) -> bool {
+        Arc::ptr_eq(&self.device, &other.device) && Arc::ptr_eq(&self.queue, &other.queue)
+    }
+
+    #[must_use]
+    pub fn get_frame(&mut self) -> Frame {
+        if self.active {
+            panic!("Earlier frame was not finished before starting a new one");
+        }
+
+        if self.init {
+            self.init = false;
+            if let Some(window) = self.get_window() {
+                window.set_visible(true);
+            }
+        }
+
+        let mut frame = match (self.surface.as_mut(), self.headless.as_ref()) {
+            (Some(surface), _) => {
+                Frame::new(&self.device, self.queue.clone(), surface, self.belt.get())
+            }
+            (None, Some(texture)) => {
+                Frame::new_headless(&self.device, self.queue.clone(), texture, self.belt.get())
+            }
+            (None, None) => unreachable!("Target has neither a surface nor a headless texture"),
+        };
+
+        let dim = frame.get_dim().into();
+        frame.set_msaa(self.get_msaa_texture(dim));
+        frame.set_depth(self.get_depth(dim));
+        frame.set_indirect_execution(
+            self.get_downlevel_capabilities()
+                .flags
+                .contains(DownlevelFlags::INDIRECT_EXECUTION),
+        );
+        frame
+    }
+
+    /// Depth/stencil format of the primary render pass
+    ///
+    /// Shaders drawing to the primary render pass
+    /// need a matching `ShaderBuilder::with_depth_stencil`.
+    pub fn set_depth_stencil(&mut self, format: Option<TextureFormat>) {
+        if self.depth_format != format {
+            self.depth_format = format;
+            self.depth = None;
+        }
+    }
+
+    pub fn get_depth_stencil(&self) -> Option<TextureFormat> {
+        self.depth_format
+    }
+
+    /// MSAA sample count of the primary render pass
+    ///
+    /// The primary render pass draws into a multisampled
+    /// color buffer that gets resolved into the surface.
+    /// `1` disables MSAA. `4` is the only count
+    /// every backend is guaranteed to support.
+    ///
+    /// Fails and keeps the old count if the target or
+    /// the depth/stencil format doesn't support `samples`.
+    /// Shaders built before the change keep the old count.
+    pub fn set_msaa(&mut self, samples: u32) -> Result<(), &'static str> {
+        if !samples.is_power_of_two() || samples > 32 {
+            return Err("Invalid MSAA sample count");
+        }
+
+        let supported = |format: TextureFormat, flags: TextureFormatFeatureFlags| {
+            self.get_format_features(format).flags.contains(flags)
+        };
+        if samples > 1
+            && !supported(
+                self.get_format(),
+                TextureFormatFeatureFlags::MULTISAMPLE
+                    | TextureFormatFeatureFlags::MULTISAMPLE_RESOLVE,
+            )
+        {
+            return Err("Target format does not support MSAA");
+        }
+        if let Some(depth_format) = self.depth_format.filter(|_| samples > 1) {
+            if !supported(depth_format, TextureFormatFeatureFlags::MULTISAMPLE) {
+                return Err("Depth/stencil format does not support MSAA");
+            }
+        }
+
+        if self.msaa != samples {
+            self.msaa = samples;
+            self.msaa_texture = None;
+            self.depth = None;
+        }
+        Ok(())
+    }
+
+    pub fn get_msaa(&self) -> u32 {
+        self.msaa
+    }
+
+    /// What the adapter supports of the
+    /// features WebGPU requires
+    pub fn get_downlevel_capabilities(&self) -> DownlevelCapabilities {
+        self.adapter.get_downlevel_capabilities()
+    }
+
+    /// Features of `format` on this device
+    ///
+    /// Same as the ones wgpu validates against: the
+    /// adapter's own features on downlevel devices or with
+    /// `TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES`, else the
+    /// guaranteed ones. wgpu has no per sample count flags
+    /// yet, `MULTISAMPLE` only guarantees a count of 4.
+    pub fn get_format_features(&self, format: TextureFormat) -> TextureFormatFeatures {
+        let adapter_specific = self
+            .device
+            .features()
+            .contains(Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES)
+            || !self.get_downlevel_capabilities().is_webgpu_compliant();
+
+        if adapter_specific {
+            self.adapter.get_texture_format_features(format)
+        } else {
+            format.describe().guaranteed_format_features
+        }
+    }
+
+    pub fn finish_frame(&mut self, frame: Frame) {
+        self.belt.set(frame.finish())
+    }
+
+    /// Read back the last finished frame
+    ///
+    /// Only available in headless mode
+    /// and with readable target formats
+    pub async fn read_frame(&self) -> Option<RgbaImage> {
+        Some(self.headless.as_ref()?.read(self).await.ok()?.into_rgba8())
+    }
+
+    /// Offscreen texture used in headless mode
+    pub fn get_headless_texture(&self) -> Option<&RenderTargetTexture> {
+        self.headless.as_ref()
+    }
+
+    pub fn set_vsync(&mut self, on: bool) {
+        if let Some(s) = self.surface.as_mut() {
+            s.set_vsync(on);
+        }
+    }
+
+    pub fn get_vsync(&self) -> Option<bool> {
+        self.surface.as_ref().map(|s| s.get_vsync())
+    }
+
+    pub fn get_window(&self) -> Option<Arc<Window>> {
+        self.surface.as_ref().map(|surface| surface.get_window())
+    }
+
+    pub fn get_format(&self) -> TextureFormat {
+        self.surface
+            .as_ref()
+            .map(|surface| surface.format())
+            .unwrap_or(TextureFormat::Rgba8Unorm)
+    }
+
+    pub fn get_device(&self) -> Arc<Device> {
+        self.device.clone()
+    }
+
+    pub fn catch_error<T, F: FnOnce(&Self) -> T>(&self, f: F) -> Result<T, String> {
+        Catcher::catch_error(self, f)
+    }
+
+    fn get_depth(&mut self, dim: Rect) -> Option<&AttachmentTexture> {
+        let format = self.depth_format?;
+        let dim = Rect::new(dim.width.max(1), dim.height.max(1));
+
+        // (re)create the depth texture if the frame size changed
+        if self.depth.as_ref().map(|depth| depth.get_dim()) != Some(dim) {
+            self.depth = Some(AttachmentTexture::new_multisampled(
+                self, dim, format, self.msaa,
+            ));
+        }
+
+        self.depth.as_ref()
+    }
+
+    fn get_msaa_texture(&mut self, dim: Rect) -> Option<&AttachmentTexture> {
+        if self.msaa == 1 {
+            return None;
+        }
+        let dim = Rect::new(dim.width.max(1), dim.height.max(1));
+        let format = self.get_format();
+
+        // (re)create the color buffer if the frame size or format changed
+        if self
+            .msaa_texture
+            .as_ref()
+            .map(|texture| (texture.get_dim(), texture.get_format()))
+            != Some((dim, format))
+        {
+            self.msaa_texture = Some(AttachmentTexture::new_multisampled(
+                self, dim, format, self.msaa,
+            ));
+        }
+
+        self.msaa_texture.as_ref()
+    }
+
+    fn try_borrow_device(
+        compatible_surface: Option<&wgpu::Surface>,
+        device_storage: DeviceStorage,
+        compute: bool,
+    ) -> Option<(Arc<Adapter>, Arc<Device>, Arc<Queue>)> {
+        device_storage
+            .read()
+            .ok()?
+            .iter()
+            .find(|(adapter, device, _)| {
+                let surface_supported = if let Some(surface) = compatible_surface {
+                    adapter.is_surface_supported(surface)
+                } else {
+                    true
+                };
+                // devices created without compute have lower limits
+                surface_supported && Self::limits(adapter, compute).check_limits(&device.limits())
+            })
+            .cloned()
+    }
+
+    async fn make_adapter(
+        compatible_surface: Option<&wgpu::Surface>,
+        instance: &Instance,
+    ) -> Arc<Adapter> {
+        let options = RequestAdapterOptionsBase {
+            power_preference: power_preference_from_env()
+                .unwrap_or(PowerPreference::HighPerformance),
+            compatible_surface,
+            ..Default::default()
+        };
+        Arc::new(
+            instance
+                .request_adapter(&options)
+                .await
+                .expect("No suitable GPUs"),
+        )
+    }
+
+    fn debug_report(adapter: &Adapter) {
+        if log::log_enabled!(log::Level::Debug) {
+            let gpu_info = adapter.get_info();
+            let api = format!("{:?}", gpu_info.backend).red();
+            let name = gpu_info.name.blue();
+            let ty = format!("{:?}", gpu_info.device_type).green();
+
+            log::debug!("GPU API: {api}");
+            log::debug!("GPU: {name} ({ty})");
+        }
+    }
+
+    async fn make_device(adapter: &Adapter, compute: bool) -> (Arc<Device>, Arc<Queue>) {
+        let limits = Self::limits(adapter, compute);
+
+        let (device, queue) = adapter
+            .request_device(
+                &DeviceDescriptor {
+                    label: label!(),
+                    // used if available, `RenderPass` falls back to single draws
+                    features: adapter.features()
+                        & (Features::MULTI_DRAW_INDIRECT
+                            | Features::POLYGON_MODE_LINE
+                            | Features::POLYGON_MODE_POINT),
+                    limits: Limits {
+                        // max_texture_dimension_2d: 16384,
+                        ..limits
+                    },
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        (Arc::new(device), Arc::new(queue))
+    }
+
+    fn limits(adapter: &Adapter, compute: bool) -> Limits {
+        // compute shaders need storage buffers
+        if compute
+            && adapter
+                .get_downlevel_capabilities()
+                .flags
+                .contains(DownlevelFlags::COMPUTE_SHADERS)
+        {
+            Limits::downlevel_defaults()
+        } else {
+            Limits::downlevel_webgl2_defaults()
+        }
+    }
+}
+
+//
+
+#[cfg(all(test, feature = "testing"))]
+mod test {
+    use crate::{
+        buffer::{DefaultVertex, IndexBuffer, VertexBuffer},
+        color::Color,
+        shader::{module::ShaderModule, Shader},
+        testing::{fixture::SHADER, GoldenImage},
+    };
+    use wgpu::PipelineLayoutDescriptor;
+
+    #[test]
+    fn msaa() {
+        GoldenImage::new(concat!(env!("CARGO_MANIFEST_DIR"), "/golden/msaa.png"))
+            .with_msaa(4)
+            .check_blocking(|target, frame| {
+                assert!(target.set_msaa(3).is_err());
+                assert_eq!(target.get_msaa(), 4);
+
+                // picks up the sample count of the target
+                let module = ShaderModule::new_wgsl_source(target, SHADER.into()).unwrap();
+                let shader: Shader<DefaultVertex, u32> = Shader::builder()
+                    .with_vertex(&module, "vs_main")
+                    .with_fragment(&module, "fs_main")
+                    .with_format(target.get_format())
+                    .with_baked_layout(PipelineLayoutDescriptor::default())
+                    .build(target);
+
+                let col = Color::WHITE.to_vec4().to_array();
+                let vbo = VertexBuffer::new_with(
+                    target,
+                    &[
+                        DefaultVertex::from_arrays([-0.8, -0.6], col, [0.0, 0.0]),
+                        DefaultVertex::from_arrays([0.7, -0.9], col, [0.0, 0.0]),
+                        DefaultVertex::from_arrays([0.1, 0.8], col, [0.0, 0.0]),
+                    ],
+                );
+                let ibo = IndexBuffer::new_with(target, &[0, 1, 2]);
+
+                frame
+                    .primary_render_pass()
+                    .bind_vbo(&vbo)
+                    .bind_ibo(&ibo)
+                    .bind_shader(&shader)
+                    .draw_indexed(0..3, 0, 0..1);
+            })
+            .unwrap();
+    }
+}
@@ -0,0 +1 @@
+pub use super::{belt::*, catcher::*, surface::*, *};
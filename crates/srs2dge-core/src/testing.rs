@@ -0,0 +1,437 @@
+//! Golden image testing
+//!
+//! Renders a closure into a headless [`Target`]
+//! and compares the result against a stored
+//! reference image (`.png` or `.qoi`).
+//!
+//! Set `GOLDEN_UPDATE=1` to write the references
+//! from the rendered results. Without it, a
+//! missing reference is an error.
+//!
+//! ```ignore
+//! GoldenImage::new(concat!(env!("CARGO_MANIFEST_DIR"), "/golden/clear.png"))
+//!     .check_blocking(|_, frame| {
+//!         frame.set_clear_color(Color::RED);
+//!         frame.primary_render_pass();
+//!     })
+//!     .unwrap();
+//! ```
+
+use crate::{
+    color::Color, frame::Frame, packer::rect::Rect, target::Target, texture::stream::decode_qoi,
+    Engine,
+};
+use image::{ImageError, Rgba, RgbaImage};
+use main_game_loop::state::window::WindowState;
+use rapid_qoi::{Colors, Qoi};
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    fs, io,
+    path::{Path, PathBuf},
+};
+use wgpu::TextureFormat;
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+
+//
+
+#[derive(Debug, Clone)]
+pub struct GoldenImage {
+    path: PathBuf,
+    dim: Rect,
+    tolerance: u8,
+    clear_color: Color,
+    depth_stencil: Option<TextureFormat>,
+    msaa: u32,
+    compute: bool,
+}
+
+#[derive(Debug)]
+pub enum GoldenError {
+    Io(io::Error),
+    Image(ImageError),
+    Qoi(String),
+    /// the rendered image is written to `<name>.actual.png`
+    MissingReference(PathBuf),
+    DimensionMismatch {
+        expected: Rect,
+        actual: Rect,
+    },
+    PixelMismatch {
+        /// number of pixels outside of the tolerance
+        count: usize,
+        /// largest per channel difference
+        max_diff: u8,
+        /// mismatching pixels are red in this image
+        diff: PathBuf,
+    },
+}
+
+//
+
+impl GoldenImage {
+    /// Reference image at `path`
+    ///
+    /// The image format is picked from the extension.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            dim: Rect::new(64, 64),
+            tolerance: 2,
+            clear_color: Color::BLACK,
+            depth_stencil: None,
+            msaa: 1,
+            compute: false,
+        }
+    }
+
+    pub fn with_dim(mut self, dim: Rect) -> Self {
+        self.dim = dim;
+        self
+    }
+
+    /// max allowed difference per color channel
+    pub fn with_tolerance(mut self, tolerance: u8) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    pub fn with_clear_color(mut self, clear_color: Color) -> Self {
+        self.clear_color = clear_color;
+        self
+    }
+
+    /// depth/stencil format of the headless target
+    pub fn with_depth_stencil(mut self, format: Option<TextureFormat>) -> Self {
+        self.depth_stencil = format;
+        self
+    }
+
+    /// MSAA sample count of the headless target
+    pub fn with_msaa(mut self, samples: u32) -> Self {
+        self.msaa = samples;
+        self
+    }
+
+    /// See [`Engine::with_compute`]
+    pub fn with_compute(mut self) -> Self {
+        self.compute = true;
+        self
+    }
+
+    pub fn get_dim(&self) -> Rect {
+        self.dim
+    }
+
+    /// `WindowState` matching the headless target
+    /// for things that expect a window
+    pub fn window_state(&self) -> WindowState {
+        WindowState {
+            size: PhysicalSize::new(self.dim.width, self.dim.height),
+            aspect: self.dim.width as f32 / self.dim.height as f32,
+            focused: true,
+            should_close: false,
+            cursor_in: false,
+            cursor_pos: PhysicalPosition::new(0.0, 0.0),
+            scale_factor: 1.0,
+            id: None,
+        }
+    }
+
+    /// Render `f` into a new headless target
+    /// and read the result back
+    pub async fn render<F>(&self, f: F) -> RgbaImage
+    where
+        F: FnOnce(&mut Target, &mut Frame),
+    {
+        let engine = if self.compute {
+            Engine::new().with_compute()
+        } else {
+            Engine::new()
+        };
+        let mut target = engine.new_target_offscreen(self.dim).await;
+        target.set_depth_stencil(self.depth_stencil);
+        target
+            .set_msaa(self.msaa)
+            .unwrap_or_else(|err| panic!("{err}"));
+
+        let mut frame = target.get_frame();
+        frame.set_clear_color(self.clear_color);
+        f(&mut target, &mut frame);
+        target.finish_frame(frame);
+
+        target
+            .read_frame()
+            .await
+            .expect("Headless target has no offscreen texture")
+    }
+
+    pub async fn check<F>(&self, f: F) -> Result<(), GoldenError>
+    where
+        F: FnOnce(&mut Target, &mut Frame),
+    {
+        let actual = self.render(f).await;
+        self.compare(&actual)
+    }
+
+    pub fn check_blocking<F>(&self, f: F) -> Result<(), GoldenError>
+    where
+        F: FnOnce(&mut Target, &mut Frame),
+    {
+        pollster::block_on(self.check(f))
+    }
+
+    /// Compare `actual` against the reference image
+    ///
+    /// Writes `<name>.actual.png` and `<name>.diff.png`
+    /// next to the reference if they don't match.
+    pub fn compare(&self, actual: &RgbaImage) -> Result<(), GoldenError> {
+        if std::env::var_os("GOLDEN_UPDATE").is_some() {
+            log::warn!("Writing golden image: {}", self.path.display());
+            return save(&self.path, actual);
+        }
+        if !self.path.exists() {
+            save(&self.sibling("actual"), actual)?;
+            return Err(GoldenError::MissingReference(self.path.clone()));
+        }
+
+        let expected = load(&self.path)?;
+        if expected.dimensions() != actual.dimensions() {
+            return Err(GoldenError::DimensionMismatch {
+                expected: expected.dimensions().into(),
+                actual: actual.dimensions().into(),
+            });
+        }
+
+        let mut count = 0;
+        let mut max_diff = 0;
+        let mut diff = RgbaImage::new(actual.width(), actual.height());
+        for ((e, a), d) in expected
+            .pixels()
+            .zip(actual.pixels())
+            .zip(diff.pixels_mut())
+        {
+            let pixel_diff =
+                e.0.iter()
+                    .zip(a.0.iter())
+                    .map(|(e, a)| e.abs_diff(*a))
+                    .max()
+                    .unwrap_or(0);
+            max_diff = max_diff.max(pixel_diff);
+
+            *d = if pixel_diff > self.tolerance {
+                count += 1;
+                Rgba([255, 0, 0, 255])
+            } else {
+                // dimmed grayscale of the expected pixel
+                let l = ((e[0] as u32 + e[1] as u32 + e[2] as u32) / 12) as u8;
+                Rgba([l, l, l, 255])
+            };
+        }
+
+        if count == 0 {
+            return Ok(());
+        }
+
+        let actual_path = self.sibling("actual");
+        let diff_path = self.sibling("diff");
+        save(&actual_path, actual)?;
+        save(&diff_path, &diff)?;
+
+        Err(GoldenError::PixelMismatch {
+            count,
+            max_diff,
+            diff: diff_path,
+        })
+    }
+
+    fn sibling(&self, suffix: &str) -> PathBuf {
+        let stem = self
+            .path
+            .file_stem()
+            .map(|s| s.to_string_lossy())
+            .unwrap_or_default();
+        self.path.with_file_name(format!("{stem}.{suffix}.png"))
+    }
+}
+
+impl Display for GoldenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GoldenError::Io(err) => write!(f, "IO error: {err}"),
+            GoldenError::Image(err) => write!(f, "Image error: {err}"),
+            GoldenError::Qoi(err) => write!(f, "QOI error: {err}"),
+            GoldenError::MissingReference(path) => write!(
+                f,
+                "Missing reference image {}, run with GOLDEN_UPDATE=1 to write it",
+                path.display()
+            ),
+            GoldenError::DimensionMismatch { expected, actual } => write!(
+                f,
+                "Dimension mismatch: expected {}x{}, got {}x{}",
+                expected.width, expected.height, actual.width, actual.height
+            ),
+            GoldenError::PixelMismatch {
+                count,
+                max_diff,
+                diff,
+            } => write!(
+                f,
+                "{count} pixel(s) differ (max channel difference: {max_diff}), see: {}",
+                diff.display()
+            ),
+        }
+    }
+}
+
+impl Error for GoldenError {}
+
+impl From<io::Error> for GoldenError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<ImageError> for GoldenError {
+    fn from(err: ImageError) -> Self {
+        Self::Image(err)
+    }
+}
+
+//
+
+fn is_qoi(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.eq_ignore_ascii_case("qoi"))
+        .unwrap_or(false)
+}
+
+fn load(path: &Path) -> Result<RgbaImage, GoldenError> {
+    if !is_qoi(path) {
+        return Ok(image::open(path)?.into_rgba8());
+    }
+
+    decode_qoi(&fs::read(path)?).map_err(GoldenError::Qoi)
+}
+
+fn save(path: &Path, image: &RgbaImage) -> Result<(), GoldenError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    if !is_qoi(path) {
+        return Ok(image.save(path)?);
+    }
+
+    let qoi = Qoi {
+        width: image.width(),
+        height: image.height(),
+        colors: Colors::Rgba,
+    };
+    let bytes = qoi
+        .encode_alloc(image.as_raw())
+        .map_err(|err| GoldenError::Qoi(err.to_string()))?;
+    Ok(fs::write(path, bytes)?)
+}
+
+//
+
+#[cfg(test)]
+pub(crate) mod fixture {
+    use crate::{buffer::DefaultVertex, color::Color};
+
+    /// Draws the vertex colors, `uv.x` is the depth
+    pub const SHADER: &str = r#"
+struct VertexInput {
+	@location(0) pos: vec2<f32>,
+	@location(1) uv: vec2<f32>,
+	@location(2) col: vec4<f32>,
+};
+
+struct FragmentInput {
+	@builtin(position) pos: vec4<f32>,
+	@location(0) col: vec4<f32>,
+};
+
+@vertex
+fn vs_main(vin: VertexInput) -> FragmentInput {
+	var fin: FragmentInput;
+	fin.pos = vec4<f32>(vin.pos, vin.uv.x, 1.0);
+	fin.col = vin.col;
+	return fin;
+}
+
+@fragment
+fn fs_main(fin: FragmentInput) -> @location(0) vec4<f32> {
+	return fin.col;
+}
+"#;
+
+    /// Quad at `x` with the depth `z`, drawn as a triangle strip
+    pub fn quad(x: f32, z: f32, col: Color) -> [DefaultVertex; 4] {
+        let col = col.to_vec4().to_array();
+        [
+            DefaultVertex::from_arrays([x - 0.5, -0.5], col, [z, 0.0]),
+            DefaultVertex::from_arrays([x - 0.5, 0.5], col, [z, 0.0]),
+            DefaultVertex::from_arrays([x + 0.5, -0.5], col, [z, 0.0]),
+            DefaultVertex::from_arrays([x + 0.5, 0.5], col, [z, 0.0]),
+        ]
+    }
+}
+
+//
+
+#[cfg(test)]
+mod test {
+    use super::{save, GoldenError, GoldenImage};
+    use image::{Rgba, RgbaImage};
+
+    fn golden(name: &str) -> GoldenImage {
+        let path = std::env::temp_dir()
+            .join("srs2dge-golden")
+            .join(format!("{}-{name}", std::process::id()));
+        GoldenImage::new(path)
+    }
+
+    #[test]
+    fn compare_within_tolerance() {
+        let golden = golden("tolerance.png").with_tolerance(4);
+        let image = RgbaImage::from_pixel(4, 4, Rgba([100, 100, 100, 255]));
+        assert!(matches!(
+            golden.compare(&image),
+            Err(GoldenError::MissingReference(_))
+        ));
+        save(&golden.path, &image).unwrap();
+        golden.compare(&image).unwrap();
+
+        let image = RgbaImage::from_pixel(4, 4, Rgba([104, 96, 100, 255]));
+        golden.compare(&image).unwrap();
+
+        let mut image = image;
+        image.put_pixel(1, 2, Rgba([105, 100, 100, 255]));
+        match golden.compare(&image) {
+            Err(GoldenError::PixelMismatch {
+                count: 1,
+                max_diff: 5,
+                diff,
+            }) => assert_eq!(
+                image::open(diff).unwrap().to_rgba8()[(1, 2)],
+                Rgba([255, 0, 0, 255])
+            ),
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn compare_qoi() {
+        let golden = golden("qoi.qoi").with_tolerance(0);
+        let image = RgbaImage::from_fn(8, 3, |x, y| Rgba([x as u8 * 30, y as u8 * 80, 7, 200]));
+        save(&golden.path, &image).unwrap();
+        golden.compare(&image).unwrap();
+
+        assert!(matches!(
+            golden.compare(&RgbaImage::new(3, 8)),
+            Err(GoldenError::DimensionMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,273 @@
+use super::{layered::LayeredTexture, DEFAULT_USAGE};
+use crate::{
+    label,
+    prelude::{PositionedRect, Rect},
+    target::Target,
+};
+use image::{DynamicImage, RgbaImage};
+use std::{num::NonZeroU32, ops::Deref};
+use wgpu::{
+    DownlevelFlags, TextureFormat, TextureSampleType, TextureView, TextureViewDescriptor,
+    TextureViewDimension,
+};
+
+//
+
+/// Array of same sized 2D textures
+///
+/// Derefs to a `texture_2d_array` view so that
+/// sprite sheets, tile variants or glyph pages
+/// can share a single binding.
+#[derive(Debug)]
+pub struct TextureArray<const USAGE: u32 = DEFAULT_USAGE> {
+    inner: LayeredTexture<USAGE>,
+}
+
+//
+
+impl<const USAGE: u32> TextureArray<USAGE> {
+    pub fn new(target: &Target, format: TextureFormat, dim: Rect, layers: u32) -> Self {
+        Self {
+            inner: LayeredTexture::new(
+                target,
+                format,
+                dim,
+                layers,
+                TextureViewDimension::D2Array,
+                None,
+            ),
+        }
+    }
+
+    /// Every image becomes one layer
+    ///
+    /// All images have to be the same size.
+    pub fn new_rgba_with(target: &Target, layers: &[RgbaImage]) -> Self {
+        Self {
+            inner: LayeredTexture::new_rgba_with(target, layers, TextureViewDimension::D2Array),
+        }
+    }
+
+    pub fn inner(&self) -> &wgpu::Texture {
+        &self.inner.texture
+    }
+
+    /// Size of a single layer
+    pub fn get_dim(&self) -> Rect {
+        self.inner.dim
+    }
+
+    pub fn get_format(&self) -> TextureFormat {
+        self.inner.format
+    }
+
+    pub fn get_layers(&self) -> u32 {
+        self.inner.layers
+    }
+
+    /// Sample type for bind group layouts
+    pub fn sample_type(&self) -> TextureSampleType {
+        self.inner.sample_type()
+    }
+
+    /// Write `image` to `spot` of `layer`
+    ///
+    /// Takes the same images as `Texture::write`.
+    pub fn write_layer(
+        &self,
+        target: &Target,
+        layer: u32,
+        spot: PositionedRect,
+        image: DynamicImage,
+    ) -> Result<(), &'static str> {
+        self.inner.write(target, layer, spot, image)
+    }
+
+    /// `texture_2d` view of a single layer
+    ///
+    /// The GL backend can only sample the first
+    /// layer through these, render to them instead.
+    pub fn layer_view(&self, layer: u32) -> TextureView {
+        if layer >= self.inner.layers {
+            panic!("Layer {layer} out of the texture array's bounds");
+        }
+
+        self.inner.texture.create_view(&TextureViewDescriptor {
+            label: label!(),
+            dimension: Some(TextureViewDimension::D2),
+            base_array_layer: layer,
+            array_layer_count: NonZeroU32::new(1),
+            ..Default::default()
+        })
+    }
+
+    /// `texture_cube` view of the first six layers,
+    /// or `texture_cube_array` view of every layer
+    ///
+    /// Requires square layers and a multiple of six layers.
+    /// Cube arrays also need the `CUBE_ARRAY_TEXTURES`
+    /// downlevel flag, which WebGL2 and GLES don't have.
+    pub fn cube_view(&self, target: &Target, array: bool) -> Result<TextureView, &'static str> {
+        let LayeredTexture { dim, layers, .. } = self.inner;
+        if dim.width != dim.height || !layers.is_multiple_of(6) {
+            return Err("Cube views need square layers and a multiple of 6 layers");
+        }
+        if array
+            && !target
+                .get_downlevel_capabilities()
+                .flags
+                .contains(DownlevelFlags::CUBE_ARRAY_TEXTURES)
+        {
+            return Err("Cube array textures are not supported");
+        }
+
+        let (dimension, count) = if array {
+            (TextureViewDimension::CubeArray, layers)
+        } else {
+            (TextureViewDimension::Cube, 6)
+        };
+        Ok(self.inner.texture.create_view(&TextureViewDescriptor {
+            label: label!(),
+            dimension: Some(dimension),
+            array_layer_count: NonZeroU32::new(count),
+            ..Default::default()
+        }))
+    }
+}
+
+impl<const USAGE: u32> Deref for TextureArray<USAGE> {
+    type Target = TextureView;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner.view
+    }
+}
+
+//
+
+#[cfg(all(test, feature = "testing"))]
+mod test {
+    use super::TextureArray;
+    use crate::{
+        buffer::{DefaultVertex, IndexBuffer, VertexBuffer},
+        color::Color,
+        label,
+        packer::rect::{PositionedRect, Rect},
+        shader::{module::ShaderModule, Shader},
+        testing::{fixture::quad, GoldenImage},
+        texture::sampler::Sampler,
+    };
+    use image::{Rgba, RgbaImage};
+    use wgpu::{
+        BindGroupDescriptor, BindGroupEntry, BindingResource, DownlevelFlags, PrimitiveTopology,
+        TextureFormat, TextureUsages,
+    };
+
+    const ARRAY_SHADER: &str = r#"
+struct VertexInput {
+	@location(0) pos: vec2<f32>,
+	@location(1) uv: vec2<f32>,
+	@location(2) col: vec4<f32>,
+};
+
+struct FragmentInput {
+	@builtin(position) pos: vec4<f32>,
+	@location(0) uv: vec2<f32>,
+};
+
+@group(0)
+@binding(0)
+var t_layers: texture_2d_array<f32>;
+
+@group(0)
+@binding(1)
+var s_layers: sampler;
+
+@vertex
+fn vs_main(vin: VertexInput) -> FragmentInput {
+	var fin: FragmentInput;
+	fin.pos = vec4<f32>(vin.pos * 1.6, 0.0, 1.0);
+	fin.uv = vin.pos + 0.5;
+	return fin;
+}
+
+@fragment
+fn fs_main(fin: FragmentInput) -> @location(0) vec4<f32> {
+	// left half from layer 0, right half from layer 1
+	let layer = i32(fin.uv.x >= 0.5);
+	return textureSample(t_layers, s_layers, fin.uv, layer);
+}
+"#;
+
+    #[test]
+    fn texture_array() {
+        GoldenImage::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/golden/texture_array.png"
+        ))
+        .check_blocking(|target, frame| {
+            let module = ShaderModule::new_wgsl_source(target, ARRAY_SHADER.into()).unwrap();
+            let shader: Shader<DefaultVertex, u32> = Shader::builder()
+                .with_vertex(&module, "vs_main")
+                .with_fragment(&module, "fs_main")
+                .with_topology(PrimitiveTopology::TriangleStrip)
+                .with_format(target.get_format())
+                .build(target);
+
+            let layer = |col: [u8; 4]| RgbaImage::from_pixel(4, 4, Rgba(col));
+            let texture: TextureArray<
+                { TextureUsages::TEXTURE_BINDING.bits() | TextureUsages::COPY_DST.bits() },
+            > = TextureArray::new_rgba_with(
+                target,
+                &[layer([255, 0, 0, 255]), layer([0, 255, 0, 255])],
+            );
+            assert_eq!(texture.get_layers(), 2);
+            assert!(texture.cube_view(target, false).is_err());
+            let cube: TextureArray =
+                TextureArray::new(target, TextureFormat::Rgba8Unorm, Rect::new(4, 4), 6);
+            assert!(cube.cube_view(target, false).is_ok());
+            assert_eq!(
+                cube.cube_view(target, true).is_ok(),
+                target
+                    .get_downlevel_capabilities()
+                    .flags
+                    .contains(DownlevelFlags::CUBE_ARRAY_TEXTURES)
+            );
+            texture
+                .write_layer(
+                    target,
+                    1,
+                    PositionedRect::new(2, 2, 2, 2),
+                    RgbaImage::from_pixel(2, 2, Rgba([0, 0, 255, 255])).into(),
+                )
+                .unwrap();
+
+            let sampler = Sampler::nearest(target);
+            let bind_group = target.get_device().create_bind_group(&BindGroupDescriptor {
+                label: label!(),
+                layout: &shader.bind_group_layout(0),
+                entries: &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: BindingResource::TextureView(&texture),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: BindingResource::Sampler(&sampler),
+                    },
+                ],
+            });
+
+            let vbo = VertexBuffer::new_with(target, &quad(0.0, 0.0, Color::WHITE));
+            let ibo = IndexBuffer::new_with(target, &[0, 1, 2, 3]);
+            frame
+                .primary_render_pass()
+                .bind_vbo(&vbo)
+                .bind_ibo(&ibo)
+                .bind_shader(&shader)
+                .bind_group(&bind_group)
+                .draw_indexed(0..4, 0, 0..1);
+        })
+        .unwrap();
+    }
+}
@@ -0,0 +1,167 @@
+use crate::{label, prelude::Rect, target::Target};
+use wgpu::{
+    BindGroupDescriptor, BindGroupEntry, BindingResource, Color, ColorTargetState, ColorWrites,
+    CommandEncoderDescriptor, FragmentState, LoadOp, Operations, RenderPassColorAttachment,
+    RenderPassDescriptor, RenderPipelineDescriptor, ShaderModuleDescriptor, ShaderSource,
+    TextureDescriptor, TextureDimension, TextureFormat, TextureUsages, TextureView, VertexState,
+};
+
+//
+
+const SHADER: &str = r#"
+@group(0)
+@binding(0)
+var t_depth: texture_2d<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) i: u32) -> @builtin(position) vec4<f32> {
+	// one triangle covering the whole target
+	let uv = vec2<f32>(f32((i << 1u) & 2u), f32(i & 2u));
+	return vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) pos: vec4<f32>) -> @location(0) f32 {
+	// GLSL has no texel fetches for `texture_depth_2d`
+	return textureLoad(t_depth, vec2<i32>(pos.xy), 0).x;
+}
+"#;
+
+//
+
+/// Render the depth of `depth` into a new
+/// `R32Float` texture that can be copied
+///
+/// Depth to buffer copies are not supported
+/// everywhere, for example on GL.
+pub(super) fn to_r32float(target: &Target, depth: &TextureView, dim: Rect) -> wgpu::Texture {
+    let texture = target.device.create_texture(&TextureDescriptor {
+        label: label!(),
+        size: dim.into(),
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: TextureFormat::R32Float,
+        usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+    });
+    let view = texture.create_view(&Default::default());
+
+    let module = target.device.create_shader_module(ShaderModuleDescriptor {
+        label: label!(),
+        source: ShaderSource::Wgsl(SHADER.into()),
+    });
+    let pipeline = target
+        .device
+        .create_render_pipeline(&RenderPipelineDescriptor {
+            label: label!(),
+            layout: None,
+            vertex: VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            primitive: Default::default(),
+            depth_stencil: None,
+            multisample: Default::default(),
+            fragment: Some(FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                targets: &[Some(ColorTargetState {
+                    format: TextureFormat::R32Float,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+    let bind_group = target.device.create_bind_group(&BindGroupDescriptor {
+        label: label!(),
+        layout: &pipeline.get_bind_group_layout(0),
+        entries: &[BindGroupEntry {
+            binding: 0,
+            resource: BindingResource::TextureView(depth),
+        }],
+    });
+
+    let mut encoder = target
+        .device
+        .create_command_encoder(&CommandEncoderDescriptor { label: label!() });
+    {
+        let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: label!(),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Clear(Color::TRANSPARENT),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        pass.set_pipeline(&pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+    target.queue.submit([encoder.finish()]);
+
+    texture
+}
+
+//
+
+#[cfg(all(test, feature = "testing"))]
+mod test {
+    use crate::{
+        buffer::{DefaultVertex, IndexBuffer, VertexBuffer},
+        color::Color,
+        shader::{module::ShaderModule, Shader},
+        testing::{
+            fixture::{quad, SHADER},
+            GoldenImage,
+        },
+    };
+    use wgpu::{
+        CompareFunction, DepthStencilState, PipelineLayoutDescriptor, PrimitiveTopology,
+        TextureFormat,
+    };
+
+    #[test]
+    fn depth_test() {
+        let format = TextureFormat::Depth32Float;
+        GoldenImage::new(concat!(env!("CARGO_MANIFEST_DIR"), "/golden/depth.png"))
+            .with_depth_stencil(Some(format))
+            .check_blocking(|target, frame| {
+                let module = ShaderModule::new_wgsl_source(target, SHADER.into()).unwrap();
+                let shader: Shader<DefaultVertex, u32> = Shader::builder()
+                    .with_vertex(&module, "vs_main")
+                    .with_fragment(&module, "fs_main")
+                    .with_topology(PrimitiveTopology::TriangleStrip)
+                    .with_format(target.get_format())
+                    .with_baked_layout(PipelineLayoutDescriptor::default())
+                    .with_depth_stencil(Some(DepthStencilState {
+                        format,
+                        depth_write_enabled: true,
+                        depth_compare: CompareFunction::Less,
+                        stencil: Default::default(),
+                        bias: Default::default(),
+                    }))
+                    .build(target);
+
+                // the front (red) quad is drawn first
+                let vertices: Vec<DefaultVertex> =
+                    [quad(-0.2, 0.2, Color::RED), quad(0.2, 0.8, Color::BLUE)].concat();
+                let vbo = VertexBuffer::new_with(target, &vertices);
+                let ibo = IndexBuffer::new_with(target, &[0, 1, 2, 3]);
+
+                frame
+                    .primary_render_pass()
+                    .bind_vbo(&vbo)
+                    .bind_ibo(&ibo)
+                    .bind_shader(&shader)
+                    .draw_indexed(0..4, 0, 0..1)
+                    .draw_indexed(0..4, 4, 0..1);
+            })
+            .unwrap();
+    }
+}
@@ -0,0 +1,144 @@
+use super::{encode, write_bytes};
+use crate::{
+    label,
+    prelude::{PositionedRect, Rect},
+    target::Target,
+};
+use image::{DynamicImage, RgbaImage};
+use wgpu::{
+    util::DeviceExt, Extent3d, TextureDescriptor, TextureDimension, TextureFormat,
+    TextureSampleType, TextureUsages, TextureView, TextureViewDescriptor, TextureViewDimension,
+};
+
+//
+
+/// Same sized layers of a [`super::array::TextureArray`]
+/// or slices of a [`super::volume::Texture3D`]
+#[derive(Debug)]
+pub(super) struct LayeredTexture<const USAGE: u32> {
+    pub(super) texture: wgpu::Texture,
+    pub(super) format: TextureFormat,
+    pub(super) view: TextureView,
+    pub(super) dim: Rect,
+    pub(super) layers: u32,
+}
+
+//
+
+impl<const USAGE: u32> LayeredTexture<USAGE> {
+    /// `dimension` is the default view,
+    /// `D3` makes a 3D texture, anything else
+    /// an array of 2D textures
+    pub(super) fn new(
+        target: &Target,
+        format: TextureFormat,
+        dim: Rect,
+        layers: u32,
+        dimension: TextureViewDimension,
+        data: Option<&[u8]>,
+    ) -> Self {
+        if layers == 0 {
+            panic!("Layered textures need at least one layer");
+        }
+
+        let desc = TextureDescriptor {
+            label: label!(),
+            size: Extent3d {
+                width: dim.width,
+                height: dim.height,
+                depth_or_array_layers: layers,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: match dimension {
+                TextureViewDimension::D3 => TextureDimension::D3,
+                _ => TextureDimension::D2,
+            },
+            format,
+            usage: TextureUsages::from_bits_truncate(USAGE),
+        };
+        let texture = match data {
+            None => target.device.create_texture(&desc),
+            Some(data) => target
+                .device
+                .create_texture_with_data(&target.queue, &desc, data),
+        };
+        // a single layer would default to a `texture_2d` view
+        let view = texture.create_view(&TextureViewDescriptor {
+            label: label!(),
+            dimension: Some(dimension),
+            ..Default::default()
+        });
+
+        Self {
+            texture,
+            format,
+            view,
+            dim,
+            layers,
+        }
+    }
+
+    pub(super) fn new_rgba_with(
+        target: &Target,
+        layers: &[RgbaImage],
+        dimension: TextureViewDimension,
+    ) -> Self {
+        let dim = Rect::from(
+            layers
+                .first()
+                .expect("Layered textures need at least one layer")
+                .dimensions(),
+        );
+        if layers
+            .iter()
+            .any(|layer| Rect::from(layer.dimensions()) != dim)
+        {
+            panic!("Texture layers have to be the same size");
+        }
+
+        let data: Vec<u8> = layers
+            .iter()
+            .flat_map(|layer| layer.as_raw())
+            .copied()
+            .collect();
+        Self::new(
+            target,
+            TextureFormat::Rgba8Unorm,
+            dim,
+            layers.len() as u32,
+            dimension,
+            Some(&data),
+        )
+    }
+
+    pub(super) fn sample_type(&self) -> TextureSampleType {
+        self.format.describe().sample_type
+    }
+
+    pub(super) fn write(
+        &self,
+        target: &Target,
+        layer: u32,
+        spot: PositionedRect,
+        image: DynamicImage,
+    ) -> Result<(), &'static str> {
+        if layer >= self.layers {
+            return Err("Layer out of the texture's bounds");
+        }
+        if spot.width != image.width() || spot.height != image.height() {
+            return Err("Image dimensions do not match the spot dimension");
+        }
+
+        let bytes = encode(self.format, &image)?;
+        write_bytes(
+            target,
+            &self.texture,
+            self.format,
+            self.dim,
+            layer,
+            spot,
+            &bytes,
+        )
+    }
+}
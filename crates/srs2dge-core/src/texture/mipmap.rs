@@ -0,0 +1,226 @@
+use crate::{label, prelude::Rect, target::Target};
+use std::{cell::RefCell, collections::HashMap, num::NonZeroU32};
+use wgpu::{
+    BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor,
+    BindGroupLayoutEntry, BindingResource, BindingType, Color, ColorTargetState, ColorWrites,
+    CommandEncoder, CommandEncoderDescriptor, FilterMode, FragmentState, LoadOp, Operations,
+    Origin3d, PipelineLayoutDescriptor, PrimitiveState, RenderPassColorAttachment,
+    RenderPassDescriptor, RenderPipeline, RenderPipelineDescriptor, SamplerBindingType,
+    SamplerDescriptor, ShaderModuleDescriptor, ShaderSource, ShaderStages, TextureAspect,
+    TextureDescriptor, TextureDimension, TextureFormat, TextureSampleType, TextureUsages,
+    TextureView, TextureViewDescriptor, TextureViewDimension, VertexState,
+};
+
+//
+
+const SHADER: &str = r#"
+struct FragmentInput {
+	@builtin(position) pos: vec4<f32>,
+	@location(0) uv: vec2<f32>,
+};
+
+@group(0)
+@binding(0)
+var t_texture: texture_2d<f32>;
+
+@group(0)
+@binding(1)
+var s_texture: sampler;
+
+@vertex
+fn vs_main(@builtin(vertex_index) i: u32) -> FragmentInput {
+	// one triangle covering the whole target
+	let uv = vec2<f32>(f32((i << 1u) & 2u), f32(i & 2u));
+	var fin: FragmentInput;
+	fin.pos = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+	fin.uv = uv;
+	return fin;
+}
+
+@fragment
+fn fs_main(fin: FragmentInput) -> @location(0) vec4<f32> {
+	return textureSample(t_texture, s_texture, fin.uv);
+}
+"#;
+
+//
+
+/// Mipmap pipelines compiled so far, one per format
+#[derive(Default)]
+pub(crate) struct MipmapPipelines(
+    RefCell<HashMap<TextureFormat, (RenderPipeline, BindGroupLayout)>>,
+);
+
+//
+
+/// Number of mip levels down to 1x1
+pub fn mip_level_count(dim: Rect) -> u32 {
+    u32::BITS - dim.width.max(dim.height).max(1).leading_zeros()
+}
+
+/// Size of the mip `level`
+pub fn mip_level_dim(dim: Rect, level: u32) -> Rect {
+    Rect::new((dim.width >> level).max(1), (dim.height >> level).max(1))
+}
+
+/// Downsample `source` into every mip level of `texture`
+///
+/// Each level is rendered from the level above it. The
+/// previous level is first copied to a temporary texture,
+/// because the GL backend cannot sample from a single
+/// mip level.
+pub(super) fn generate(
+    target: &Target,
+    source: &TextureView,
+    texture: &wgpu::Texture,
+    format: TextureFormat,
+    dim: Rect,
+    mip_level_count: u32,
+) {
+    let mut pipelines = target.mipmap_pipelines.0.borrow_mut();
+    let (pipeline, layout) = pipelines
+        .entry(format)
+        .or_insert_with(|| pipeline(target, format));
+    let sampler = target.device.create_sampler(&SamplerDescriptor {
+        label: label!(),
+        mag_filter: FilterMode::Linear,
+        min_filter: FilterMode::Linear,
+        ..Default::default()
+    });
+
+    let mut encoder = target
+        .device
+        .create_command_encoder(&CommandEncoderDescriptor { label: label!() });
+
+    let blit = |encoder: &mut CommandEncoder, source: &TextureView, level: u32| {
+        let bind_group = target.device.create_bind_group(&BindGroupDescriptor {
+            label: label!(),
+            layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureView(source),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+        let view = texture.create_view(&TextureViewDescriptor {
+            label: label!(),
+            base_mip_level: level,
+            mip_level_count: NonZeroU32::new(1),
+            ..Default::default()
+        });
+
+        let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: label!(),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Clear(Color::TRANSPARENT),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        pass.set_pipeline(pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    };
+
+    blit(&mut encoder, source, 0);
+    for level in 1..mip_level_count {
+        let above = mip_level_dim(dim, level - 1);
+        let copy = target.device.create_texture(&TextureDescriptor {
+            label: label!(),
+            size: above.into(),
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format,
+            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+        });
+        encoder.copy_texture_to_texture(
+            wgpu::ImageCopyTexture {
+                texture,
+                mip_level: level - 1,
+                origin: Origin3d::ZERO,
+                aspect: TextureAspect::All,
+            },
+            copy.as_image_copy(),
+            above.into(),
+        );
+
+        let view = copy.create_view(&Default::default());
+        blit(&mut encoder, &view, level);
+    }
+
+    target.queue.submit([encoder.finish()]);
+}
+
+fn pipeline(target: &Target, format: TextureFormat) -> (RenderPipeline, BindGroupLayout) {
+    let module = target.device.create_shader_module(ShaderModuleDescriptor {
+        label: label!(),
+        source: ShaderSource::Wgsl(SHADER.into()),
+    });
+    let layout = target
+        .device
+        .create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: label!(),
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+    let pipeline_layout = target
+        .device
+        .create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: label!(),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+
+    let pipeline = target
+        .device
+        .create_render_pipeline(&RenderPipelineDescriptor {
+            label: label!(),
+            layout: Some(&pipeline_layout),
+            vertex: VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: Default::default(),
+            fragment: Some(FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                targets: &[Some(ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+
+    (pipeline, layout)
+}
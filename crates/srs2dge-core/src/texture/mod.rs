@@ -3,19 +3,28 @@ use crate::{
     prelude::{PositionedRect, Rect},
     target::Target,
 };
-use image::{DynamicImage, GrayImage, RgbaImage};
-use std::{mem, num::NonZeroU32, ops::Deref};
+use half::f16;
+use image::{DynamicImage, GrayAlphaImage, GrayImage, ImageBuffer, Luma, Rgba32FImage, RgbaImage};
+use std::{borrow::Cow, num::NonZeroU32, ops::Deref};
 use tokio::sync::oneshot::channel;
 use wgpu::{
     util::DeviceExt, BufferDescriptor, BufferUsages, CommandEncoderDescriptor, Extent3d,
     ImageCopyBuffer, ImageCopyTexture, ImageDataLayout, MapMode, Origin3d, TextureAspect,
-    TextureDescriptor, TextureDimension, TextureFormat, TextureUsages, TextureView,
+    TextureDescriptor, TextureDimension, TextureFormat, TextureFormatFeatureFlags,
+    TextureSampleType, TextureUsages, TextureView,
 };
 
 //
 
+pub mod array;
+mod depth;
+mod layered;
+pub mod mipmap;
 pub mod pos;
 pub mod prelude;
+pub mod sampler;
+pub mod stream;
+pub mod volume;
 
 //
 
@@ -23,11 +32,38 @@ const DEFAULT_USAGE: u32 = TextureUsages::TEXTURE_BINDING.bits();
 
 //
 
+/// Single channel float image
+///
+/// For `R32Float` and `Depth32Float` textures.
+pub type Luma32FImage = ImageBuffer<Luma<f32>, Vec<f32>>;
+
+//
+
 pub const fn has_render_attachment(usage: u32) -> bool {
     TextureUsages::from_bits_truncate(usage).contains(TextureUsages::RENDER_ATTACHMENT)
     // usage & TextureUsages::RENDER_ATTACHMENT.bits() != 0
 }
 
+pub const fn has_depth(format: TextureFormat) -> bool {
+    matches!(
+        format,
+        TextureFormat::Depth32Float
+            | TextureFormat::Depth32FloatStencil8
+            | TextureFormat::Depth24Plus
+            | TextureFormat::Depth24PlusStencil8
+            | TextureFormat::Depth24UnormStencil8
+    )
+}
+
+pub const fn has_stencil(format: TextureFormat) -> bool {
+    matches!(
+        format,
+        TextureFormat::Depth32FloatStencil8
+            | TextureFormat::Depth24PlusStencil8
+            | TextureFormat::Depth24UnormStencil8
+    )
+}
+
 //
 
 #[derive(Debug)]
@@ -36,6 +72,8 @@ pub struct Texture<const USAGE: u32 = DEFAULT_USAGE> {
     format: TextureFormat,
     view: TextureView,
     dim: Rect,
+    sample_count: u32,
+    mip_level_count: u32,
 }
 
 //
@@ -48,15 +86,24 @@ pub type RenderTargetTexture = Texture<
     },
 >;
 
+pub type DepthTexture =
+    Texture<{ TextureUsages::TEXTURE_BINDING.bits() | TextureUsages::RENDER_ATTACHMENT.bits() }>;
+
+/// Texture that can only be rendered to
+///
+/// Use this for multisampled textures,
+/// the GL backend can only multisample these.
+pub type AttachmentTexture = Texture<{ TextureUsages::RENDER_ATTACHMENT.bits() }>;
+
 //
 
 impl<const USAGE: u32> Texture<USAGE> {
     pub fn new(target: &Target, format: TextureFormat, dim: Rect) -> Self {
-        Self::new_inner(target, format, dim, None)
+        Self::new_inner(target, format, dim, 1, None)
     }
 
     pub fn new_rgba(target: &Target, dim: Rect) -> Self {
-        Self::new_inner(target, TextureFormat::Rgba8Unorm, dim, None)
+        Self::new_inner(target, TextureFormat::Rgba8Unorm, dim, 1, None)
     }
 
     pub fn new_rgba_with(target: &Target, data: &RgbaImage) -> Self {
@@ -64,12 +111,13 @@ impl<const USAGE: u32> Texture<USAGE> {
             target,
             TextureFormat::Rgba8Unorm,
             Rect::from(data.dimensions()),
+            1,
             Some(data.as_raw()),
         )
     }
 
     pub fn new_grey(target: &Target, dim: Rect) -> Self {
-        Self::new_inner(target, TextureFormat::R8Unorm, dim, None)
+        Self::new_inner(target, TextureFormat::R8Unorm, dim, 1, None)
     }
 
     pub fn new_grey_with(target: &Target, data: &GrayImage) -> Self {
@@ -77,12 +125,90 @@ impl<const USAGE: u32> Texture<USAGE> {
             target,
             TextureFormat::R8Unorm,
             Rect::from(data.dimensions()),
+            1,
             Some(data.as_raw()),
         )
     }
 
     pub fn new_format(target: &Target, dim: Rect, format: TextureFormat) -> Self {
-        Self::new_inner(target, format, dim, None)
+        Self::new_inner(target, format, dim, 1, None)
+    }
+
+    /// Multisampled texture for MSAA render passes
+    ///
+    /// Multisampled textures are resolved into
+    /// single sampled ones with
+    /// `Frame::secondary_render_pass_multisampled`.
+    /// See [`AttachmentTexture`].
+    pub fn new_multisampled(
+        target: &Target,
+        dim: Rect,
+        format: TextureFormat,
+        sample_count: u32,
+    ) -> Self {
+        Self::new_inner(target, format, dim, sample_count, None)
+    }
+
+    /// Replace this texture with one that
+    /// has every mip level down to 1x1
+    ///
+    /// The levels are downsampled from the current
+    /// contents with a linear filter, later writes
+    /// only go to the first level. The format has
+    /// to be renderable and filterable.
+    pub fn with_mipmaps(self, target: &Target) -> Self {
+        if !TextureUsages::from_bits_truncate(USAGE).contains(TextureUsages::TEXTURE_BINDING) {
+            panic!("Generating mipmaps requires the TEXTURE_BINDING usage");
+        }
+        if self.sample_count != 1 {
+            panic!("Multisampled textures cannot have mipmaps");
+        }
+        let features = self.format.describe().guaranteed_format_features;
+        if !features
+            .allowed_usages
+            .contains(TextureUsages::RENDER_ATTACHMENT)
+            || !features
+                .flags
+                .contains(TextureFormatFeatureFlags::FILTERABLE)
+        {
+            panic!(
+                "Cannot generate mipmaps for {:?}, it is not renderable and filterable",
+                self.format
+            );
+        }
+
+        let mip_level_count = mipmap::mip_level_count(self.dim);
+        let texture = target.device.create_texture(&TextureDescriptor {
+            label: label!(),
+            size: self.dim.into(),
+            mip_level_count,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: self.format,
+            usage: TextureUsages::from_bits_truncate(USAGE)
+                | TextureUsages::RENDER_ATTACHMENT
+                | TextureUsages::COPY_SRC,
+        });
+        mipmap::generate(
+            target,
+            &self.view,
+            &texture,
+            self.format,
+            self.dim,
+            mip_level_count,
+        );
+        let view = texture.create_view(&Default::default());
+
+        Self {
+            texture,
+            view,
+            mip_level_count,
+            ..self
+        }
+    }
+
+    pub fn inner(&self) -> &wgpu::Texture {
+        &self.texture
     }
 
     pub fn get_dim(&self) -> Rect {
@@ -93,6 +219,33 @@ impl<const USAGE: u32> Texture<USAGE> {
         self.format
     }
 
+    pub fn get_sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    pub fn get_mip_level_count(&self) -> u32 {
+        self.mip_level_count
+    }
+
+    /// Sample type for bind group layouts
+    ///
+    /// Float formats are filterable
+    /// when the format allows it.
+    pub fn sample_type(&self) -> TextureSampleType {
+        self.format.describe().sample_type
+    }
+
+    /// Write `image` to `spot` of the first mip level
+    ///
+    /// The image has to match the format:
+    ///  - `Rgba8` and `Bgra8`: `RgbaImage`
+    ///  - `R8Unorm`: `GrayImage`
+    ///  - `Rg8Unorm`: `GrayAlphaImage`
+    ///  - `Rgba16Float` and `Rgba32Float`: `Rgba32FImage`
+    ///  - `R32Float`: the red channel of an `Rgb32FImage` or an `Rgba32FImage`
+    ///
+    /// Use [`Self::write_luma32f`] for raw `R32Float`
+    /// data. Depth textures cannot be written to.
     pub fn write(
         &self,
         target: &Target,
@@ -103,44 +256,150 @@ impl<const USAGE: u32> Texture<USAGE> {
             return Err("Image dimensions do not match the spot dimension");
         }
 
-        if spot.x + spot.width > self.dim.width || spot.y + spot.height > self.dim.height {
-            return Err("Spot out of the texture's bounds");
+        let bytes = encode(self.format, &image)?;
+        write_bytes(
+            target,
+            &self.texture,
+            self.format,
+            self.dim,
+            0,
+            spot,
+            &bytes,
+        )
+    }
+
+    /// Write single channel float data to
+    /// an `R32Float` texture without conversions
+    pub fn write_luma32f(
+        &self,
+        target: &Target,
+        spot: PositionedRect,
+        image: &Luma32FImage,
+    ) -> Result<(), &'static str> {
+        if spot.width != image.width() || spot.height != image.height() {
+            return Err("Image dimensions do not match the spot dimension");
+        }
+        if self.format != TextureFormat::R32Float {
+            return Err("Image format doesn't match with the texture format");
+        }
+
+        write_bytes(
+            target,
+            &self.texture,
+            self.format,
+            self.dim,
+            0,
+            spot,
+            bytemuck::cast_slice(image.as_raw()),
+        )
+    }
+
+    /// Read the first mip level back
+    ///
+    /// Returns the image types [`Self::write`] takes.
+    /// `R32Float` and `Depth32Float` are converted to a
+    /// grey `Rgb32FImage`, use [`Self::read_luma32f`]
+    /// for the raw values.
+    ///
+    /// Compressed, integer and stencil
+    /// formats cannot be read back.
+    pub async fn read(&self, target: &Target) -> Result<DynamicImage, &'static str> {
+        let (width, height) = (self.dim.width, self.dim.height);
+        let format = self.format;
+        if !matches!(
+            format,
+            TextureFormat::Rgba8Unorm
+                | TextureFormat::Rgba8UnormSrgb
+                | TextureFormat::Bgra8Unorm
+                | TextureFormat::Bgra8UnormSrgb
+                | TextureFormat::R8Unorm
+                | TextureFormat::Rg8Unorm
+                | TextureFormat::Rgba16Float
+                | TextureFormat::Rgba32Float
+                | TextureFormat::R32Float
+                | TextureFormat::Depth32Float
+        ) {
+            return Err("Reading this texture format is not supported");
         }
+        let bytes = self.read_bytes(target).await?;
 
-        const INVALID_FORMAT: &str = "Image format doesn't match with the texture format";
-        let image = match self.format {
+        Ok(match format {
             TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => {
-                image.as_rgba8().ok_or(INVALID_FORMAT)?.as_raw()
+                RgbaImage::from_raw(width, height, bytes).unwrap().into()
             }
-            TextureFormat::R8Unorm => image.as_luma8().ok_or(INVALID_FORMAT)?.as_raw(),
-            _ => unimplemented!(),
-        };
+            TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => {
+                RgbaImage::from_raw(width, height, swap_red_blue(bytes))
+                    .unwrap()
+                    .into()
+            }
+            TextureFormat::R8Unorm => GrayImage::from_raw(width, height, bytes).unwrap().into(),
+            TextureFormat::Rg8Unorm => GrayAlphaImage::from_raw(width, height, bytes)
+                .unwrap()
+                .into(),
+            TextureFormat::Rgba16Float => {
+                let data = bytes
+                    .chunks_exact(2)
+                    .map(|c| f16::from_le_bytes([c[0], c[1]]).to_f32())
+                    .collect();
+                Rgba32FImage::from_raw(width, height, data).unwrap().into()
+            }
+            TextureFormat::Rgba32Float => {
+                Rgba32FImage::from_raw(width, height, bytemuck::pod_collect_to_vec(&bytes))
+                    .unwrap()
+                    .into()
+            }
+            TextureFormat::R32Float | TextureFormat::Depth32Float => {
+                Luma32FImage::from_raw(width, height, bytemuck::pod_collect_to_vec(&bytes))
+                    .unwrap()
+                    .into()
+            }
+            _ => unreachable!(),
+        })
+    }
 
-        target.queue.write_texture(
-            ImageCopyTexture {
-                texture: &self.texture,
-                mip_level: 0,
-                origin: Origin3d {
-                    x: spot.x,
-                    y: spot.y,
-                    z: 0,
-                },
-                aspect: TextureAspect::All,
-            },
-            image,
-            ImageDataLayout {
-                offset: 0,
-                bytes_per_row: Some(NonZeroU32::new(spot.width).unwrap()),
-                rows_per_image: Some(NonZeroU32::new(spot.height).unwrap()),
-            },
-            spot.rect().into(),
-        );
+    /// Read an `R32Float` or `Depth32Float`
+    /// texture back without conversions
+    pub async fn read_luma32f(&self, target: &Target) -> Result<Luma32FImage, &'static str> {
+        if !matches!(
+            self.format,
+            TextureFormat::R32Float | TextureFormat::Depth32Float
+        ) {
+            return Err("Texture format is not a single channel float format");
+        }
 
-        Ok(())
+        let bytes = self.read_bytes(target).await?;
+        Ok(Luma32FImage::from_raw(
+            self.dim.width,
+            self.dim.height,
+            bytemuck::pod_collect_to_vec(&bytes),
+        )
+        .unwrap())
     }
 
-    pub async fn read(&self, target: &Target) -> DynamicImage {
-        let dim = BufferDimensions::new(self.dim.width as _, self.dim.height as _, self.format);
+    /// Tightly packed bytes of the first mip level
+    async fn read_bytes(&self, target: &Target) -> Result<Vec<u8>, &'static str> {
+        let depth;
+        let (texture, format) = match self.format {
+            TextureFormat::Depth32Float => {
+                if !TextureUsages::from_bits_truncate(USAGE)
+                    .contains(TextureUsages::TEXTURE_BINDING)
+                {
+                    return Err("Reading depth textures requires the TEXTURE_BINDING usage");
+                }
+                depth = depth::to_r32float(target, &self.view, self.dim);
+                (&depth, TextureFormat::R32Float)
+            }
+            format if has_depth(format) => {
+                return Err("Only Depth32Float depth textures can be read back")
+            }
+            format => {
+                if !TextureUsages::from_bits_truncate(USAGE).contains(TextureUsages::COPY_SRC) {
+                    return Err("Reading textures requires the COPY_SRC usage");
+                }
+                (&self.texture, format)
+            }
+        };
+        let dim = BufferDimensions::new(self.dim.width as _, self.dim.height as _, format);
 
         // cache these buffers
         let read_buffer = target.device.create_buffer(&BufferDescriptor {
@@ -155,7 +414,7 @@ impl<const USAGE: u32> Texture<USAGE> {
             .create_command_encoder(&CommandEncoderDescriptor { label: label!() });
 
         encoder.copy_texture_to_buffer(
-            self.texture.as_image_copy(),
+            texture.as_image_copy(),
             ImageCopyBuffer {
                 buffer: &read_buffer,
                 layout: ImageDataLayout {
@@ -176,7 +435,9 @@ impl<const USAGE: u32> Texture<USAGE> {
         let range = read_buffer.slice(..);
         let (tx, rx) = channel();
         range.map_async(MapMode::Read, |o| tx.send(o).unwrap());
-        rx.await.unwrap().unwrap();
+        rx.await
+            .unwrap()
+            .map_err(|_| "Failed to map the texture read buffer")?;
 
         let bytes = range
             .get_mapped_range()
@@ -184,29 +445,21 @@ impl<const USAGE: u32> Texture<USAGE> {
             .flat_map(|s| &s[..dim.unpadded_bytes_per_row])
             .copied()
             .collect();
-
-        match self.format {
-            TextureFormat::Rgba8Unorm
-            | TextureFormat::Rgba8UnormSrgb
-            | TextureFormat::Bgra8Unorm
-            | TextureFormat::Bgra8UnormSrgb => {
-                RgbaImage::from_raw(dim.width as _, dim.height as _, bytes)
-                    .unwrap()
-                    .into()
-            }
-            TextureFormat::R8Unorm => GrayImage::from_raw(dim.width as _, dim.height as _, bytes)
-                .unwrap()
-                .into(),
-            _ => unimplemented!(),
-        }
+        Ok(bytes)
     }
 
-    fn new_inner(target: &Target, format: TextureFormat, dim: Rect, data: Option<&[u8]>) -> Self {
+    fn new_inner(
+        target: &Target,
+        format: TextureFormat,
+        dim: Rect,
+        sample_count: u32,
+        data: Option<&[u8]>,
+    ) -> Self {
         let desc = TextureDescriptor {
             label: label!(),
             size: dim.into(),
             mip_level_count: 1,
-            sample_count: 1,
+            sample_count,
             dimension: TextureDimension::D2,
             format,
             usage: TextureUsages::from_bits_truncate(USAGE),
@@ -224,6 +477,8 @@ impl<const USAGE: u32> Texture<USAGE> {
             format,
             view,
             dim,
+            sample_count,
+            mip_level_count: 1,
         }
     }
 }
@@ -239,7 +494,6 @@ impl<const USAGE: u32> Deref for Texture<USAGE> {
 //
 
 struct BufferDimensions {
-    width: usize,
     height: usize,
     unpadded_bytes_per_row: usize,
     padded_bytes_per_row: usize,
@@ -247,24 +501,305 @@ struct BufferDimensions {
 
 impl BufferDimensions {
     fn new(width: usize, height: usize, format: TextureFormat) -> Self {
-        let pixel_size = match format {
-            TextureFormat::Rgba8Unorm
-            | TextureFormat::Rgba8UnormSrgb
-            | TextureFormat::Bgra8Unorm
-            | TextureFormat::Bgra8UnormSrgb => mem::size_of::<f32>(),
-            TextureFormat::R8Unorm => mem::size_of::<u8>(),
-            _ => unimplemented!(),
-        };
+        let pixel_size = pixel_size(format);
 
         let unpadded_bytes_per_row = width * pixel_size;
         let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as usize;
         let padded_bytes_per_row_padding = (align - unpadded_bytes_per_row % align) % align;
         let padded_bytes_per_row = unpadded_bytes_per_row + padded_bytes_per_row_padding;
         Self {
-            width,
             height,
             unpadded_bytes_per_row,
             padded_bytes_per_row,
         }
     }
 }
+
+/// Bytes of `image` in the layout of `format`
+fn encode(format: TextureFormat, image: &DynamicImage) -> Result<Cow<'_, [u8]>, &'static str> {
+    const INVALID_FORMAT: &str = "Image format doesn't match with the texture format";
+    let bytes: Cow<[u8]> = match format {
+        TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => {
+            image.as_rgba8().ok_or(INVALID_FORMAT)?.as_raw().into()
+        }
+        TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => {
+            swap_red_blue(image.as_rgba8().ok_or(INVALID_FORMAT)?.to_vec()).into()
+        }
+        TextureFormat::R8Unorm => image.as_luma8().ok_or(INVALID_FORMAT)?.as_raw().into(),
+        TextureFormat::Rg8Unorm => image
+            .as_luma_alpha8()
+            .ok_or(INVALID_FORMAT)?
+            .as_raw()
+            .into(),
+        TextureFormat::Rgba16Float => image
+            .as_rgba32f()
+            .ok_or(INVALID_FORMAT)?
+            .iter()
+            .flat_map(|&c| f16::from_f32(c).to_le_bytes())
+            .collect::<Vec<u8>>()
+            .into(),
+        TextureFormat::Rgba32Float => {
+            bytemuck::cast_slice(image.as_rgba32f().ok_or(INVALID_FORMAT)?.as_raw()).into()
+        }
+        // only the red channel is used
+        TextureFormat::R32Float => match image {
+            DynamicImage::ImageRgb32F(image) => image
+                .pixels()
+                .flat_map(|p| p.0[0].to_le_bytes())
+                .collect::<Vec<u8>>(),
+            DynamicImage::ImageRgba32F(image) => image
+                .pixels()
+                .flat_map(|p| p.0[0].to_le_bytes())
+                .collect::<Vec<u8>>(),
+            _ => return Err(INVALID_FORMAT),
+        }
+        .into(),
+        format if has_depth(format) => return Err("Depth textures cannot be written to"),
+        _ => return Err("Writing to this texture format is not supported"),
+    };
+
+    Ok(bytes)
+}
+
+/// Write `bytes` to `spot` of the array `layer`
+/// or 3D slice of the first mip level
+fn write_bytes(
+    target: &Target,
+    texture: &wgpu::Texture,
+    format: TextureFormat,
+    dim: Rect,
+    layer: u32,
+    spot: PositionedRect,
+    bytes: &[u8],
+) -> Result<(), &'static str> {
+    if spot.x + spot.width > dim.width || spot.y + spot.height > dim.height {
+        return Err("Spot out of the texture's bounds");
+    }
+
+    let pixel_size = pixel_size(format) as u32;
+    target.queue.write_texture(
+        ImageCopyTexture {
+            texture,
+            mip_level: 0,
+            origin: Origin3d {
+                x: spot.x,
+                y: spot.y,
+                z: layer,
+            },
+            aspect: TextureAspect::All,
+        },
+        bytes,
+        ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(NonZeroU32::new(spot.width * pixel_size).unwrap()),
+            rows_per_image: Some(NonZeroU32::new(spot.height).unwrap()),
+        },
+        spot.rect().into(),
+    );
+
+    Ok(())
+}
+
+/// Bytes per pixel in buffer copies
+fn pixel_size(format: TextureFormat) -> usize {
+    let desc = format.describe();
+    if desc.block_dimensions != (1, 1) {
+        panic!("Compressed texture format {format:?} is not supported");
+    }
+    desc.block_size as usize
+}
+
+/// RGBA <-> BGRA
+fn swap_red_blue(mut bytes: Vec<u8>) -> Vec<u8> {
+    for pixel in bytes.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+    }
+    bytes
+}
+
+//
+
+#[cfg(all(test, feature = "testing"))]
+mod test {
+    use super::{stream::StreamedTexture, Luma32FImage, Texture};
+    use crate::{label, packer::rect::Rect, Engine};
+    use image::{
+        DynamicImage, GrayAlphaImage, ImageOutputFormat, LumaA, Rgba, Rgba32FImage, RgbaImage,
+    };
+    use rapid_qoi::{Colors, Qoi};
+    use std::io::Cursor;
+    use wgpu::{
+        CommandEncoderDescriptor, LoadOp, Operations, RenderPassDepthStencilAttachment,
+        RenderPassDescriptor, TextureFormat, TextureUsages,
+    };
+
+    type ReadWriteTexture = Texture<
+        {
+            TextureUsages::TEXTURE_BINDING.bits()
+                | TextureUsages::COPY_SRC.bits()
+                | TextureUsages::COPY_DST.bits()
+                | TextureUsages::RENDER_ATTACHMENT.bits()
+        },
+    >;
+
+    #[test]
+    fn read_write_formats() {
+        let engine = Engine::new();
+        let target = pollster::block_on(engine.new_target_offscreen(Rect::new(4, 4)));
+        let dim = Rect::new(3, 2);
+        let round_trip = |format, image: DynamicImage| {
+            let texture = ReadWriteTexture::new_format(&target, dim, format);
+            texture
+                .write(&target, dim.positioned(0, 0), image.clone())
+                .unwrap();
+            assert_eq!(
+                pollster::block_on(texture.read(&target)).unwrap(),
+                image,
+                "{format:?}"
+            );
+        };
+
+        let rgba = RgbaImage::from_fn(3, 2, |x, y| Rgba([x as u8 * 80, y as u8 * 80, 7, 255]));
+        round_trip(TextureFormat::Rgba8Unorm, rgba.clone().into());
+        round_trip(TextureFormat::Bgra8UnormSrgb, rgba.into());
+        round_trip(
+            TextureFormat::Rg8Unorm,
+            GrayAlphaImage::from_fn(3, 2, |x, y| LumaA([x as u8, y as u8])).into(),
+        );
+        // exactly representable as f16
+        let hdr =
+            Rgba32FImage::from_fn(3, 2, |x, y| Rgba([x as f32 * 4.5, -(y as f32), 0.25, 1.0]));
+        round_trip(TextureFormat::Rgba16Float, hdr.clone().into());
+        round_trip(TextureFormat::Rgba32Float, hdr.into());
+
+        let luma = Luma32FImage::from_fn(3, 2, |x, y| [x as f32 * 100.5 + y as f32].into());
+        let texture = ReadWriteTexture::new_format(&target, dim, TextureFormat::R32Float);
+        texture
+            .write_luma32f(&target, dim.positioned(0, 0), &luma)
+            .unwrap();
+        assert_eq!(
+            pollster::block_on(texture.read_luma32f(&target)).unwrap(),
+            luma
+        );
+        let grey = pollster::block_on(texture.read(&target)).unwrap();
+        texture.write(&target, dim.positioned(0, 0), grey).unwrap();
+        assert_eq!(
+            pollster::block_on(texture.read_luma32f(&target)).unwrap(),
+            luma
+        );
+        // the red channel of rgba images
+        let red = Rgba32FImage::from_fn(3, 2, |x, y| {
+            Rgba([luma.get_pixel(x, y).0[0], 1.0, 2.0, 3.0])
+        });
+        texture
+            .write(&target, dim.positioned(0, 0), red.into())
+            .unwrap();
+        assert_eq!(
+            pollster::block_on(texture.read_luma32f(&target)).unwrap(),
+            luma
+        );
+        assert!(texture
+            .write(&target, dim.positioned(0, 0), RgbaImage::new(3, 2).into())
+            .is_err());
+    }
+
+    #[test]
+    fn read_unsupported() {
+        let engine = Engine::new();
+        let target = pollster::block_on(engine.new_target_offscreen(Rect::new(4, 4)));
+        let dim = Rect::new(3, 2);
+
+        let texture = ReadWriteTexture::new_format(&target, dim, TextureFormat::Rgba8Uint);
+        assert!(pollster::block_on(texture.read(&target)).is_err());
+        let texture = ReadWriteTexture::new_format(&target, dim, TextureFormat::Rgba8Unorm);
+        assert!(pollster::block_on(texture.read_luma32f(&target)).is_err());
+        let texture = Texture::<{ TextureUsages::TEXTURE_BINDING.bits() }>::new_format(
+            &target,
+            dim,
+            TextureFormat::Rgba8Unorm,
+        );
+        assert!(pollster::block_on(texture.read(&target)).is_err());
+    }
+
+    #[test]
+    fn read_depth() {
+        let engine = Engine::new();
+        let target = pollster::block_on(engine.new_target_offscreen(Rect::new(4, 4)));
+        let texture =
+            ReadWriteTexture::new_format(&target, Rect::new(4, 4), TextureFormat::Depth32Float);
+        assert!(texture
+            .write(
+                &target,
+                Rect::new(4, 4).positioned(0, 0),
+                Luma32FImage::new(4, 4).into()
+            )
+            .is_err());
+
+        let mut encoder = target
+            .device
+            .create_command_encoder(&CommandEncoderDescriptor { label: label!() });
+        encoder.begin_render_pass(&RenderPassDescriptor {
+            label: label!(),
+            color_attachments: &[],
+            depth_stencil_attachment: Some(RenderPassDepthStencilAttachment {
+                view: &texture,
+                depth_ops: Some(Operations {
+                    load: LoadOp::Clear(0.25),
+                    store: true,
+                }),
+                stencil_ops: None,
+            }),
+        });
+        target.queue.submit([encoder.finish()]);
+
+        let depth = pollster::block_on(texture.read_luma32f(&target)).unwrap();
+        assert!(depth.pixels().all(|p| p.0 == [0.25]));
+    }
+
+    #[test]
+    fn streamed() {
+        let engine = Engine::new();
+        let mut target = pollster::block_on(engine.new_target_offscreen(Rect::new(4, 4)));
+        let image = RgbaImage::from_fn(5, 3, |x, y| Rgba([x as u8 * 50, y as u8 * 100, 9, 255]));
+
+        let mut png = vec![];
+        DynamicImage::from(image.clone())
+            .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+            .unwrap();
+        let rgb: Vec<u8> = image
+            .pixels()
+            .flat_map(|p| [p.0[0], p.0[1], p.0[2]])
+            .collect();
+        let qoi = Qoi {
+            width: 5,
+            height: 3,
+            colors: Colors::Rgb,
+        }
+        .encode_alloc(&rgb)
+        .unwrap();
+
+        for bytes in [png, qoi] {
+            let mut texture = StreamedTexture::<
+                {
+                    TextureUsages::TEXTURE_BINDING.bits()
+                        | TextureUsages::COPY_SRC.bits()
+                        | TextureUsages::COPY_DST.bits()
+                },
+            >::new(&target, bytes)
+            .unwrap();
+            assert_eq!(texture.get_dim(), Rect::new(5, 3));
+
+            let mut frame = target.get_frame();
+            assert!(texture.wait(&target, &mut frame));
+            target.finish_frame(frame);
+            assert!(texture.is_loaded());
+            let read = pollster::block_on(texture.read(&target)).unwrap();
+            assert_eq!(read.as_rgba8(), Some(&image));
+        }
+
+        assert!(
+            StreamedTexture::<{ super::stream::STREAMED_USAGE }>::new(&target, vec![1, 2, 3])
+                .is_err()
+        );
+    }
+}
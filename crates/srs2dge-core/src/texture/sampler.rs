@@ -0,0 +1,173 @@
+use crate::{label, target::Target};
+use std::{num::NonZeroU8, ops::Deref};
+use wgpu::{AddressMode, CompareFunction, FilterMode, SamplerBindingType, SamplerDescriptor};
+
+//
+
+/// Texture sampler
+///
+/// Derefs to a [`wgpu::Sampler`] for bind groups.
+#[derive(Debug)]
+pub struct Sampler {
+    sampler: wgpu::Sampler,
+    binding_type: SamplerBindingType,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SamplerBuilder {
+    address_mode: [AddressMode; 3],
+    mag_filter: FilterMode,
+    min_filter: FilterMode,
+    mipmap_filter: FilterMode,
+    anisotropy: Option<u8>,
+    lod_clamp: (f32, f32),
+    compare: Option<CompareFunction>,
+}
+
+//
+
+impl Sampler {
+    pub fn builder() -> SamplerBuilder {
+        SamplerBuilder::default()
+    }
+
+    /// Pixelated sampler clamped to the edges
+    pub fn nearest(target: &Target) -> Self {
+        Self::builder().build(target)
+    }
+
+    /// Smooth sampler clamped to the edges
+    ///
+    /// Also blends between mip levels.
+    pub fn linear(target: &Target) -> Self {
+        Self::builder()
+            .with_filter(FilterMode::Linear, FilterMode::Linear)
+            .with_mipmap_filter(FilterMode::Linear)
+            .build(target)
+    }
+
+    pub fn inner(&self) -> &wgpu::Sampler {
+        &self.sampler
+    }
+
+    /// Binding type this sampler needs in a bind group layout
+    pub fn binding_type(&self) -> SamplerBindingType {
+        self.binding_type
+    }
+}
+
+impl Deref for Sampler {
+    type Target = wgpu::Sampler;
+
+    fn deref(&self) -> &Self::Target {
+        &self.sampler
+    }
+}
+
+impl Default for SamplerBuilder {
+    fn default() -> Self {
+        Self {
+            address_mode: [AddressMode::ClampToEdge; 3],
+            mag_filter: FilterMode::Nearest,
+            min_filter: FilterMode::Nearest,
+            mipmap_filter: FilterMode::Nearest,
+            anisotropy: None,
+            lod_clamp: (0.0, f32::MAX),
+            compare: None,
+        }
+    }
+}
+
+impl SamplerBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Same address mode for `u`, `v` and `w`
+    pub fn with_address_mode(mut self, address_mode: AddressMode) -> Self {
+        self.address_mode = [address_mode; 3];
+        self
+    }
+
+    pub fn with_address_modes(mut self, u: AddressMode, v: AddressMode, w: AddressMode) -> Self {
+        self.address_mode = [u, v, w];
+        self
+    }
+
+    pub fn with_filter(mut self, mag_filter: FilterMode, min_filter: FilterMode) -> Self {
+        self.mag_filter = mag_filter;
+        self.min_filter = min_filter;
+        self
+    }
+
+    /// Filter between mip levels
+    pub fn with_mipmap_filter(mut self, mipmap_filter: FilterMode) -> Self {
+        self.mipmap_filter = mipmap_filter;
+        self
+    }
+
+    /// Anisotropic filtering with `1`, `2`, `4`, `8` or `16` samples
+    ///
+    /// Requires every filter to be linear.
+    pub fn with_anisotropy(mut self, anisotropy: u8) -> Self {
+        if !matches!(anisotropy, 1 | 2 | 4 | 8 | 16) {
+            panic!("Anisotropy has to be 1, 2, 4, 8 or 16, got {anisotropy}");
+        }
+        self.anisotropy = Some(anisotropy);
+        self
+    }
+
+    /// Limit the mip levels that are sampled
+    pub fn with_lod_clamp(mut self, min: f32, max: f32) -> Self {
+        self.lod_clamp = (min, max);
+        self
+    }
+
+    /// Comparison sampler for depth textures
+    pub fn with_compare(mut self, compare: Option<CompareFunction>) -> Self {
+        self.compare = compare;
+        self
+    }
+
+    pub fn build(self, target: &Target) -> Sampler {
+        let [address_mode_u, address_mode_v, address_mode_w] = self.address_mode;
+        let linear = [self.mag_filter, self.min_filter, self.mipmap_filter]
+            .iter()
+            .all(|filter| *filter == FilterMode::Linear);
+        if self.anisotropy.is_some_and(|a| a > 1) && !linear {
+            panic!("Anisotropic filtering requires linear mag, min and mipmap filters");
+        }
+
+        let sampler = target.device.create_sampler(&SamplerDescriptor {
+            label: label!(),
+            address_mode_u,
+            address_mode_v,
+            address_mode_w,
+            mag_filter: self.mag_filter,
+            min_filter: self.min_filter,
+            mipmap_filter: self.mipmap_filter,
+            lod_min_clamp: self.lod_clamp.0,
+            lod_max_clamp: self.lod_clamp.1,
+            compare: self.compare,
+            anisotropy_clamp: self.anisotropy.and_then(NonZeroU8::new),
+            border_color: None,
+        });
+
+        Sampler {
+            sampler,
+            binding_type: self.binding_type(),
+        }
+    }
+
+    fn binding_type(&self) -> SamplerBindingType {
+        if self.compare.is_some() {
+            SamplerBindingType::Comparison
+        } else if [self.mag_filter, self.min_filter, self.mipmap_filter]
+            .contains(&FilterMode::Linear)
+        {
+            SamplerBindingType::Filtering
+        } else {
+            SamplerBindingType::NonFiltering
+        }
+    }
+}
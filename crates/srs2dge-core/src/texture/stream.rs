@@ -0,0 +1,353 @@
+use super::{BufferDimensions, Texture};
+use crate::{frame::Frame, label, prelude::Rect, target::Target};
+use image::{io::Reader, RgbaImage};
+use rapid_qoi::Qoi;
+use std::{
+    io::Cursor,
+    num::{NonZeroU32, NonZeroU64},
+    ops::Deref,
+};
+use tokio::sync::oneshot::{channel, error::TryRecvError, Receiver, Sender};
+use wgpu::{
+    BufferDescriptor, BufferUsages, ImageCopyBuffer, ImageDataLayout, TextureFormat, TextureUsages,
+};
+
+//
+
+pub(super) const STREAMED_USAGE: u32 =
+    TextureUsages::TEXTURE_BINDING.bits() | TextureUsages::COPY_DST.bits();
+
+//
+
+/// Texture that is decoded in the background
+///
+/// The texture is created right away at the final
+/// size and stays transparent until the image is
+/// decoded. The pixels are then uploaded through the
+/// staging belt of a [`Frame`] in [`Self::poll`], so
+/// bind groups created with the placeholder stay valid.
+///
+/// PNG, QOI and the other formats `image` can guess
+/// are decoded on the rayon thread pool. On wasm the
+/// browser decodes them with `createImageBitmap`, QOI
+/// images and images with translucent pixels are
+/// decoded on the main thread.
+#[derive(Debug)]
+pub struct StreamedTexture<const USAGE: u32 = STREAMED_USAGE> {
+    texture: Texture<USAGE>,
+    pending: Option<Receiver<Result<RgbaImage, String>>>,
+    error: Option<String>,
+}
+
+//
+
+impl<const USAGE: u32> StreamedTexture<USAGE> {
+    /// Start decoding `bytes`
+    ///
+    /// Only the header is read here.
+    pub fn new(target: &Target, bytes: Vec<u8>) -> Result<Self, String> {
+        if !TextureUsages::from_bits_truncate(USAGE).contains(TextureUsages::COPY_DST) {
+            return Err("Streamed textures require the COPY_DST usage".to_string());
+        }
+
+        let dim = header(&bytes)?;
+        let texture = Texture::new_inner(target, TextureFormat::Rgba8Unorm, dim, 1, None);
+
+        let (tx, rx) = channel();
+        spawn_decode(bytes, tx);
+
+        Ok(Self {
+            texture,
+            pending: Some(rx),
+            error: None,
+        })
+    }
+
+    /// Upload the image if it was decoded
+    ///
+    /// Returns `true` once the real data is in.
+    pub fn poll(&mut self, target: &Target, frame: &mut Frame) -> bool {
+        let pending = match self.pending.as_mut() {
+            Some(pending) => pending,
+            None => return self.error.is_none(),
+        };
+
+        let result = match pending.try_recv() {
+            Err(TryRecvError::Empty) => return false,
+            Err(TryRecvError::Closed) => Err("Decoder thread panicked".to_string()),
+            Ok(result) => result,
+        };
+        self.finish(target, frame, result)
+    }
+
+    /// Block until the image is decoded and upload it
+    ///
+    /// Returns `true` if the real data is in.
+    /// Must not be called from an async context.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn wait(&mut self, target: &Target, frame: &mut Frame) -> bool {
+        let pending = match self.pending.take() {
+            Some(pending) => pending,
+            None => return self.error.is_none(),
+        };
+
+        let result = pending
+            .blocking_recv()
+            .unwrap_or_else(|_| Err("Decoder thread panicked".to_string()));
+        self.finish(target, frame, result)
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        self.pending.is_none() && self.error.is_none()
+    }
+
+    /// Why decoding failed, the placeholder is kept
+    pub fn get_error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    pub fn into_inner(self) -> Texture<USAGE> {
+        self.texture
+    }
+
+    fn finish(
+        &mut self,
+        target: &Target,
+        frame: &mut Frame,
+        result: Result<RgbaImage, String>,
+    ) -> bool {
+        self.pending = None;
+
+        let result = result.and_then(|image| {
+            if Rect::from(image.dimensions()) == self.texture.get_dim() {
+                Ok(image)
+            } else {
+                Err("Decoded image size does not match the header".to_string())
+            }
+        });
+
+        match result {
+            Ok(image) => {
+                self.upload(target, frame, &image);
+                true
+            }
+            Err(err) => {
+                log::error!("Texture decoding failed: {err}");
+                self.error = Some(err);
+                false
+            }
+        }
+    }
+
+    fn upload(&self, target: &Target, frame: &mut Frame, image: &RgbaImage) {
+        let (width, height) = image.dimensions();
+        let dim = BufferDimensions::new(width as _, height as _, TextureFormat::Rgba8Unorm);
+        let size = (dim.padded_bytes_per_row * dim.height) as u64;
+
+        // texture copies need padded rows
+        let staging = target.device.create_buffer(&BufferDescriptor {
+            label: label!(),
+            size,
+            usage: BufferUsages::COPY_SRC | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        {
+            let mut map =
+                frame.write_buffer(&staging, 0, NonZeroU64::new(size).unwrap(), &target.device);
+            for (to, from) in map
+                .chunks_mut(dim.padded_bytes_per_row)
+                .zip(image.as_raw().chunks(dim.unpadded_bytes_per_row))
+            {
+                to[..from.len()].copy_from_slice(from);
+            }
+        }
+
+        frame.encoder().copy_buffer_to_texture(
+            ImageCopyBuffer {
+                buffer: &staging,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(dim.padded_bytes_per_row as _),
+                    rows_per_image: None,
+                },
+            },
+            self.texture.inner().as_image_copy(),
+            self.texture.get_dim().into(),
+        );
+    }
+}
+
+impl<const USAGE: u32> Deref for StreamedTexture<USAGE> {
+    type Target = Texture<USAGE>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.texture
+    }
+}
+
+//
+
+fn is_qoi(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"qoif")
+}
+
+/// Image size without decoding the pixels
+fn header(bytes: &[u8]) -> Result<Rect, String> {
+    let (width, height) = if is_qoi(bytes) {
+        let qoi = Qoi::decode_header(bytes).map_err(|err| err.to_string())?;
+        (qoi.width, qoi.height)
+    } else {
+        Reader::new(Cursor::new(bytes))
+            .with_guessed_format()
+            .map_err(|err| err.to_string())?
+            .into_dimensions()
+            .map_err(|err| err.to_string())?
+    };
+
+    if width == 0 || height == 0 {
+        return Err("Image is empty".to_string());
+    }
+    Ok(Rect::new(width, height))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn spawn_decode(bytes: Vec<u8>, tx: Sender<Result<RgbaImage, String>>) {
+    rayon::spawn(move || {
+        // rayon aborts on panics, dropping `tx`
+        // reports them as a decoder error instead
+        let result = std::panic::catch_unwind(|| decode(&bytes));
+        if let Ok(result) = result {
+            // the texture might have been dropped already
+            _ = tx.send(result);
+        }
+    });
+}
+
+// web workers need a shared memory build, so the
+// browser decodes the image off the main thread
+#[cfg(target_arch = "wasm32")]
+fn spawn_decode(bytes: Vec<u8>, tx: Sender<Result<RgbaImage, String>>) {
+    wasm_bindgen_futures::spawn_local(async move {
+        let result = if is_qoi(&bytes) {
+            decode(&bytes)
+        } else {
+            decode_browser(&bytes).await
+        };
+        _ = tx.send(result);
+    });
+}
+
+fn decode(bytes: &[u8]) -> Result<RgbaImage, String> {
+    if !is_qoi(bytes) {
+        return image::load_from_memory(bytes)
+            .map(|image| image.into_rgba8())
+            .map_err(|err| err.to_string());
+    }
+
+    decode_qoi(bytes)
+}
+
+/// Decodes a QOI image, RGB images get an opaque alpha channel
+pub(crate) fn decode_qoi(bytes: &[u8]) -> Result<RgbaImage, String> {
+    let (qoi, pixels) = Qoi::decode_alloc(bytes).map_err(|err| err.to_string())?;
+    let pixels = if qoi.colors.has_alpha() {
+        pixels
+    } else {
+        pixels
+            .chunks_exact(3)
+            .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+            .collect()
+    };
+
+    Ok(RgbaImage::from_raw(qoi.width, qoi.height, pixels).unwrap())
+}
+
+/// Let the browser decode the image with
+/// `createImageBitmap` and read the pixels
+/// back through a 2d canvas
+///
+/// The canvas stores premultiplied colors, so
+/// images with translucent pixels are decoded
+/// again with `image` to keep their colors.
+#[cfg(target_arch = "wasm32")]
+async fn decode_browser(bytes: &[u8]) -> Result<RgbaImage, String> {
+    use wasm_bindgen::{JsCast, JsValue};
+    use wasm_bindgen_futures::JsFuture;
+    use web_sys::{Blob, CanvasRenderingContext2d, HtmlCanvasElement, ImageBitmap};
+
+    let js_err = |err: JsValue| format!("{err:?}");
+    let window = web_sys::window().ok_or("No window to decode the image with")?;
+
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+    let blob = Blob::new_with_u8_array_sequence(&parts).map_err(js_err)?;
+    let promise = window
+        .create_image_bitmap_with_blob(&blob)
+        .map_err(js_err)?;
+    let bitmap: ImageBitmap = JsFuture::from(promise)
+        .await
+        .map_err(js_err)?
+        .unchecked_into();
+
+    let (width, height) = (bitmap.width(), bitmap.height());
+    let canvas: HtmlCanvasElement = window
+        .document()
+        .ok_or("No document to decode the image with")?
+        .create_element("canvas")
+        .map_err(js_err)?
+        .unchecked_into();
+    canvas.set_width(width);
+    canvas.set_height(height);
+    let context: CanvasRenderingContext2d = canvas
+        .get_context("2d")
+        .map_err(js_err)?
+        .ok_or("No 2d context to read the image with")?
+        .unchecked_into();
+    context
+        .draw_image_with_image_bitmap(&bitmap, 0.0, 0.0)
+        .map_err(js_err)?;
+    bitmap.close();
+    let data = context
+        .get_image_data(0.0, 0.0, width as _, height as _)
+        .map_err(js_err)?
+        .data()
+        .0;
+
+    if data.chunks_exact(4).any(|p| p[3] != 0 && p[3] != 255) {
+        return decode(bytes);
+    }
+    RgbaImage::from_raw(width, height, data)
+        .ok_or_else(|| "Canvas returned too few pixels".to_string())
+}
+
+//
+
+#[cfg(test)]
+mod test {
+    use super::decode;
+    use rapid_qoi::{Colors, Qoi};
+
+    #[test]
+    fn qoi_rgb() {
+        let (width, height) = (100, 150);
+        let rgba: Vec<u8> = (0..width * height)
+            .flat_map(|i| [(i % 7) as u8 * 30, (i / 100) as u8, 9, 255])
+            .collect();
+        let rgb: Vec<u8> = rgba
+            .chunks_exact(4)
+            .flat_map(|p| [p[0], p[1], p[2]])
+            .collect();
+
+        // rgb images are expanded with an alpha of 255
+        let [rgba, rgb] = [(Colors::Rgba, rgba), (Colors::Rgb, rgb)].map(|(colors, pixels)| {
+            let bytes = Qoi {
+                width,
+                height,
+                colors,
+            }
+            .encode_alloc(&pixels)
+            .unwrap();
+            decode(&bytes).unwrap()
+        });
+        assert_eq!(rgb, rgba);
+    }
+}
@@ -0,0 +1,200 @@
+use super::{layered::LayeredTexture, DEFAULT_USAGE};
+use crate::{
+    prelude::{PositionedRect, Rect},
+    target::Target,
+};
+use image::{DynamicImage, RgbaImage};
+use std::ops::Deref;
+use wgpu::{TextureFormat, TextureSampleType, TextureView, TextureViewDimension};
+
+//
+
+/// 3D texture made of same sized slices
+///
+/// Derefs to a `texture_3d` view. Unlike a
+/// [`super::array::TextureArray`], samples are
+/// filtered between the slices too.
+#[derive(Debug)]
+pub struct Texture3D<const USAGE: u32 = DEFAULT_USAGE> {
+    inner: LayeredTexture<USAGE>,
+}
+
+//
+
+impl<const USAGE: u32> Texture3D<USAGE> {
+    pub fn new(target: &Target, format: TextureFormat, dim: Rect, depth: u32) -> Self {
+        Self {
+            inner: LayeredTexture::new(target, format, dim, depth, TextureViewDimension::D3, None),
+        }
+    }
+
+    /// Every image becomes one slice
+    ///
+    /// All images have to be the same size.
+    pub fn new_rgba_with(target: &Target, slices: &[RgbaImage]) -> Self {
+        Self {
+            inner: LayeredTexture::new_rgba_with(target, slices, TextureViewDimension::D3),
+        }
+    }
+
+    pub fn inner(&self) -> &wgpu::Texture {
+        &self.inner.texture
+    }
+
+    /// Size of a single slice
+    pub fn get_dim(&self) -> Rect {
+        self.inner.dim
+    }
+
+    pub fn get_format(&self) -> TextureFormat {
+        self.inner.format
+    }
+
+    pub fn get_depth(&self) -> u32 {
+        self.inner.layers
+    }
+
+    /// Sample type for bind group layouts
+    pub fn sample_type(&self) -> TextureSampleType {
+        self.inner.sample_type()
+    }
+
+    /// Write `image` to `spot` of the slice `z`
+    ///
+    /// Takes the same images as `Texture::write`.
+    pub fn write_slice(
+        &self,
+        target: &Target,
+        z: u32,
+        spot: PositionedRect,
+        image: DynamicImage,
+    ) -> Result<(), &'static str> {
+        self.inner.write(target, z, spot, image)
+    }
+}
+
+impl<const USAGE: u32> Deref for Texture3D<USAGE> {
+    type Target = TextureView;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner.view
+    }
+}
+
+//
+
+#[cfg(all(test, feature = "testing"))]
+mod test {
+    use super::Texture3D;
+    use crate::{
+        buffer::{DefaultVertex, IndexBuffer, VertexBuffer},
+        color::Color,
+        label,
+        packer::rect::PositionedRect,
+        shader::{module::ShaderModule, Shader},
+        testing::{fixture::quad, GoldenImage},
+        texture::sampler::Sampler,
+    };
+    use image::{Rgba, RgbaImage};
+    use wgpu::{
+        BindGroupDescriptor, BindGroupEntry, BindingResource, PrimitiveTopology, TextureUsages,
+    };
+
+    const VOLUME_SHADER: &str = r#"
+struct VertexInput {
+	@location(0) pos: vec2<f32>,
+	@location(1) uv: vec2<f32>,
+	@location(2) col: vec4<f32>,
+};
+
+struct FragmentInput {
+	@builtin(position) pos: vec4<f32>,
+	@location(0) uv: vec2<f32>,
+};
+
+@group(0)
+@binding(0)
+var t_volume: texture_3d<f32>;
+
+@group(0)
+@binding(1)
+var s_volume: sampler;
+
+@vertex
+fn vs_main(vin: VertexInput) -> FragmentInput {
+	var fin: FragmentInput;
+	fin.pos = vec4<f32>(vin.pos * 1.6, 0.0, 1.0);
+	fin.uv = vin.pos + 0.5;
+	return fin;
+}
+
+@fragment
+fn fs_main(fin: FragmentInput) -> @location(0) vec4<f32> {
+	// left half from slice 0, right half from slice 1
+	let w = select(0.25, 0.75, fin.uv.x >= 0.5);
+	return textureSample(t_volume, s_volume, vec3<f32>(fin.uv, w));
+}
+"#;
+
+    #[test]
+    fn texture_3d() {
+        // same slices as the texture array
+        GoldenImage::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/golden/texture_array.png"
+        ))
+        .check_blocking(|target, frame| {
+            let module = ShaderModule::new_wgsl_source(target, VOLUME_SHADER.into()).unwrap();
+            let shader: Shader<DefaultVertex, u32> = Shader::builder()
+                .with_vertex(&module, "vs_main")
+                .with_fragment(&module, "fs_main")
+                .with_topology(PrimitiveTopology::TriangleStrip)
+                .with_format(target.get_format())
+                .build(target);
+
+            let slice = |col: [u8; 4]| RgbaImage::from_pixel(4, 4, Rgba(col));
+            let texture: Texture3D<
+                { TextureUsages::TEXTURE_BINDING.bits() | TextureUsages::COPY_DST.bits() },
+            > = Texture3D::new_rgba_with(
+                target,
+                &[slice([255, 0, 0, 255]), slice([0, 255, 0, 255])],
+            );
+            assert_eq!(texture.get_depth(), 2);
+            texture
+                .write_slice(
+                    target,
+                    1,
+                    PositionedRect::new(2, 2, 2, 2),
+                    RgbaImage::from_pixel(2, 2, Rgba([0, 0, 255, 255])).into(),
+                )
+                .unwrap();
+
+            let sampler = Sampler::nearest(target);
+            let bind_group = target.get_device().create_bind_group(&BindGroupDescriptor {
+                label: label!(),
+                layout: &shader.bind_group_layout(0),
+                entries: &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: BindingResource::TextureView(&texture),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: BindingResource::Sampler(&sampler),
+                    },
+                ],
+            });
+
+            let vbo = VertexBuffer::new_with(target, &quad(0.0, 0.0, Color::WHITE));
+            let ibo = IndexBuffer::new_with(target, &[0, 1, 2, 3]);
+            frame
+                .primary_render_pass()
+                .bind_vbo(&vbo)
+                .bind_ibo(&ibo)
+                .bind_shader(&shader)
+                .bind_group(&bind_group)
+                .draw_indexed(0..4, 0, 0..1);
+        })
+        .unwrap();
+    }
+}
@@ -0,0 +1,312 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use std::path::PathBuf;
+use syn::{
+    parse_macro_input, spanned::Spanned, Attribute, Data, DeriveInput, Error, Fields, Ident, Lit,
+    LitStr, Meta, NestedMeta, Path, Result,
+};
+
+//
+
+mod wgsl;
+
+//
+
+/// Derives `Vertex` for a `#[repr(C)]` struct
+///
+/// Offsets and the stride come from the struct
+/// itself, formats from the `VertexField` of each
+/// field and shader locations count up in field order.
+///
+/// Struct attributes:
+///  - `#[vertex(instance)]`: per instance step mode
+///  - `#[vertex(location = 3)]`: first shader location
+///  - `#[vertex(wgsl = "shader.wgsl", input = "VertexInput")]`:
+///    fail to compile if the layout doesn't match the
+///    struct `input` of the shader, the path is relative
+///    to `CARGO_MANIFEST_DIR` and `input` defaults to
+///    `VertexInput`. The shader is preprocessed with
+///    includes relative to it and no defines.
+///  - `#[vertex(crate = "srs2dge_core")]`: path to the
+///    engine, defaults to `srs2dge`
+///
+/// Field attributes:
+///  - `#[vertex(location = 5)]`: shader location of this
+///    and the following fields
+///  - `#[vertex(format = "Unorm8x4")]`: any `VertexFormat`
+///  - `#[vertex(skip)]`: padding that isn't an attribute
+#[proc_macro_derive(Vertex, attributes(vertex))]
+pub fn derive_vertex(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    vertex(input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+//
+
+struct Options {
+    krate: Path,
+    instance: bool,
+    location: u32,
+    wgsl: Option<LitStr>,
+    input: Option<LitStr>,
+}
+
+#[derive(Default)]
+struct FieldOptions {
+    location: Option<u32>,
+    format: Option<Ident>,
+    skip: bool,
+}
+
+//
+
+fn vertex(input: DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(Error::new(
+            input.generics.span(),
+            "Vertex cannot be derived for generic structs",
+        ));
+    }
+    if !is_repr_c(&input.attrs) {
+        return Err(Error::new(
+            name.span(),
+            "Vertex requires `#[repr(C)]` for stable field offsets",
+        ));
+    }
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new(
+                    name.span(),
+                    "Vertex can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new(
+                name.span(),
+                "Vertex can only be derived for structs",
+            ))
+        }
+    };
+
+    let options = options(&input.attrs)?;
+    let krate = &options.krate;
+    let format_path = quote! { #krate::wgpu::VertexFormat };
+
+    // (field, location, format)
+    let mut location = options.location;
+    let mut attributes = vec![];
+    for field in fields {
+        let field_options = field_options(&field.attrs)?;
+        if field_options.skip {
+            continue;
+        }
+        location = field_options.location.unwrap_or(location);
+
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let format = match &field_options.format {
+            Some(format) => quote! { #format_path::#format },
+            None => quote! { <#ty as #krate::buffer::VertexField>::FORMAT },
+        };
+        attributes.push((ident, location, format));
+        location += 1;
+    }
+
+    let step_mode = if options.instance {
+        quote! { #krate::wgpu::VertexStepMode::Instance }
+    } else {
+        quote! { #krate::wgpu::VertexStepMode::Vertex }
+    };
+    let layout_attributes = attributes.iter().map(|(ident, location, format)| {
+        quote! {
+            #krate::wgpu::VertexAttribute {
+                format: #format,
+                offset: ::core::mem::offset_of!(#name, #ident) as u64,
+                shader_location: #location,
+            }
+        }
+    });
+
+    let checks = match &options.wgsl {
+        Some(wgsl) => check(&options, wgsl, &attributes)?,
+        None => quote! {},
+    };
+
+    Ok(quote! {
+        impl #krate::buffer::Vertex for #name {
+            const LAYOUT: &'static [#krate::wgpu::VertexBufferLayout<'static>] =
+                &[#krate::wgpu::VertexBufferLayout {
+                    array_stride: ::core::mem::size_of::<#name>() as u64,
+                    step_mode: #step_mode,
+                    attributes: &[#(#layout_attributes),*],
+                }];
+        }
+
+        #checks
+    })
+}
+
+/// Compare the attributes to the WGSL struct
+///
+/// Locations are compared here, formats in a const
+/// assertion because only the compiler knows them.
+fn check(
+    options: &Options,
+    wgsl: &LitStr,
+    attributes: &[(&Ident, u32, TokenStream2)],
+) -> Result<TokenStream2> {
+    let krate = &options.krate;
+    let input = options
+        .input
+        .as_ref()
+        .map(LitStr::value)
+        .unwrap_or_else(|| "VertexInput".to_string());
+    let dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let path = PathBuf::from(dir).join(wgsl.value());
+
+    let (inputs, files) =
+        wgsl::find_struct(&path, &input).map_err(|err| Error::new(wgsl.span(), err))?;
+    let shown = wgsl.value();
+
+    let mut checks = vec![];
+    for (ident, location, format) in attributes {
+        let shader = inputs
+            .iter()
+            .find(|shader| shader.location == *location)
+            .ok_or_else(|| {
+                Error::new(
+                    ident.span(),
+                    format!("`{input}` in {shown} has no @location({location}) for `{ident}`"),
+                )
+            })?;
+
+        let (kind, components) = shader.numeric.ok_or_else(|| {
+            Error::new(
+                wgsl.span(),
+                format!(
+                    "`{}: {}` of `{input}` in {shown} cannot be a vertex input",
+                    shader.name, shader.ty
+                ),
+            )
+        })?;
+        let message = format!(
+            "`{ident}` does not match `@location({location}) {}: {}` of `{input}` in {shown}",
+            shader.name, shader.ty
+        );
+        checks.push(quote! {
+            assert!(
+                #krate::buffer::vertex_format_matches(#format, #kind, #components),
+                #message
+            );
+        });
+    }
+    if let Some(missing) = inputs
+        .iter()
+        .find(|shader| !attributes.iter().any(|(_, l, _)| *l == shader.location))
+    {
+        return Err(Error::new(
+            wgsl.span(),
+            format!(
+                "No field for `@location({}) {}` of `{input}` in {shown}",
+                missing.location, missing.name
+            ),
+        ));
+    }
+
+    // rebuild when the shader changes
+    let files = files.iter().map(|file| file.to_string_lossy().into_owned());
+    Ok(quote! {
+        const _: () = {
+            #(let _ = include_str!(#files);)*
+            #(#checks)*
+        };
+    })
+}
+
+fn vertex_metas(attrs: &[Attribute]) -> Result<Vec<NestedMeta>> {
+    let mut metas = vec![];
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("vertex")) {
+        match attr.parse_meta()? {
+            Meta::List(list) => metas.extend(list.nested),
+            meta => return Err(Error::new(meta.span(), "Expected `#[vertex(..)]`")),
+        }
+    }
+    Ok(metas)
+}
+
+fn options(attrs: &[Attribute]) -> Result<Options> {
+    let mut options = Options {
+        krate: syn::parse_quote! { ::srs2dge },
+        instance: false,
+        location: 0,
+        wgsl: None,
+        input: None,
+    };
+
+    for meta in vertex_metas(attrs)? {
+        match &meta {
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("instance") => {
+                options.instance = true
+            }
+            NestedMeta::Meta(Meta::NameValue(nv)) => match (&nv.lit, nv.path.get_ident()) {
+                (Lit::Int(lit), Some(ident)) if ident == "location" => {
+                    options.location = lit.base10_parse()?
+                }
+                (Lit::Str(lit), Some(ident)) if ident == "crate" => options.krate = lit.parse()?,
+                (Lit::Str(lit), Some(ident)) if ident == "wgsl" => options.wgsl = Some(lit.clone()),
+                (Lit::Str(lit), Some(ident)) if ident == "input" => {
+                    options.input = Some(lit.clone())
+                }
+                _ => return Err(Error::new(meta.span(), "Unknown vertex option")),
+            },
+            _ => return Err(Error::new(meta.span(), "Unknown vertex option")),
+        }
+    }
+    if let (Some(input), None) = (&options.input, &options.wgsl) {
+        return Err(Error::new(input.span(), "`input` requires `wgsl`"));
+    }
+
+    Ok(options)
+}
+
+fn field_options(attrs: &[Attribute]) -> Result<FieldOptions> {
+    let mut options = FieldOptions::default();
+
+    for meta in vertex_metas(attrs)? {
+        match &meta {
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("skip") => options.skip = true,
+            NestedMeta::Meta(Meta::NameValue(nv)) => match (&nv.lit, nv.path.get_ident()) {
+                (Lit::Int(lit), Some(ident)) if ident == "location" => {
+                    options.location = Some(lit.base10_parse()?)
+                }
+                (Lit::Str(lit), Some(ident)) if ident == "format" => {
+                    options.format = Some(lit.parse()?)
+                }
+                _ => return Err(Error::new(meta.span(), "Unknown vertex field option")),
+            },
+            _ => return Err(Error::new(meta.span(), "Unknown vertex field option")),
+        }
+    }
+
+    Ok(options)
+}
+
+fn is_repr_c(attrs: &[Attribute]) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("repr"))
+        .filter_map(|attr| attr.parse_meta().ok())
+        .any(|meta| match meta {
+            Meta::List(list) => list.nested.iter().any(
+                |nested| matches!(nested, NestedMeta::Meta(Meta::Path(path)) if path.is_ident("C")),
+            ),
+            _ => false,
+        })
+}
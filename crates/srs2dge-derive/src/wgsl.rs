@@ -0,0 +1,141 @@
+//! Shader input structs from WGSL files
+//!
+//! The file goes through the engine's preprocessor, with
+//! `#include "file"` relative to the shader, and naga.
+
+use naga::{Binding, ScalarKind, Type, TypeInner};
+use srs2dge_preprocess::Preprocessor;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+//
+
+pub struct ShaderInput {
+    pub location: u32,
+    pub name: String,
+    pub ty: String,
+    /// `('f' | 'i' | 'u', components)` if
+    /// the type can be a vertex input
+    pub numeric: Option<(char, u32)>,
+}
+
+//
+
+/// Fields of `struct name` with a `@location`
+/// and every file that was read
+pub fn find_struct(path: &Path, name: &str) -> Result<(Vec<ShaderInput>, Vec<PathBuf>), String> {
+    let source = fs::read_to_string(path)
+        .map_err(|err| format!("Could not read {}: {err}", path.display()))?;
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+
+    let preprocessed = Preprocessor::new()
+        .with_include_dir(dir)
+        .process(&path.display().to_string(), &source)?;
+    let module = preprocessed.parse()?;
+    let files = std::iter::once(path.to_path_buf())
+        .chain(preprocessed.dependencies().iter().cloned())
+        .collect();
+
+    let members = module
+        .types
+        .iter()
+        .find_map(|(_, ty)| match &ty.inner {
+            TypeInner::Struct { members, .. } if ty.name.as_deref() == Some(name) => Some(members),
+            _ => None,
+        })
+        .ok_or_else(|| format!("No `struct {name}` in {}", path.display()))?;
+
+    let inputs = members
+        .iter()
+        .filter_map(|member| match member.binding {
+            Some(Binding::Location { location, .. }) => {
+                let (ty, numeric) = describe(&module.types[member.ty]);
+                Some(ShaderInput {
+                    location,
+                    name: member.name.clone().unwrap_or_default(),
+                    ty,
+                    numeric,
+                })
+            }
+            _ => None,
+        })
+        .collect();
+
+    Ok((inputs, files))
+}
+
+//
+
+/// WGSL name and numeric kind of a type
+fn describe(ty: &Type) -> (String, Option<(char, u32)>) {
+    let scalar = |kind: ScalarKind, width: u8| match (kind, width) {
+        (ScalarKind::Float, 4) => Some(("f32", 'f')),
+        (ScalarKind::Sint, 4) => Some(("i32", 'i')),
+        (ScalarKind::Uint, 4) => Some(("u32", 'u')),
+        _ => None,
+    };
+
+    match ty.inner {
+        TypeInner::Scalar { kind, width } => match scalar(kind, width) {
+            Some((name, kind)) => (name.to_string(), Some((kind, 1))),
+            None => (format!("{kind:?}{width}"), None),
+        },
+        TypeInner::Vector { size, kind, width } => match scalar(kind, width) {
+            Some((name, kind)) => (
+                format!("vec{}<{name}>", size as u32),
+                Some((kind, size as u32)),
+            ),
+            None => (format!("vec{}<{kind:?}{width}>", size as u32), None),
+        },
+        ref other => (
+            ty.name.clone().unwrap_or_else(|| format!("{other:?}")),
+            None,
+        ),
+    }
+}
+
+//
+
+#[cfg(test)]
+mod test {
+    use super::find_struct;
+    use std::fs;
+
+    #[test]
+    fn preprocessed_struct() {
+        let dir = std::env::temp_dir().join("srs2dge-derive-wgsl");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("input.wgsl"),
+            "// struct VertexInput { @location(9) x: f32, };\nstruct VertexInput {\n#ifdef WIDE\n\t@location(0) pos: vec4<f32>,\n#else\n\t@location(0) pos: vec2<f32>, // }\n#endif\n\t/* @location(7) */ @location(1) @interpolate(flat) id: u32,\n\t@builtin(vertex_index) index: u32,\n};",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("main.wgsl"),
+            "#include \"input.wgsl\"\n\n@vertex\nfn vs_main(vin: VertexInput) -> @builtin(position) vec4<f32> {\n\treturn vec4<f32>(vin.pos, 0.0, 1.0);\n}",
+        )
+        .unwrap();
+
+        let (inputs, files) = find_struct(&dir.join("main.wgsl"), "VertexInput").unwrap();
+        assert_eq!(
+            inputs
+                .iter()
+                .map(|input| (
+                    input.location,
+                    input.name.as_str(),
+                    input.ty.as_str(),
+                    input.numeric
+                ))
+                .collect::<Vec<_>>(),
+            [
+                (0, "pos", "vec2<f32>", Some(('f', 2))),
+                (1, "id", "u32", Some(('u', 1)))
+            ]
+        );
+        assert_eq!(files, [dir.join("main.wgsl"), dir.join("input.wgsl")]);
+
+        assert!(find_struct(&dir.join("main.wgsl"), "Missing").is_err());
+    }
+}
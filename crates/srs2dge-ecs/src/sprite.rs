@@ -8,11 +8,19 @@ use srs2dge_core::{
 
 //
 
-#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Sprite {
     pub sprite: TexturePosition,
     pub color: Color,
 
+    /// point of the sprite placed at the translation,
+    /// also the point it rotates around
+    ///
+    /// `(0, 0)` is the bottom left corner
+    /// and `(1, 1)` is the top right corner.
+    #[serde(default = "default_anchor")]
+    pub anchor: Vec2,
+
     pub idx: Option<Idx>,
 
     #[serde(skip)]
@@ -24,6 +32,18 @@ pub struct SpritePlugin;
 
 //
 
+impl Default for Sprite {
+    fn default() -> Self {
+        Self {
+            sprite: Default::default(),
+            color: Default::default(),
+            anchor: default_anchor(),
+            idx: None,
+            lerp_transform: Default::default(),
+        }
+    }
+}
+
 impl Plugin for SpritePlugin {
     fn build(&self, world: &mut World) {
         world.updates.insert_internal(200, set_pos_static_system);
@@ -72,41 +92,43 @@ fn set_pos_body(
 #[filter(maybe_changed::<Sprite>())]
 fn set_sprite(sprite: &mut Sprite, #[resource] batcher: &mut BatchRenderer) {
     let Transform2D {
-        translation, scale, ..
+        translation,
+        rotation,
+        scale,
     } = sprite.lerp_transform;
 
+    let new = QuadMesh::new_top_left(
+        translation - scale * sprite.anchor,
+        scale,
+        sprite.color,
+        sprite.sprite,
+    )
+    .with_rotation(rotation, sprite.anchor);
+
     // println!("set sprite");
     if let Some(idx) = sprite.idx {
         let mesh = batcher.get(idx).unwrap();
-        if (mesh.pos - translation + scale * 0.5)
-            .abs()
-            .cmpgt(Vec2::splat(f32::EPSILON))
-            .any()
-            || (mesh.size - scale)
-                .abs()
-                .cmpgt(Vec2::splat(f32::EPSILON))
-                .any()
-            || (mesh.col.to_vec4() - sprite.color.to_vec4())
-                .abs()
-                .cmpgt(Vec4::splat(f32::EPSILON))
-                .any()
-            || (mesh.tex.to_vec4() - sprite.sprite.to_vec4())
-                .abs()
-                .cmpgt(Vec4::splat(f32::EPSILON))
-                .any()
-        {
-            let mesh = batcher.get_mut(idx).unwrap();
-            mesh.pos = translation - scale * 0.5;
-            mesh.size = scale;
-            mesh.col = sprite.color;
-            mesh.tex = sprite.sprite;
+        if changed(mesh, &new) {
+            *batcher.get_mut(idx).unwrap() = new;
         }
     } else {
-        sprite.idx = Some(batcher.push_with(QuadMesh::new_top_left(
-            translation - scale * 0.5,
-            scale,
-            sprite.color,
-            sprite.sprite,
-        )));
+        sprite.idx = Some(batcher.push_with(new));
     }
 }
+
+//
+
+fn default_anchor() -> Vec2 {
+    Vec2::splat(0.5)
+}
+
+fn changed(a: &QuadMesh, b: &QuadMesh) -> bool {
+    let ne2 = |a: Vec2, b: Vec2| (a - b).abs().cmpgt(Vec2::splat(f32::EPSILON)).any();
+    let ne4 = |a: Vec4, b: Vec4| (a - b).abs().cmpgt(Vec4::splat(f32::EPSILON)).any();
+    ne2(a.pos, b.pos)
+        || ne2(a.size, b.size)
+        || ne2(a.pivot, b.pivot)
+        || (a.rotation - b.rotation).abs() > f32::EPSILON
+        || ne4(a.col.to_vec4(), b.col.to_vec4())
+        || ne4(a.tex.to_vec4(), b.tex.to_vec4())
+}
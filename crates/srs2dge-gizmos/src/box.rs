@@ -32,7 +32,8 @@ impl GizmosBox {
 }
 
 impl Mesh<DefaultVertex> for GizmosBox {
-    const PRIM: PrimitiveTopology = PrimitiveTopology::LineStrip;
+    const PRIM: PrimitiveTopology = PrimitiveTopology::LineList;
+    const FIXED_VERTICES: Option<u32> = Some(4);
 
     // TODO : #![feature(type_alias_impl_trait)]
     type VertexIter = GizmosBoxVertexIter;
@@ -97,14 +98,11 @@ impl Iterator for GizmosBoxIndexIter {
         let i = self.i;
         self.i += 1;
 
-        if i == 4 {
-            Some(self.offset)
-        } else if i == 5 {
-            Some(!0)
-        } else if i >= 6 {
+        // 0 1, 1 2, 2 3, 3 0
+        if i >= 8 {
             None
         } else {
-            Some(i + self.offset)
+            Some((i + 1) / 2 % 4 + self.offset)
         }
     }
 }
@@ -134,14 +132,14 @@ pub(super) struct GizmosBoxes {
 
 impl GizmosBoxes {
     pub fn new(target: &Target, ubo: &UniformBuffer<Mat4>) -> Self {
-        let shader = LineShader::new(target, true);
+        let shader = LineShader::new(target, false);
         let bind_group = shader.bind_group(ubo);
 
         Self {
             boxes: vec![],
 
             vbo: VertexBuffer::new(target, 4),
-            ibo: IndexBuffer::new(target, 6),
+            ibo: IndexBuffer::new(target, 8),
             ibo_len: 0,
             shader,
             bind_group,
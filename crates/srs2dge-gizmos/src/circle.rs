@@ -36,7 +36,8 @@ impl GizmosCircle {
 }
 
 impl Mesh<DefaultVertex> for GizmosCircle {
-    const PRIM: PrimitiveTopology = PrimitiveTopology::LineStrip;
+    const PRIM: PrimitiveTopology = PrimitiveTopology::LineList;
+    const FIXED_VERTICES: Option<u32> = Some(RES);
 
     // TODO : #![feature(type_alias_impl_trait)]
     type VertexIter = GizmosCircleVertexIter;
@@ -101,14 +102,12 @@ impl Iterator for GizmosCircleIndexIter {
         let i = self.i;
         self.i += 1;
 
-        if i == RES {
-            Some(self.offset)
-        } else if i == RES + 1 {
-            Some(!0)
-        } else if i >= RES + 2 {
+        // every segment as its own line,
+        // the last one back to the first vertex
+        if i >= RES * 2 {
             None
         } else {
-            Some(i + self.offset)
+            Some((i + 1) / 2 % RES + self.offset)
         }
     }
 }
@@ -129,14 +128,14 @@ pub(super) struct GizmosCircles {
 
 impl GizmosCircles {
     pub fn new(target: &Target, ubo: &UniformBuffer<Mat4>) -> Self {
-        let shader = LineShader::new(target, true);
+        let shader = LineShader::new(target, false);
         let bind_group = shader.bind_group(ubo);
 
         Self {
             circles: vec![],
 
             vbo: VertexBuffer::new(target, RES as usize),
-            ibo: IndexBuffer::new(target, RES as usize + 2),
+            ibo: IndexBuffer::new(target, RES as usize * 2),
             ibo_len: 0,
             shader,
             bind_group,
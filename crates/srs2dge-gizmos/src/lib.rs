@@ -188,3 +188,40 @@ impl Gizmos {
             .draw(self.circles.draw(self.lines.draw(render_pass)))
     }
 }
+
+//
+
+#[cfg(test)]
+mod test {
+    use super::{Gizmos, GizmosBox, GizmosCircle, GizmosLine};
+    use srs2dge_core::{color::Color, glam::Vec2, testing::GoldenImage};
+
+    #[test]
+    fn gizmos() {
+        let golden = GoldenImage::new(concat!(env!("CARGO_MANIFEST_DIR"), "/golden/gizmos.png"));
+        let ws = golden.window_state();
+
+        golden
+            .check_blocking(|target, frame| {
+                let mut gizmos = Gizmos::new(target);
+                gizmos.add_line(GizmosLine::new(
+                    Vec2::new(-0.9, -0.9),
+                    Vec2::new(0.9, 0.9),
+                    Color::RED,
+                ));
+                gizmos.add_circle(GizmosCircle::new(
+                    Vec2::new(0.3, -0.3),
+                    Vec2::new(0.4, 0.4),
+                    Color::GREEN,
+                ));
+                gizmos.add_box(GizmosBox::new(
+                    Vec2::new(-0.4, 0.4),
+                    Vec2::new(0.3, 0.2),
+                    Color::BLUE,
+                ));
+                gizmos.prepare(target, frame, &ws);
+                gizmos.draw(frame.primary_render_pass());
+            })
+            .unwrap();
+    }
+}
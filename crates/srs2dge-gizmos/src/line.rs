@@ -29,6 +29,7 @@ impl GizmosLine {
 
 impl Mesh<DefaultVertex> for GizmosLine {
     const PRIM: PrimitiveTopology = PrimitiveTopology::LineList;
+    const FIXED_VERTICES: Option<u32> = Some(2);
 
     type VertexIter = IntoIter<DefaultVertex, 2>;
     type IndexIter = IntoIter<u32, 2>;
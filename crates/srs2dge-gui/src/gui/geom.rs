@@ -7,7 +7,7 @@ use srs2dge_core::{
 
 //
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum GuiGeom {
     Quad(QuadMesh),
 }
@@ -15,7 +15,7 @@ pub enum GuiGeom {
 //
 
 impl Mesh for GuiGeom {
-    const PRIM: PrimitiveTopology = PrimitiveTopology::TriangleStrip;
+    const PRIM: PrimitiveTopology = PrimitiveTopology::TriangleList;
 
     type VertexIter = IterEnum<<QuadMesh as Mesh>::VertexIter, DefaultVertex>;
     type IndexIter = IterEnum<<QuadMesh as Mesh>::IndexIter, DefaultIndex>;
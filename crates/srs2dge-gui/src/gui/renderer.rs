@@ -1,7 +1,7 @@
 use super::geom::GuiGeom;
 use srs2dge_core::{
     batch::mesh::Mesh,
-    buffer::{IndexBuffer, VertexBuffer},
+    buffer::{DefaultVertex, GrowableIndexBuffer, GrowableVertexBuffer, IndexBuffer, VertexBuffer},
     prelude::Frame,
     target::Target,
 };
@@ -10,10 +10,16 @@ use srs2dge_core::{
 
 #[derive(Debug, Default)]
 pub struct GuiRenderer {
-    pub vbo: Option<VertexBuffer>,
-    pub ibo: Option<IndexBuffer>,
+    vbo: Option<GrowableVertexBuffer>,
+    ibo: Option<GrowableIndexBuffer>,
 
-    pub geometry: Vec<GuiGeom>,
+    geometry: Vec<GuiGeom>,
+
+    // geometry of the last upload and the vertex,
+    // index and index step offsets of each of them
+    uploaded: Vec<(GuiGeom, usize, usize, u32)>,
+    // first pushed geometry that differs from the last upload
+    dirty: Option<usize>,
 }
 
 //
@@ -23,40 +29,82 @@ impl GuiRenderer {
         Self::default()
     }
 
+    /// Marks the renderer dirty if `geometry` differs
+    /// from the geometry uploaded at the same spot
     pub fn push_with(&mut self, geometry: GuiGeom) {
+        let i = self.geometry.len();
+        if self.dirty.is_none() && self.uploaded.get(i).map(|(geom, ..)| geom) != Some(&geometry) {
+            self.dirty = Some(i);
+        }
         self.geometry.push(geometry)
     }
 
+    /// Vertex buffer from the last [`Self::generate`]
+    pub fn vbo(&self) -> Option<&VertexBuffer> {
+        self.vbo.as_deref()
+    }
+
+    /// Index buffer from the last [`Self::generate`]
+    pub fn ibo(&self) -> Option<&IndexBuffer> {
+        self.ibo.as_deref()
+    }
+
+    /// Upload the pushed geometry
+    ///
+    /// Only the geometry after the first one that changed
+    /// since the last upload is generated and uploaded again.
+    /// Nothing is generated if nothing changed.
     pub fn generate(
         &mut self,
         target: &mut Target,
         frame: &mut Frame,
     ) -> (&VertexBuffer, &IndexBuffer, u32) {
-        let mut vertices = vec![];
-        let mut indices = vec![];
-
-        let mut i = 0;
-        for geom in self.geometry.drain(..) {
-            vertices.extend(geom.vertices());
-            indices.extend(geom.indices(i));
-            i += geom.index_step();
-        }
+        let end_step = self.end_step();
+        let vbo = self
+            .vbo
+            .get_or_insert_with(|| GrowableVertexBuffer::new(target));
+        let ibo = self
+            .ibo
+            .get_or_insert_with(|| GrowableIndexBuffer::new(target));
 
-        let vbo_cap = self.vbo.as_ref().map(|vbo| vbo.capacity());
-        let ibo_cap = self.ibo.as_ref().map(|ibo| ibo.capacity());
-        if vbo_cap < Some(vertices.len()) {
-            self.vbo = Some(VertexBuffer::new(target, vertices.len() * 2));
-        }
-        if ibo_cap < Some(indices.len()) {
-            self.ibo = Some(IndexBuffer::new(target, indices.len() * 2));
+        // less geometry than last time
+        if self.geometry.len() < self.uploaded.len() {
+            self.dirty = self.dirty.or(Some(self.geometry.len()));
         }
 
-        let vbo = self.vbo.as_mut().unwrap();
-        let ibo = self.ibo.as_mut().unwrap();
+        if let Some(first) = self.dirty.take() {
+            let (v, i, mut step) = self
+                .uploaded
+                .get(first)
+                .map(|&(_, v, i, step)| (v, i, step))
+                .unwrap_or((vbo.len(), ibo.len(), end_step));
+            self.uploaded.truncate(first);
+
+            let mut vertices: Vec<DefaultVertex> = vec![];
+            let mut indices = vec![];
+            for geom in self.geometry.drain(first..) {
+                self.uploaded
+                    .push((geom, v + vertices.len(), i + indices.len(), step));
+                vertices.extend(geom.vertices());
+                indices.extend(geom.indices(step));
+                step += geom.index_step();
+            }
 
-        vbo.upload(target, frame, &vertices);
-        ibo.upload(target, frame, &indices);
+            vbo.truncate(v);
+            vbo.extend(target, frame, &vertices);
+            ibo.truncate(i);
+            ibo.extend(target, frame, &indices);
+        }
+        self.geometry.clear();
+
+        (vbo, ibo, ibo.len() as _)
+    }
 
-        (vbo, ibo, indices.len() as _)
+    // index step after the last uploaded geometry
+    fn end_step(&self) -> u32 {
+        self.uploaded
+            .last()
+            .map(|(geom, _, _, step)| step + geom.index_step())
+            .unwrap_or(0)
     }
 }
@@ -0,0 +1,372 @@
+//! WGSL preprocessor of srs2dge
+//!
+//! It's a separate crate because it runs at compile time too.
+//! The `srs2dge-derive` proc macro checks vertex layouts against
+//! preprocessed shaders, and the `srs2dge-res` build script
+//! exports the built in shaders as plain WGSL. Neither can depend
+//! on `srs2dge-core`, which re-exports it in `shader::module`.
+
+use naga::{
+    front::wgsl,
+    valid::{Capabilities, ValidationFlags, Validator},
+    Module, SourceLocation,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fs,
+    path::PathBuf,
+};
+
+//
+
+/// WGSL preprocessor
+///
+/// Supported directives, each on its own line:
+///  - `#include "name"`: a file from one of the include
+///    directories or a registered snippet, every name is
+///    included only once
+///  - `#define NAME [value]` and `#undef NAME`:
+///    `NAME` is replaced with `value` in the code
+///  - `#ifdef NAME`, `#ifndef NAME`, `#else` and `#endif`
+#[derive(Debug, Clone, Default)]
+pub struct Preprocessor {
+    snippets: HashMap<String, String>,
+    defines: HashMap<String, String>,
+    include_dirs: Vec<PathBuf>,
+}
+
+/// Preprocessed WGSL with the origin of every line
+#[derive(Debug, Clone, Default)]
+pub struct Preprocessed {
+    source: String,
+    files: Vec<String>,
+    // file index and line of each output line
+    lines: Vec<(usize, u32)>,
+    dependencies: Vec<PathBuf>,
+}
+
+struct State<'p> {
+    preprocessor: &'p Preprocessor,
+    defines: HashMap<String, String>,
+    included: HashSet<String>,
+    output: Preprocessed,
+}
+
+//
+
+impl Preprocessor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Source for `#include "name"`
+    pub fn with_snippet(mut self, name: impl Into<String>, source: impl Into<String>) -> Self {
+        self.snippets.insert(name.into(), source.into());
+        self
+    }
+
+    pub fn with_snippets<'a, I>(self, snippets: I) -> Self
+    where
+        I: IntoIterator<Item = &'a (&'a str, &'a str)>,
+    {
+        snippets
+            .into_iter()
+            .fold(self, |s, (name, source)| s.with_snippet(*name, *source))
+    }
+
+    /// Same as `#define name value` at the top of the source
+    pub fn with_define(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.defines.insert(name.into(), value.into());
+        self
+    }
+
+    /// Same as `#define name`
+    pub fn with_flag(self, name: impl Into<String>) -> Self {
+        self.with_define(name, "")
+    }
+
+    /// Directory to search for includes
+    /// that are not registered snippets
+    pub fn with_include_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.include_dirs.push(dir.into());
+        self
+    }
+
+    /// `name` is used in errors and line mapping
+    pub fn process(&self, name: &str, source: &str) -> Result<Preprocessed, String> {
+        let mut state = State {
+            preprocessor: self,
+            defines: self.defines.clone(),
+            included: HashSet::from([name.to_string()]),
+            output: Preprocessed::default(),
+        };
+        state.file(name, source)?;
+        Ok(state.output)
+    }
+}
+
+impl Preprocessed {
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn into_source(self) -> String {
+        self.source
+    }
+
+    /// File name and line that the 1-based
+    /// output `line` came from
+    pub fn locate(&self, line: u32) -> Option<(&str, u32)> {
+        let (file, line) = *self.lines.get(line.checked_sub(1)? as usize)?;
+        Some((&self.files[file], line))
+    }
+
+    /// Included files that were read from the disk
+    pub fn dependencies(&self) -> &[PathBuf] {
+        &self.dependencies
+    }
+
+    /// Parse with naga
+    ///
+    /// Errors point at the original file and line.
+    pub fn parse(&self) -> Result<Module, String> {
+        wgsl::parse_str(&self.source)
+            .map_err(|err| self.error_at(err.location(&self.source), err.message().to_string()))
+    }
+
+    /// Parse and validate with naga
+    ///
+    /// Errors point at the original file and line.
+    pub fn check(&self) -> Result<(), String> {
+        let module = self.parse()?;
+
+        Validator::new(ValidationFlags::all(), Capabilities::all())
+            .validate(&module)
+            .map_err(|err| {
+                let mut message = err.to_string();
+                let mut source = err.source();
+                while let Some(err) = source {
+                    message = format!("{message}: {err}");
+                    source = err.source();
+                }
+                self.error_at(err.location(&self.source), message)
+            })?;
+
+        Ok(())
+    }
+
+    fn error_at(&self, location: Option<SourceLocation>, message: String) -> String {
+        match location
+            .and_then(|location| Some((self.locate(location.line_number)?, location.line_position)))
+        {
+            Some(((file, line), column)) => format!("{file}:{line}:{column}: {message}"),
+            None => message,
+        }
+    }
+}
+
+impl<'p> State<'p> {
+    fn file(&mut self, name: &str, source: &str) -> Result<(), String> {
+        let file = self.output.files.len();
+        self.output.files.push(name.to_string());
+
+        // (taking this branch, #else seen) of each #ifdef
+        let mut branches: Vec<(bool, bool)> = vec![];
+        for (line, code) in (1..).zip(source.lines()) {
+            let at = |message: &str| format!("{name}:{line}: {message}");
+            let active = branches.iter().all(|(taking, _)| *taking);
+
+            let directive = match code.trim_start().strip_prefix('#') {
+                Some(directive) => directive.trim(),
+                None => {
+                    if active {
+                        self.push_line(code, file, line);
+                    }
+                    continue;
+                }
+            };
+            let (command, arg) = directive
+                .split_once(char::is_whitespace)
+                .map(|(command, arg)| (command, arg.trim()))
+                .unwrap_or((directive, ""));
+
+            match command {
+                "ifdef" | "ifndef" => {
+                    let defined = self.defines.contains_key(identifier(arg).map_err(at)?);
+                    branches.push((defined == (command == "ifdef"), false));
+                }
+                "else" => match branches.last_mut() {
+                    Some((_, true)) => return Err(at("#else after #else")),
+                    Some((taking, seen)) => {
+                        *taking = !*taking;
+                        *seen = true;
+                    }
+                    None => return Err(at("#else without #ifdef")),
+                },
+                "endif" => {
+                    branches.pop().ok_or_else(|| at("#endif without #ifdef"))?;
+                }
+                _ if !active => {}
+                "define" => {
+                    let (define, value) = arg.split_once(char::is_whitespace).unwrap_or((arg, ""));
+                    let define = identifier(define).map_err(at)?;
+                    self.defines
+                        .insert(define.to_string(), value.trim().to_string());
+                }
+                "undef" => {
+                    self.defines.remove(identifier(arg).map_err(at)?);
+                }
+                "include" => {
+                    let include = arg
+                        .strip_prefix('"')
+                        .and_then(|arg| arg.strip_suffix('"'))
+                        .ok_or_else(|| at("Expected #include \"name\""))?;
+                    self.include(include).map_err(|err| at(&err))?;
+                }
+                _ => return Err(at(&format!("Unknown directive #{command}"))),
+            }
+        }
+
+        if !branches.is_empty() {
+            return Err(format!("{name}: Missing #endif"));
+        }
+        Ok(())
+    }
+
+    fn include(&mut self, name: &str) -> Result<(), String> {
+        if !self.included.insert(name.to_string()) {
+            return Ok(());
+        }
+
+        // files first so that edits show up when hot-reloading
+        for dir in self.preprocessor.include_dirs.iter() {
+            let path = dir.join(name);
+            if let Ok(source) = fs::read_to_string(&path) {
+                self.output.dependencies.push(path);
+                return self.file(name, &source);
+            }
+        }
+        if let Some(source) = self.preprocessor.snippets.get(name) {
+            return self.file(name, source);
+        }
+
+        Err(format!("Could not find #include \"{name}\""))
+    }
+
+    fn push_line(&mut self, code: &str, file: usize, line: u32) {
+        let output = &mut self.output;
+        substitute(&mut output.source, code, &self.defines);
+        output.source.push('\n');
+        output.lines.push((file, line));
+    }
+}
+
+//
+
+fn identifier(s: &str) -> Result<&str, &'static str> {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return Err("Expected an identifier"),
+    }
+    if chars.all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        Ok(s)
+    } else {
+        Err("Expected an identifier")
+    }
+}
+
+/// Replace defined identifiers in `code`
+fn substitute(output: &mut String, code: &str, defines: &HashMap<String, String>) {
+    let mut rest = code;
+    while let Some(start) = rest.find(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        let (before, from) = rest.split_at(start);
+        let end = from
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(from.len());
+        let (word, after) = from.split_at(end);
+
+        output.push_str(before);
+        // suffixes of number literals like `1u` or `0x1f` are not identifiers
+        let literal = before.ends_with(|c: char| c.is_ascii_digit());
+        match defines.get(word) {
+            Some(value) if !value.is_empty() && !literal => output.push_str(value),
+            _ => output.push_str(word),
+        }
+        rest = after;
+    }
+    output.push_str(rest);
+}
+
+//
+
+#[cfg(test)]
+mod test {
+    use super::Preprocessor;
+
+    #[test]
+    fn directives() {
+        let preprocessor = Preprocessor::new()
+            .with_snippet("common", "struct A { x: f32, };\n#include \"common\"")
+            .with_flag("OUTLINE")
+            .with_define("COUNT", "4")
+            .with_define("u", "x");
+        let source = "#include \"common\"\n#include \"common\"\n#ifdef OUTLINE\nlet a = COUNT + 1u + u;\n#ifndef OUTLINE\nlet b = 1;\n#else\nlet c = COUNT_2;\n#endif\n#else\nlet d = 2;\n#endif\n#undef COUNT\nlet e = COUNT;";
+        let output = preprocessor.process("main", source).unwrap();
+
+        assert_eq!(
+            output.source(),
+            "struct A { x: f32, };\nlet a = 4 + 1u + x;\nlet c = COUNT_2;\nlet e = COUNT;\n"
+        );
+        assert_eq!(output.locate(1), Some(("common", 1)));
+        assert_eq!(output.locate(3), Some(("main", 8)));
+        assert_eq!(output.locate(4), Some(("main", 14)));
+        assert_eq!(output.locate(5), None);
+
+        for (source, err) in [
+            ("#ifdef A", "main: Missing #endif"),
+            ("\n#endif", "main:2: #endif without #ifdef"),
+            ("#ifdef A\n#else\n#else", "main:3: #else after #else"),
+            ("#include \"b\"", "main:1: Could not find #include \"b\""),
+            ("#include b", "main:1: Expected #include \"name\""),
+            ("#define 1", "main:1: Expected an identifier"),
+            ("#version 450", "main:1: Unknown directive #version"),
+        ] {
+            assert_eq!(
+                Preprocessor::new().process("main", source).unwrap_err(),
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn error_lines() {
+        let preprocessor = Preprocessor::new().with_snippet(
+            "vertex",
+            "struct VertexInput {\n\t@location(0) pos: vec2<f32>,\n};",
+        );
+        let source = "#include \"vertex\"\n\n@vertex\nfn vs_main(vin: VertexInput) -> @builtin(position) vec4<f32> {\n\treturn vec4<f32>(vin.pos, 0.0, 1.0)\n}";
+        let err = preprocessor
+            .process("main.wgsl", source)
+            .unwrap()
+            .check()
+            .unwrap_err();
+        assert_eq!(err, "main.wgsl:6:1: expected ';', found '}'");
+
+        let err = preprocessor
+            .process(
+                "main.wgsl",
+                &source
+                    .replace("@builtin(position) vec4<f32>", "@location(0) vec2<f32>")
+                    .replace("1.0)\n", "1.0);\n"),
+            )
+            .unwrap()
+            .check()
+            .unwrap_err();
+        assert!(
+            err.starts_with("main.wgsl:5:8: Entry point vs_main"),
+            "{err}"
+        );
+    }
+}
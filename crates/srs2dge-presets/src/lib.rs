@@ -1,4 +1,12 @@
+use srs2dge_core::{
+    shader::module::{Preprocessor, ShaderModule},
+    target::Target,
+};
+
+//
+
 pub use colored_2d::*;
+pub use instanced_2d::*;
 pub use line::*;
 pub use sdf::*;
 pub use text::*;
@@ -7,7 +15,517 @@ pub use texture_2d::*;
 //
 
 pub mod colored_2d;
+pub mod instanced_2d;
 pub mod line;
 pub mod sdf;
 pub mod text;
 pub mod texture_2d;
+
+//
+
+/// [`Preprocessor`] with the snippets that
+/// the built in shaders `#include`
+///
+/// Needed to compile the `srs2dge_res::shader::*_SOURCE`
+/// constants with [`ShaderModule::new_wgsl_preprocessed`].
+pub fn preprocessor() -> Preprocessor {
+    Preprocessor::new().with_snippets(srs2dge_res::shader::INCLUDES)
+}
+
+fn built_in<'a>(
+    target: &Target,
+    preprocessor: &Preprocessor,
+    name: &str,
+    source: &str,
+) -> ShaderModule<'a> {
+    ShaderModule::new_wgsl_preprocessed(target, preprocessor, name, source)
+        .unwrap_or_else(|err| panic!("Built in shader compilation failed: {err}"))
+}
+
+//
+
+#[cfg(test)]
+mod test {
+    use super::{
+        Colored2DShader, Instanced2DShader, LineShader, SdfShader, SdfUniform, ShaderModule,
+        TextShader, Texture2DShader,
+    };
+    use srs2dge_core::{
+        batch::{
+            instanced::InstancedQuadRenderer, mesh::Mesh, quad::QuadMesh, textured::TextureBatcher,
+        },
+        buffer::{IndexBuffer, UniformBuffer, VertexBuffer},
+        color::Color,
+        glam::{Mat4, Vec2, Vec3},
+        graph::{GraphTexture, RenderGraph},
+        image::{self, Rgba, RgbaImage},
+        prelude::{DefaultVertex, Layout, TexturePosition},
+        target::Target,
+        testing::GoldenImage,
+        texture::Texture,
+    };
+
+    fn golden(name: &str) -> GoldenImage {
+        GoldenImage::new(format!("{}/golden/{name}.png", env!("CARGO_MANIFEST_DIR")))
+    }
+
+    fn quad(target: &Target, col: Color) -> (VertexBuffer, IndexBuffer) {
+        let quad = QuadMesh::new_centered(
+            Vec2::ZERO,
+            Vec2::new(1.5, 1.5),
+            col,
+            TexturePosition::default(),
+        );
+        let vertices: Vec<DefaultVertex> = quad.vertices().collect();
+        let indices: Vec<u32> = quad.indices(0).collect();
+        (
+            VertexBuffer::new_with(target, &vertices),
+            IndexBuffer::new_with(target, &indices),
+        )
+    }
+
+    fn texture(target: &Target, bytes: &[u8]) -> Texture {
+        Texture::new_rgba_with(target, &image::load_from_memory(bytes).unwrap().to_rgba8())
+    }
+
+    #[test]
+    fn colored_2d() {
+        golden("colored_2d")
+            .check_blocking(|target, frame| {
+                let shader = Colored2DShader::new(target);
+                let (vbo, ibo) = quad(target, Color::ORANGE);
+                let ubo = UniformBuffer::new_single(target, Mat4::IDENTITY);
+                let bind_group = shader.bind_group(&ubo);
+
+                frame
+                    .primary_render_pass()
+                    .bind_vbo(&vbo)
+                    .bind_ibo(&ibo)
+                    .bind_group(&bind_group)
+                    .bind_shader(&shader)
+                    .draw_indexed(0..6, 0, 0..1);
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn custom_without_uniform() {
+        // draws the same as the built in shader with an identity mvp
+        golden("colored_2d")
+            .check_blocking(|target, frame| {
+                let module = super::built_in(
+                    target,
+                    &super::preprocessor(),
+                    "custom.wgsl",
+                    "#include \"include/vertex_input.wgsl\"\n#include \"include/fragment_input.wgsl\"\n@vertex\nfn vs_main(vin: VertexInput) -> FragmentInput {\n\treturn FragmentInput(vec4<f32>(vin.pos, 0.0, 1.0), vin.col, vin.uv);\n}\n@fragment\nfn fs_main(fin: FragmentInput) -> @location(0) vec4<f32> {\n\treturn fin.col;\n}",
+                );
+                let shader =
+                    Colored2DShader::<u32>::new_custom(target, &module, "vs_main", &module, "fs_main");
+                let (vbo, ibo) = quad(target, Color::ORANGE);
+                let ubo = UniformBuffer::new_single(target, Mat4::IDENTITY);
+                let bind_group = shader.bind_group(&ubo);
+
+                frame
+                    .primary_render_pass()
+                    .bind_vbo(&vbo)
+                    .bind_ibo(&ibo)
+                    .bind_group(&bind_group)
+                    .bind_shader(&shader)
+                    .draw_indexed(0..6, 0, 0..1);
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn plain_sources() {
+        // the preprocessed constants compile on their own
+        golden("colored_2d")
+            .check_blocking(|target, frame| {
+                use srs2dge_res::shader::{COLORED_2D, INSTANCED_2D, SDF, TEXT, TEXTURE_2D};
+                for source in [INSTANCED_2D, SDF, TEXT, TEXTURE_2D] {
+                    ShaderModule::new_wgsl_source(target, source.into()).unwrap();
+                }
+
+                let module = ShaderModule::new_wgsl_source(target, COLORED_2D.into()).unwrap();
+                let shader = Colored2DShader::<u32>::new_custom(
+                    target, &module, "vs_main", &module, "fs_main",
+                );
+                let (vbo, ibo) = quad(target, Color::ORANGE);
+                let ubo = UniformBuffer::new_single(target, Mat4::IDENTITY);
+                let bind_group = shader.bind_group(&ubo);
+
+                frame
+                    .primary_render_pass()
+                    .bind_vbo(&vbo)
+                    .bind_ibo(&ibo)
+                    .bind_group(&bind_group)
+                    .bind_shader(&shader)
+                    .draw_indexed(0..6, 0, 0..1);
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn line() {
+        golden("line")
+            .check_blocking(|target, frame| {
+                let shader = LineShader::new(target, true);
+                let vertices = [
+                    DefaultVertex::from_arrays([-0.8, -0.8], [1.0, 0.0, 0.0, 1.0], [0.0, 0.0]),
+                    DefaultVertex::from_arrays([0.8, -0.8], [0.0, 1.0, 0.0, 1.0], [0.0, 0.0]),
+                    DefaultVertex::from_arrays([0.0, 0.8], [0.0, 0.0, 1.0, 1.0], [0.0, 0.0]),
+                ];
+                let vbo = VertexBuffer::new_with(target, &vertices);
+                let ibo = IndexBuffer::new_with(target, &[0u32, 1, 2, 0]);
+                let ubo = UniformBuffer::new_single(target, Mat4::IDENTITY);
+                let bind_group = shader.bind_group(&ubo);
+
+                frame
+                    .primary_render_pass()
+                    .bind_vbo(&vbo)
+                    .bind_ibo(&ibo)
+                    .bind_group(&bind_group)
+                    .bind_shader(&shader)
+                    .draw_indexed(0..4, 0, 0..1);
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn texture_2d() {
+        golden("texture_2d")
+            .with_clear_color(Color::LIGHT_GREY)
+            .check_blocking(|target, frame| {
+                let shader = Texture2DShader::<false>::new(target);
+                let (vbo, ibo) = quad(target, Color::WHITE);
+                let ubo = UniformBuffer::new_single(target, Mat4::IDENTITY);
+                let texture = texture(target, srs2dge_res::texture::RUST);
+                let bind_group = shader.bind_group((&ubo, &texture));
+
+                frame
+                    .primary_render_pass()
+                    .bind_vbo(&vbo)
+                    .bind_ibo(&ibo)
+                    .bind_group(&bind_group)
+                    .bind_shader(&shader)
+                    .draw_indexed(0..6, 0, 0..1);
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn sdf() {
+        // the outline is black, it only shows up on a lighter background
+        sdf_variant(golden("sdf").with_clear_color(Color::LIGHT_GREY), true)
+    }
+
+    #[test]
+    fn sdf_without_outline() {
+        sdf_variant(golden("sdf_without_outline"), false)
+    }
+
+    fn sdf_variant(golden: GoldenImage, outline: bool) {
+        golden
+            .check_blocking(|target, frame| {
+                let shader = if outline {
+                    SdfShader::new(target)
+                } else {
+                    SdfShader::new_without_outline(target)
+                };
+                let (vbo, ibo) = quad(target, Color::AZURE);
+                let ubo = UniformBuffer::new_single(
+                    target,
+                    SdfUniform::new(Mat4::IDENTITY, 0.1, 0.05, 0.2),
+                );
+                let texture = texture(target, srs2dge_res::texture::SDF);
+                let bind_group = shader.bind_group((&ubo, &texture));
+
+                frame
+                    .primary_render_pass()
+                    .bind_vbo(&vbo)
+                    .bind_ibo(&ibo)
+                    .bind_group(&bind_group)
+                    .bind_shader(&shader)
+                    .draw_indexed(0..6, 0, 0..1);
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn text() {
+        golden("text")
+            .check_blocking(|target, frame| {
+                let shader = TextShader::new(target);
+                let (vbo, ibo) = quad(target, Color::MINT);
+                let ubo = UniformBuffer::new_single(target, Mat4::IDENTITY);
+                let texture = texture(target, srs2dge_res::texture::SDF);
+                let bind_group = shader.bind_group((&ubo, &texture));
+
+                frame
+                    .primary_render_pass()
+                    .bind_vbo(&vbo)
+                    .bind_ibo(&ibo)
+                    .bind_group(&bind_group)
+                    .bind_shader(&shader)
+                    .draw_indexed(0..6, 0, 0..1);
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn instanced_2d() {
+        golden("instanced_2d")
+            .check_blocking(|target, frame| {
+                let shader = Instanced2DShader::<false>::new(target);
+                let ubo = UniformBuffer::new_single(target, Mat4::IDENTITY);
+                let texture = texture(target, srs2dge_res::texture::EMPTY);
+                let bind_group = shader.bind_group((&ubo, &texture));
+
+                let mut renderer = InstancedQuadRenderer::new(target);
+                let mut dropped = None;
+                for y in 0..8 {
+                    for x in 0..8 {
+                        let idx = renderer.push_with(QuadMesh::new_top_left(
+                            Vec2::new(x as f32, y as f32) * 0.25 - 1.0,
+                            Vec2::new(0.2, 0.2),
+                            Color::new(x as f32 / 7.0, y as f32 / 7.0, 1.0, 1.0),
+                            TexturePosition::default(),
+                        ));
+                        if x == y {
+                            dropped = Some(idx);
+                        }
+                    }
+                }
+                renderer.drop(dropped.unwrap());
+
+                let (quad, instances, ibo, count) = renderer.generate(target, frame);
+                frame
+                    .primary_render_pass()
+                    .bind_vbo(quad)
+                    .bind_vbo_slot::<1, _, _>(instances)
+                    .bind_ibo(ibo)
+                    .bind_group(&bind_group)
+                    .bind_shader(&shader)
+                    .draw_indexed(0..4, 0, 0..count);
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn instanced_rotation() {
+        // instances and plain quads have to match
+        let golden = golden("instanced_rotation");
+        let quads = [
+            QuadMesh::new_centered(
+                Vec2::new(-0.4, 0.3),
+                Vec2::new(0.8, 0.4),
+                Color::ORANGE,
+                TexturePosition::default(),
+            )
+            .with_rotation(0.5, Vec2::splat(0.5)),
+            QuadMesh::new_top_left(
+                Vec2::new(0.2, -0.6),
+                Vec2::new(0.6, 0.3),
+                Color::CYAN,
+                TexturePosition::default(),
+            )
+            .with_rotation(-1.0, Vec2::ZERO),
+        ];
+
+        golden
+            .check_blocking(|target, frame| {
+                let shader = Instanced2DShader::<false>::new(target);
+                let ubo = UniformBuffer::new_single(target, Mat4::IDENTITY);
+                let texture = texture(target, srs2dge_res::texture::EMPTY);
+                let bind_group = shader.bind_group((&ubo, &texture));
+
+                let mut renderer = InstancedQuadRenderer::new(target);
+                for quad in quads {
+                    renderer.push_with(quad);
+                }
+
+                let (quad, instances, ibo, count) = renderer.generate(target, frame);
+                frame
+                    .primary_render_pass()
+                    .bind_vbo(quad)
+                    .bind_vbo_slot::<1, _, _>(instances)
+                    .bind_ibo(ibo)
+                    .bind_group(&bind_group)
+                    .bind_shader(&shader)
+                    .draw_indexed(0..4, 0, 0..count);
+            })
+            .unwrap();
+
+        golden
+            .check_blocking(|target, frame| {
+                let shader = Texture2DShader::<false>::new(target);
+                let ubo = UniformBuffer::new_single(target, Mat4::IDENTITY);
+                let texture = texture(target, srs2dge_res::texture::EMPTY);
+                let bind_group = shader.bind_group((&ubo, &texture));
+
+                let vertices: Vec<DefaultVertex> =
+                    quads.iter().flat_map(|quad| quad.vertices()).collect();
+                let indices: Vec<u32> = [quads[0].indices(0), quads[1].indices(4)]
+                    .into_iter()
+                    .flatten()
+                    .collect();
+                let vbo = VertexBuffer::new_with(target, &vertices);
+                let ibo = IndexBuffer::new_with(target, &indices);
+
+                frame
+                    .primary_render_pass()
+                    .bind_vbo(&vbo)
+                    .bind_ibo(&ibo)
+                    .bind_group(&bind_group)
+                    .bind_shader(&shader)
+                    .draw_indexed(0..indices.len() as u32, 0, 0..1);
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn render_graph() {
+        golden("render_graph")
+            .with_clear_color(Color::LIGHT_GREY)
+            .check_blocking(|target, frame| {
+                let mut graph = RenderGraph::new();
+                let scene = graph.texture(target.get_format());
+
+                // added before the node it depends on
+                let shader = Texture2DShader::<false>::new(target);
+                let (vbo, ibo) = quad(target, Color::WHITE);
+                let ubo = UniformBuffer::new_single(target, Mat4::from_scale(Vec3::splat(1.2)));
+                graph.add_node(
+                    &[scene],
+                    &[GraphTexture::PRIMARY],
+                    move |_, frame, textures| {
+                        frame
+                            .primary_render_pass()
+                            .bind_vbo(&vbo)
+                            .bind_ibo(&ibo)
+                            .bind_group(&shader.bind_group((&ubo, textures.get(scene))))
+                            .bind_shader(&shader)
+                            .draw_indexed(0..6, 0, 0..1);
+                    },
+                );
+
+                let shader = Colored2DShader::new(target);
+                let (vbo, ibo) = quad(target, Color::ORANGE);
+                let ubo = UniformBuffer::new_single(target, Mat4::from_scale(Vec3::splat(0.5)));
+                graph.add_node(&[], &[scene], move |_, frame, textures| {
+                    frame
+                        .secondary_render_pass(textures.get(scene))
+                        .unwrap()
+                        .bind_vbo(&vbo)
+                        .bind_ibo(&ibo)
+                        .bind_group(&shader.bind_group(&ubo))
+                        .bind_shader(&shader)
+                        .draw_indexed(0..6, 0, 0..1);
+                });
+
+                graph.execute(target, frame);
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn texture_batcher() {
+        golden("texture_batcher")
+            .with_clear_color(Color::LIGHT_GREY)
+            .check_blocking(|target, frame| {
+                let shader = Texture2DShader::<false>::new(target);
+                let ubo = UniformBuffer::new_single(target, Mat4::IDENTITY);
+                let rust = texture(target, srs2dge_res::texture::RUST);
+                let sdf = texture(target, srs2dge_res::texture::SDF);
+                let quad = |x: f32, y: f32| {
+                    QuadMesh::new_centered(
+                        Vec2::new(x, y),
+                        Vec2::new(0.8, 0.8),
+                        Color::WHITE,
+                        TexturePosition::default(),
+                    )
+                };
+
+                let mut batcher = TextureBatcher::new(target);
+                let rust = batcher.add_texture(shader.bind_group((&ubo, &rust)));
+                let sdf = batcher.add_texture(shader.bind_group((&ubo, &sdf)));
+
+                // the later pushed mesh is on a lower layer
+                batcher.push_on_layer(quad(0.2, 0.2), rust, 1);
+                batcher.push_on_layer(quad(-0.2, -0.2), sdf, 0);
+
+                let batch = batcher.generate(target, frame);
+                assert_eq!(batch.draws().len(), 2);
+                batch.draw(frame.primary_render_pass().bind_shader(&shader), 0);
+
+                // interleaved textures are grouped
+                let mut batcher = TextureBatcher::new(target);
+                let a = batcher.add_texture(
+                    shader.bind_group((&ubo, &texture(target, srs2dge_res::texture::EMPTY))),
+                );
+                let b = batcher.add_texture(
+                    shader.bind_group((&ubo, &texture(target, srs2dge_res::texture::EMPTY))),
+                );
+                for texture in [a, b, a, b, a] {
+                    batcher.push_with(quad(0.0, 0.0), texture);
+                }
+                let ranges: Vec<_> = batcher
+                    .generate(target, frame)
+                    .draws()
+                    .iter()
+                    .map(|(range, _)| range.clone())
+                    .collect();
+                assert_eq!(ranges, [0..18, 18..30]);
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn mipmaps() {
+        golden("mipmaps")
+            .with_clear_color(Color::LIGHT_GREY)
+            .check_blocking(|target, frame| {
+                let shader = Texture2DShader::<true>::new(target);
+                let ubo = UniformBuffer::new_single(target, Mat4::IDENTITY);
+                let checker = RgbaImage::from_fn(64, 64, |x, y| {
+                    if (x + y) % 2 == 0 {
+                        Rgba([255, 0, 0, 255])
+                    } else {
+                        Rgba([0, 0, 255, 255])
+                    }
+                });
+                let texture: Texture =
+                    Texture::new_rgba_with(target, &checker).with_mipmaps(target);
+                assert_eq!(texture.get_mip_level_count(), 7);
+                let bind_group = shader.bind_group((&ubo, &texture));
+
+                // the checker averages out to purple instead of shimmering
+                let quad = QuadMesh::new_centered(
+                    Vec2::new(0.0, -0.5),
+                    Vec2::new(0.1, 0.1),
+                    Color::WHITE,
+                    TexturePosition::default(),
+                );
+                let big = QuadMesh::new_centered(
+                    Vec2::new(0.0, 0.5),
+                    Vec2::new(0.5, 0.5),
+                    Color::WHITE,
+                    TexturePosition::default(),
+                );
+                let vertices: Vec<DefaultVertex> = quad.vertices().chain(big.vertices()).collect();
+                let indices: Vec<u32> = quad.indices(0).chain(big.indices(4)).collect();
+                let vbo = VertexBuffer::new_with(target, &vertices);
+                let ibo = IndexBuffer::new_with(target, &indices);
+
+                frame
+                    .primary_render_pass()
+                    .bind_vbo(&vbo)
+                    .bind_ibo(&ibo)
+                    .bind_group(&bind_group)
+                    .bind_shader(&shader)
+                    .draw_indexed(0..6, 0, 0..1)
+                    .draw_indexed(6..12, 0, 0..1);
+            })
+            .unwrap();
+    }
+}
@@ -2,7 +2,7 @@ use srs2dge_core::{
     buffer::{DefaultIndex, DefaultVertex, Index, UniformBuffer},
     glam::Mat4,
     label,
-    shader::{module::ShaderModule, Layout, Shader},
+    shader::{Layout, Shader},
     target::Target,
     wgpu::{
         BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor,
@@ -11,7 +11,6 @@ use srs2dge_core::{
     },
 };
 use std::{
-    borrow::Cow,
     ops::{Deref, DerefMut},
     sync::Arc,
 };
@@ -37,9 +36,12 @@ where
     I: Index,
 {
     pub fn new(target: &Target, strip: bool) -> Self {
-        let module =
-            ShaderModule::new_wgsl_source(target, Cow::Borrowed(srs2dge_res::shader::COLORED_2D))
-                .unwrap_or_else(|err| panic!("{err}"));
+        let module = crate::built_in(
+            target,
+            &crate::preprocessor(),
+            "colored_2d.wgsl",
+            srs2dge_res::shader::COLORED_2D_SOURCE,
+        );
 
         let layout = Self::bind_group_layout(&target.get_device());
 
@@ -48,6 +50,7 @@ where
                 .with_vertex(&module, "vs_main")
                 .with_fragment(&module, "fs_main")
                 .with_format(target.get_format())
+                .with_uniform::<Mat4>(0, 0)
                 .with_baked_layout(PipelineLayoutDescriptor {
                     label: label!(),
                     bind_group_layouts: &[&layout],
@@ -1,5 +1,6 @@
 use bytemuck::{Pod, Zeroable};
 use srs2dge_core::{
+    batch::{mesh::Mesh, quad::QuadMesh},
     buffer::{DefaultIndex, DefaultVertex, Index, UniformBuffer},
     glam::Mat4,
     label,
@@ -14,7 +15,6 @@ use srs2dge_core::{
     },
 };
 use std::{
-    borrow::Cow,
     ops::{Deref, DerefMut},
     sync::Arc,
 };
@@ -90,9 +90,27 @@ impl<I> SdfShader<I>
 where
     I: Index,
 {
+    /// Draws the `border` of [`SdfUniform`] as a black outline
     pub fn new(target: &Target) -> Self {
-        let module = ShaderModule::new_wgsl_source(target, Cow::Borrowed(srs2dge_res::shader::SDF))
-            .unwrap_or_else(|err| panic!("{err}"));
+        Self::new_variant(target, true)
+    }
+
+    /// Ignores the `border` of [`SdfUniform`]
+    pub fn new_without_outline(target: &Target) -> Self {
+        Self::new_variant(target, false)
+    }
+
+    fn new_variant(target: &Target, outline: bool) -> Self {
+        let mut preprocessor = crate::preprocessor();
+        if outline {
+            preprocessor = preprocessor.with_flag("OUTLINE");
+        }
+        let module: ShaderModule = crate::built_in(
+            target,
+            &preprocessor,
+            "sdf.wgsl",
+            srs2dge_res::shader::SDF_SOURCE,
+        );
 
         let layout = Self::bind_group_layout(&target.get_device());
 
@@ -116,12 +134,14 @@ where
                 .with_vertex(&module, "vs_main")
                 .with_fragment(&module, "fs_main")
                 .with_format(target.get_format())
+                .with_uniform::<SdfUniform>(0, 0)
                 .with_baked_layout(PipelineLayoutDescriptor {
                     label: label!(),
                     bind_group_layouts: &[&layout],
                     push_constant_ranges: &[],
                 })
                 .with_label(label!())
+                .with_topology(QuadMesh::PRIM)
                 .build(target),
             layout,
             sampler,
@@ -4,10 +4,7 @@ use srs2dge_core::{
     shader::module::ShaderModule,
     target::Target,
 };
-use std::{
-    borrow::Cow,
-    ops::{Deref, DerefMut},
-};
+use std::ops::{Deref, DerefMut};
 
 //
 
@@ -30,7 +27,7 @@ where
     pub fn new(target: &Target) -> Self {
         let module = Self::built_in(target);
         Self {
-            inner: Internal::new_custom(target, &module, "vs_main", &module, "fs_main"),
+            inner: Internal::new_with(target, (&module, "vs_main"), (&module, "fs_main"), true),
         }
     }
 
@@ -55,8 +52,12 @@ where
     }
 
     pub fn built_in(target: &Target) -> ShaderModule {
-        ShaderModule::new_wgsl_source(target, Cow::Borrowed(srs2dge_res::shader::TEXT))
-            .unwrap_or_else(|err| panic!("Built in shader compilation failed: {err}"))
+        crate::built_in(
+            target,
+            &crate::preprocessor(),
+            "text.wgsl",
+            srs2dge_res::shader::TEXT_SOURCE,
+        )
     }
 }
 
@@ -1,19 +1,19 @@
 use srs2dge_core::{
+    batch::{mesh::Mesh, quad::QuadMesh},
     buffer::{DefaultIndex, DefaultVertex, Index, UniformBuffer},
     glam::Mat4,
     label,
     shader::{module::ShaderModule, Layout, Shader},
     target::Target,
+    texture::sampler::Sampler,
     wgpu::{
-        AddressMode, BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout,
-        BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingResource, BindingType,
-        BufferBindingType, Device, FilterMode, PipelineLayoutDescriptor, Sampler,
-        SamplerBindingType, SamplerDescriptor, ShaderStages, TextureSampleType, TextureView,
+        BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor,
+        BindGroupLayoutEntry, BindingResource, BindingType, BufferBindingType, Device,
+        PipelineLayoutDescriptor, SamplerBindingType, ShaderStages, TextureSampleType, TextureView,
         TextureViewDimension,
     },
 };
 use std::{
-    borrow::Cow,
     ops::{Deref, DerefMut},
     sync::Arc,
 };
@@ -42,7 +42,7 @@ where
 {
     pub fn new(target: &Target) -> Self {
         let module = Self::built_in(target);
-        Self::new_custom(target, &module, "vs_main", &module, "fs_main")
+        Self::new_with(target, (&module, "vs_main"), (&module, "fs_main"), true)
     }
 
     pub fn new_custom_vert(
@@ -66,50 +66,65 @@ where
     }
 
     pub fn built_in(target: &Target) -> ShaderModule {
-        ShaderModule::new_wgsl_source(target, Cow::Borrowed(srs2dge_res::shader::TEXTURE_2D))
-            .unwrap_or_else(|err| panic!("Built in shader compilation failed: {err}"))
+        crate::built_in(
+            target,
+            &crate::preprocessor(),
+            "texture_2d.wgsl",
+            srs2dge_res::shader::TEXTURE_2D_SOURCE,
+        )
     }
 
+    /// Custom modules don't have to declare the
+    /// uniform, so its size is not checked
     pub fn new_custom(
         target: &Target,
         vert_module: &ShaderModule,
         vert_entry: &str,
         frag_module: &ShaderModule,
         frag_entry: &str,
+    ) -> Self {
+        Self::new_with(
+            target,
+            (vert_module, vert_entry),
+            (frag_module, frag_entry),
+            false,
+        )
+    }
+
+    pub(crate) fn new_with(
+        target: &Target,
+        (vert_module, vert_entry): (&ShaderModule, &str),
+        (frag_module, frag_entry): (&ShaderModule, &str),
+        check_uniform: bool,
     ) -> Self {
         let layout = Self::bind_group_layout(&target.get_device());
 
-        let filter = if FILTER {
-            FilterMode::Linear
+        // linear also blends between mip levels
+        let sampler = if FILTER {
+            Sampler::linear(target)
         } else {
-            FilterMode::Nearest
+            Sampler::nearest(target)
+        };
+
+        let builder = Shader::builder()
+            .with_vertex(vert_module, vert_entry)
+            .with_fragment(frag_module, frag_entry)
+            .with_format(target.get_format());
+        let builder = if check_uniform {
+            builder.with_uniform::<Mat4>(0, 0)
+        } else {
+            builder
         };
-        let sampler = target.get_device().create_sampler(&SamplerDescriptor {
-            label: label!(),
-            address_mode_u: AddressMode::ClampToEdge,
-            address_mode_v: AddressMode::ClampToEdge,
-            address_mode_w: AddressMode::ClampToEdge,
-            mag_filter: filter,
-            min_filter: filter,
-            mipmap_filter: filter,
-            lod_min_clamp: 0.0,
-            lod_max_clamp: f32::MAX,
-            compare: None,
-            anisotropy_clamp: None,
-            border_color: None,
-        });
 
         Self {
-            inner: Shader::builder()
-                .with_vertex(vert_module, vert_entry)
-                .with_fragment(frag_module, frag_entry)
-                .with_format(target.get_format())
+            inner: builder
                 .with_baked_layout(PipelineLayoutDescriptor {
                     label: label!(),
                     bind_group_layouts: &[&layout],
                     push_constant_ranges: &[],
                 })
                 .with_label(label!())
+                .with_topology(QuadMesh::PRIM)
                 .build(target),
             layout,
             sampler,
@@ -0,0 +1,44 @@
+use srs2dge_preprocess::Preprocessor;
+use std::{env, fs, path::Path};
+
+//
+
+/// Shaders exported as plain WGSL and the flags they are built with
+const SHADERS: &[(&str, &[&str])] = &[
+    ("colored_2d", &[]),
+    ("instanced_2d", &[]),
+    ("sdf", &["OUTLINE"]),
+    ("text", &[]),
+    ("texture_2d", &[]),
+];
+
+//
+
+fn main() {
+    let out_dir = env::var("OUT_DIR").unwrap();
+
+    for (name, flags) in SHADERS {
+        let path = format!("res/shader/{name}.wgsl");
+        let source = fs::read_to_string(&path).unwrap();
+
+        let preprocessed = flags
+            .iter()
+            .fold(
+                Preprocessor::new().with_include_dir("res/shader"),
+                |preprocessor, flag| preprocessor.with_flag(*flag),
+            )
+            .process(&path, &source)
+            .unwrap_or_else(|err| panic!("{err}"));
+
+        println!("cargo:rerun-if-changed={path}");
+        for dependency in preprocessed.dependencies() {
+            println!("cargo:rerun-if-changed={}", dependency.display());
+        }
+
+        fs::write(
+            Path::new(&out_dir).join(format!("{name}.wgsl")),
+            preprocessed.source(),
+        )
+        .unwrap();
+    }
+}
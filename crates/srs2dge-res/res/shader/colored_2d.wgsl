@@ -1,27 +1,13 @@
-struct VertexInput {
-	@location(0) pos: vec2<f32>,
-	@location(1) uv: vec2<f32>,
-	@location(2) col: vec4<f32>,
-};
-
-struct FragmentInput {
-	@builtin(position) pos: vec4<f32>,
-	@location(0) col: vec4<f32>,
-};
-
-struct UniformInput {
-	mvp: mat4x4<f32>
-};
-
-@group(0)
-@binding(0)
-var<uniform> ubo: UniformInput;
+#include "include/vertex_input.wgsl"
+#include "include/fragment_input.wgsl"
+#include "include/mvp.wgsl"
 
 @vertex
 fn vs_main(vin: VertexInput) -> FragmentInput {
 	var fin: FragmentInput;
 	fin.pos = ubo.mvp * vec4<f32>(vin.pos, 0.0, 1.0);
 	fin.col = vin.col;
+	fin.uv = vin.uv;
 	return fin;
 }
 
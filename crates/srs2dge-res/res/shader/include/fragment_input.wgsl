@@ -0,0 +1,5 @@
+struct FragmentInput {
+	@builtin(position) pos: vec4<f32>,
+	@location(0) col: vec4<f32>,
+	@location(1) uv: vec2<f32>,
+};
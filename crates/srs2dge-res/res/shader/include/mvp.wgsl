@@ -0,0 +1,7 @@
+struct UniformInput {
+	mvp: mat4x4<f32>,
+};
+
+@group(0)
+@binding(0)
+var<uniform> ubo: UniformInput;
@@ -0,0 +1,7 @@
+@group(0)
+@binding(1)
+var t_texture: texture_2d<f32>;
+
+@group(0)
+@binding(2)
+var s_texture: sampler;
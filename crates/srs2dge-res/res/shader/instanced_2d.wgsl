@@ -0,0 +1,34 @@
+#include "include/vertex_input.wgsl"
+
+struct InstanceInput {
+	@location(3) pos: vec2<f32>,
+	@location(4) size: vec2<f32>,
+	@location(5) col: vec4<f32>,
+	@location(6) tex: vec4<f32>,
+	@location(7) pivot: vec2<f32>,
+	@location(8) rotation: f32,
+};
+
+#include "include/fragment_input.wgsl"
+#include "include/mvp.wgsl"
+#include "include/texture.wgsl"
+
+@vertex
+fn vs_main(vin: VertexInput, iin: InstanceInput) -> FragmentInput {
+	var fin: FragmentInput;
+	// counter clockwise around `pos + pivot * size`
+	let pivot = iin.pivot * iin.size;
+	let local = vin.pos * iin.size - pivot;
+	let c = cos(iin.rotation);
+	let s = sin(iin.rotation);
+	let rotated = vec2<f32>(c * local.x - s * local.y, s * local.x + c * local.y);
+	fin.pos = ubo.mvp * vec4<f32>(iin.pos + pivot + rotated, 0.0, 1.0);
+	fin.col = vin.col * iin.col;
+	fin.uv = mix(iin.tex.xy, iin.tex.zw, vin.uv);
+	return fin;
+}
+
+@fragment
+fn fs_main(fin: FragmentInput) -> @location(0) vec4<f32> {
+	return textureSample(t_texture, s_texture, fin.uv) * fin.col;
+}
@@ -1,14 +1,5 @@
-struct VertexInput {
-	@location(0) pos: vec2<f32>,
-	@location(1) uv: vec2<f32>,
-	@location(2) col: vec4<f32>,
-};
-
-struct FragmentInput {
-	@builtin(position) pos: vec4<f32>,
-	@location(0) col: vec4<f32>,
-	@location(1) uv: vec2<f32>,
-};
+#include "include/vertex_input.wgsl"
+#include "include/fragment_input.wgsl"
 
 struct UniformInput {
 	mvp: mat4x4<f32>,
@@ -21,13 +12,7 @@ struct UniformInput {
 @binding(0)
 var<uniform> ubo: UniformInput;
 
-@group(0)
-@binding(1)
-var t_texture: texture_2d<f32>;
-
-@group(0)
-@binding(2)
-var s_texture: sampler;
+#include "include/texture.wgsl"
 
 @vertex
 fn vs_main(vin: VertexInput) -> FragmentInput {
@@ -63,16 +48,22 @@ fn fs_main(fin: FragmentInput) -> @location(0) vec4<f32> {
 
 	// smooth edges
 	let aa = ubo.anti_alias;
-	// border begin weight
-	let bbw = -ubo.weight - ubo.border;
 	// border end weigth
 	let bew = -ubo.weight;
 
+#ifdef OUTLINE
+	// border begin weight
+	let bbw = -ubo.weight - ubo.border;
+
 	// border
 	let alpha = smoothstep(bbw, bbw + aa, val);
 
 	// outline
 	let col = vec3<f32>(smoothstep(bew, bew + aa, val));
 	return fin.col * vec4<f32>(col, alpha);
+#else
+	let alpha = smoothstep(bew, bew + aa, val);
+	return fin.col * vec4<f32>(1.0, 1.0, 1.0, alpha);
+#endif
 }
 
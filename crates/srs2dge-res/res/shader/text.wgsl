@@ -1,30 +1,7 @@
-struct VertexInput {
-	@location(0) pos: vec2<f32>,
-	@location(1) uv: vec2<f32>,
-	@location(2) col: vec4<f32>,
-};
-
-struct FragmentInput {
-	@builtin(position) pos: vec4<f32>,
-	@location(0) col: vec4<f32>,
-	@location(1) uv: vec2<f32>,
-};
-
-struct UniformInput {
-	mvp: mat4x4<f32>,
-};
-
-@group(0)
-@binding(0)
-var<uniform> ubo: UniformInput;
-
-@group(0)
-@binding(1)
-var t_texture: texture_2d<f32>;
-
-@group(0)
-@binding(2)
-var s_texture: sampler;
+#include "include/vertex_input.wgsl"
+#include "include/fragment_input.wgsl"
+#include "include/mvp.wgsl"
+#include "include/texture.wgsl"
 
 @vertex
 fn vs_main(vin: VertexInput) -> FragmentInput {
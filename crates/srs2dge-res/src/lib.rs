@@ -3,11 +3,60 @@ pub mod font {
     pub const ROBOTO: &[u8] = include_bytes!("../res/font/roboto/font.ttf");
 }
 
+/// Built in shader sources
+///
+/// The `*_SOURCE` constants are not plain WGSL, they
+/// `#include` the [`INCLUDES`] snippets and `sdf.wgsl`
+/// has an `OUTLINE` variant. Run them through
+/// `srs2dge_presets::preprocessor()` before compiling.
+///
+/// The constants without the suffix are the same
+/// shaders preprocessed at build time, [`SDF`]
+/// with the outline.
 pub mod shader {
-    pub const COLORED_2D: &str = include_str!("../res/shader/colored_2d.wgsl");
-    pub const SDF: &str = include_str!("../res/shader/sdf.wgsl");
-    pub const TEXT: &str = include_str!("../res/shader/text.wgsl");
-    pub const TEXTURE_2D: &str = include_str!("../res/shader/texture_2d.wgsl");
+    pub const COLORED_2D: &str = include_str!(concat!(env!("OUT_DIR"), "/colored_2d.wgsl"));
+    pub const INSTANCED_2D: &str = include_str!(concat!(env!("OUT_DIR"), "/instanced_2d.wgsl"));
+    pub const SDF: &str = include_str!(concat!(env!("OUT_DIR"), "/sdf.wgsl"));
+    pub const TEXT: &str = include_str!(concat!(env!("OUT_DIR"), "/text.wgsl"));
+    pub const TEXTURE_2D: &str = include_str!(concat!(env!("OUT_DIR"), "/texture_2d.wgsl"));
+
+    pub const COLORED_2D_SOURCE: &str = include_str!("../res/shader/colored_2d.wgsl");
+    pub const INSTANCED_2D_SOURCE: &str = include_str!("../res/shader/instanced_2d.wgsl");
+    pub const SDF_SOURCE: &str = include_str!("../res/shader/sdf.wgsl");
+    pub const TEXT_SOURCE: &str = include_str!("../res/shader/text.wgsl");
+    pub const TEXTURE_2D_SOURCE: &str = include_str!("../res/shader/texture_2d.wgsl");
+
+    /// Snippets the shaders `#include`
+    pub const INCLUDES: &[(&str, &str)] = &[
+        (
+            "include/fragment_input.wgsl",
+            include_str!("../res/shader/include/fragment_input.wgsl"),
+        ),
+        (
+            "include/mvp.wgsl",
+            include_str!("../res/shader/include/mvp.wgsl"),
+        ),
+        (
+            "include/texture.wgsl",
+            include_str!("../res/shader/include/texture.wgsl"),
+        ),
+        (
+            "include/vertex_input.wgsl",
+            include_str!("../res/shader/include/vertex_input.wgsl"),
+        ),
+    ];
+
+    /// Source file locations for `ShaderWatcher`
+    pub mod path {
+        pub const COLORED_2D: &str =
+            concat!(env!("CARGO_MANIFEST_DIR"), "/res/shader/colored_2d.wgsl");
+        pub const INSTANCED_2D: &str =
+            concat!(env!("CARGO_MANIFEST_DIR"), "/res/shader/instanced_2d.wgsl");
+        pub const SDF: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/res/shader/sdf.wgsl");
+        pub const TEXT: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/res/shader/text.wgsl");
+        pub const TEXTURE_2D: &str =
+            concat!(env!("CARGO_MANIFEST_DIR"), "/res/shader/texture_2d.wgsl");
+    }
 }
 
 pub mod texture {
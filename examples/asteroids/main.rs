@@ -52,7 +52,7 @@ impl App {
         let old_cursor_pos = ws.cursor_pos;
 
         let mut batcher = Some(BatchRenderer::new(&target));
-        let shader = LineShader::new(&target, true);
+        let shader = LineShader::new(&target, false);
         let ubo = UniformBuffer::new(&target, 1);
 
         let mut world = World::new()
@@ -28,7 +28,7 @@ impl Default for AsteroidMesh {
 }
 
 impl Mesh<DefaultVertex> for AsteroidMesh {
-    const PRIM: PrimitiveTopology = PrimitiveTopology::LineStrip;
+    const PRIM: PrimitiveTopology = PrimitiveTopology::LineList;
 
     // TODO : #![feature(type_alias_impl_trait)]
     type VertexIter = Box<dyn Iterator<Item = DefaultVertex>>;
@@ -56,7 +56,8 @@ impl Mesh<DefaultVertex> for AsteroidMesh {
     }
 
     fn indices(&self, offset: u32) -> Self::IndexIter {
-        Box::new((0..RES).chain([0]).map(move |i| i + offset).chain([!0]))
+        // every edge as its own line
+        Box::new((0..RES * 2).map(move |i| (i + 1) / 2 % RES + offset))
     }
 
     fn index_step(&self) -> u32 {
@@ -19,7 +19,7 @@ pub enum MultiMesh {
 //
 
 impl Mesh<DefaultVertex> for MultiMesh {
-    const PRIM: PrimitiveTopology = PrimitiveTopology::LineStrip;
+    const PRIM: PrimitiveTopology = PrimitiveTopology::LineList;
 
     type VertexIter = IterEnum<
         <PlayerMesh as Mesh<DefaultVertex>>::VertexIter,
@@ -12,11 +12,11 @@ pub struct PlayerMesh {
 //
 
 impl Mesh<DefaultVertex> for PlayerMesh {
-    const PRIM: PrimitiveTopology = PrimitiveTopology::LineStrip;
+    const PRIM: PrimitiveTopology = PrimitiveTopology::LineList;
 
     // TODO : #![feature(type_alias_impl_trait)]
     type VertexIter = IntoIter<DefaultVertex, 4>;
-    type IndexIter = IntoIter<DefaultIndex, 6>;
+    type IndexIter = IntoIter<DefaultIndex, 8>;
 
     fn vertices(&self) -> Self::VertexIter {
         let mat = Mat2::from_scale_angle(self.lerp_transform.scale, self.lerp_transform.rotation);
@@ -34,7 +34,8 @@ impl Mesh<DefaultVertex> for PlayerMesh {
     }
 
     fn indices(&self, offset: u32) -> Self::IndexIter {
-        IntoIterator::into_iter([offset, offset + 1, offset + 2, offset + 3, offset, !0])
+        // every edge as its own line
+        IntoIterator::into_iter([0, 1, 1, 2, 2, 3, 3, 0].map(|i| i + offset))
     }
 
     fn index_step(&self) -> u32 {
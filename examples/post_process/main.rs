@@ -8,24 +8,11 @@ const POST_PROCESSOR: &str = include_str!("main.wgsl");
 
 struct App {
     target: Target,
-    secondary_target: RenderTargetTexture,
 
     ws: WindowState,
 
-    // logo
-    texture: Texture,
-
-    // logo quad and screen quad
-    vbo: VertexBuffer,
-    ibo: IndexBuffer,
-
-    // drawing to texture
-    ubo: UniformBuffer<Mat4>,
-    shader: Texture2DShader,
-
-    // drawing to screen
-    identity_ubo: UniformBuffer<Mat4>,
-    custom_shader: Texture2DShader,
+    // offscreen scene and the post processing pass
+    graph: RenderGraph,
 }
 
 //
@@ -36,138 +23,122 @@ impl App {
         let target = engine.new_target_default(target).await.unwrap();
 
         let ws = WindowState::new(&target.get_window().unwrap());
-        let secondary_target = RenderTargetTexture::new_format(
-            &target,
-            Rect::new(ws.size.width, ws.size.height),
-            target.get_format(), // secondary target format set to same as primary target format to be able to use a single pipeline
-        );
 
-        let texture = Texture::new_rgba_with(
+        let mut graph = RenderGraph::new();
+        // secondary target format set to same as primary target format to be able to use a single pipeline
+        let scene = graph.texture(target.get_format());
+
+        // logo quad drawn to the scene texture
+        let texture: Texture = Texture::new_rgba_with(
             &target,
             &image::load_from_memory(res::texture::RUST)
                 .unwrap()
                 .to_rgba8(),
         );
-
-        let quad_a = QuadMesh::new_centered(
-            Vec2::new(-0.5, -0.5), // bottom left
-            Vec2::new(1.0, 1.0),
-            Color::WHITE,
-            TexturePosition::default(),
-        );
-        let quad_b = QuadMesh::new_centered(
-            Vec2::new(0.0, 0.0),
-            Vec2::new(2.0, 2.0),
-            Color::WHITE,
-            TexturePosition::default(),
-        );
-        let vbo = VertexBuffer::new_with(
-            &target,
-            &[quad_a, quad_b]
-                .into_iter()
-                .flat_map(|q| q.vertices())
-                .collect::<Box<_>>(),
-        );
-        let mut i = 0;
-        let ibo = IndexBuffer::new_with(
+        let (vbo, ibo) = quad(
             &target,
-            &[quad_a, quad_b]
-                .into_iter()
-                .flat_map(|q| {
-                    let offset = i;
-                    i += q.index_step();
-                    q.indices(offset)
-                })
-                .collect::<Box<_>>(),
+            QuadMesh::new_centered(
+                Vec2::new(-0.5, -0.5), // bottom left
+                Vec2::new(1.0, 1.0),
+                Color::WHITE,
+                TexturePosition::default(),
+            ),
         );
         let ubo = UniformBuffer::new(&target, 1);
-        let shader = Texture2DShader::new(&target);
+        let shader: Texture2DShader = Texture2DShader::new(&target);
+        let bind_group = shader.bind_group((&ubo, &texture));
+        graph.add_node(&[], &[scene], move |target, frame, textures| {
+            let (width, height) = frame.get_dim();
+            let aspect = width as f32 / height as f32;
+            ubo.upload(
+                target,
+                frame,
+                &[Mat4::orthographic_rh(
+                    -aspect, aspect, -1.0, 1.0, -100.0, 100.0,
+                )],
+            );
 
+            frame
+                .secondary_render_pass(textures.get(scene))
+                .unwrap()
+                .bind_vbo(&vbo)
+                .bind_ibo(&ibo)
+                .bind_group(&bind_group)
+                .bind_shader(&shader)
+                .draw_indexed(0..ibo.capacity() as _, 0, 0..1);
+        });
+
+        // screen quad drawn with the scene texture
+        let (vbo, ibo) = quad(
+            &target,
+            QuadMesh::new_centered(
+                Vec2::new(0.0, 0.0),
+                Vec2::new(2.0, 2.0),
+                Color::WHITE,
+                TexturePosition::default(),
+            ),
+        );
         let identity_ubo = UniformBuffer::new_single(&target, Mat4::IDENTITY);
         let custom_frag = ShaderModule::new_wgsl_source(&target, POST_PROCESSOR.into())
             .unwrap_or_else(|err| panic!("Custom module compilation failed: {err}"));
-        let custom_shader = Texture2DShader::new_custom_frag(&target, &custom_frag, "main")
-            .unwrap_or_else(|err| panic!("Custom module incompatible: {err}"));
-
-        Self {
-            target,
-            secondary_target,
-
-            ws,
-
-            texture,
-
-            vbo,
-            ibo,
-            ubo,
-            shader,
+        let custom_shader: Texture2DShader =
+            Texture2DShader::new_custom_frag(&target, &custom_frag, "main")
+                .unwrap_or_else(|err| panic!("Custom module incompatible: {err}"));
+        // the scene texture is recreated when the window is resized
+        let mut cached = None;
+        graph.add_node(
+            &[scene],
+            &[GraphTexture::PRIMARY],
+            move |_, frame, textures| {
+                let dim = textures.get_dim();
+                if !matches!(&cached, Some((cached_dim, _)) if *cached_dim == dim) {
+                    let bind_group = custom_shader.bind_group((&identity_ubo, textures.get(scene)));
+                    cached = Some((dim, bind_group));
+                }
+                let (_, bind_group) = cached.as_ref().unwrap();
+
+                frame
+                    .primary_render_pass()
+                    .bind_vbo(&vbo)
+                    .bind_ibo(&ibo)
+                    .bind_group(bind_group)
+                    .bind_shader(&custom_shader)
+                    .draw_indexed(0..ibo.capacity() as _, 0, 0..1);
+            },
+        );
 
-            identity_ubo,
-            custom_shader,
-        }
+        Self { target, ws, graph }
     }
 }
 
 impl Runnable for App {
     fn event(&mut self, event: Event, _: &EventLoopTarget, control: &mut ControlFlow) {
-        let old = self.ws.size;
         self.ws.event(&event);
-        let changed = self.ws.size != old;
 
         if self.ws.should_close {
             *control = ControlFlow::Exit;
         }
-
-        if changed {
-            self.secondary_target = RenderTargetTexture::new_format(
-                &self.target,
-                self.ws.size.into(),
-                self.target.get_format(),
-            );
-        }
     }
 
     fn draw(&mut self) {
         let mut frame = self.target.get_frame();
-
-        self.ubo.upload(
-            &mut self.target,
-            &mut frame,
-            &[Mat4::orthographic_rh(
-                -self.ws.aspect,
-                self.ws.aspect,
-                -1.0,
-                1.0,
-                -100.0,
-                100.0,
-            )],
-        );
-
-        frame
-            .secondary_render_pass(&self.secondary_target)
-            .unwrap()
-            .bind_vbo(&self.vbo)
-            .bind_ibo(&self.ibo)
-            .bind_group(&self.shader.bind_group((&self.ubo, &self.texture)))
-            .bind_shader(&self.shader)
-            .draw_indexed(0..5, 0, 0..1);
-
-        frame
-            .primary_render_pass()
-            .bind_vbo(&self.vbo)
-            .bind_ibo(&self.ibo)
-            .bind_group(
-                &self
-                    .shader
-                    .bind_group((&self.identity_ubo, &self.secondary_target)),
-            )
-            .bind_shader(&self.custom_shader)
-            .draw_indexed(4..9, 0, 0..1);
-
+        // the scene texture follows the window size
+        self.graph.execute(&mut self.target, &mut frame);
         self.target.finish_frame(frame);
     }
 }
 
 //
 
+fn quad(target: &Target, quad: QuadMesh) -> (VertexBuffer, IndexBuffer) {
+    let vertices: Vec<DefaultVertex> = quad.vertices().collect();
+    let indices: Vec<u32> = quad.indices(0).collect();
+    (
+        VertexBuffer::new_with(target, &vertices),
+        IndexBuffer::new_with(target, &indices),
+    )
+}
+
+//
+
 main_app!(async App);
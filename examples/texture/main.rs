@@ -88,7 +88,7 @@ impl Runnable for App {
             .bind_ibo(&self.ibo)
             .bind_group(&self.shader.bind_group((&self.ubo, &self.texture)))
             .bind_shader(&self.shader)
-            .draw_indexed(0..4, 0, 0..1);
+            .draw_indexed(0..self.ibo.capacity() as _, 0, 0..1);
 
         self.target.finish_frame(frame);
     }